@@ -49,16 +49,19 @@ export const TABLE_EVENT = 0;
 // Table type amortization.
 export const TABLE_AM = 1;
 
+// Column formats (defined by the wasm engine).
+const { ColumnFormat } = window.wasm_bindgen;
+
 // Format - string.
-export const FORMAT_STRING = 0;
+export const FORMAT_STRING = ColumnFormat.String;
 // Format - date.
-export const FORMAT_DATE = 1;
+export const FORMAT_DATE = ColumnFormat.Date;
 // Format - integer.
-export const FORMAT_INTEGER = 2;
+export const FORMAT_INTEGER = ColumnFormat.Integer;
 // Format - decimal.
-export const FORMAT_DECIMAL = 3;
+export const FORMAT_DECIMAL = ColumnFormat.Decimal;
 // Format - currency.
-export const FORMAT_CURRENCY = 4;
+export const FORMAT_CURRENCY = ColumnFormat.Currency;
 
 // Column field names
 export const FIELD_TYPE = "Type";
@@ -68,6 +71,7 @@ export const FIELD_VALUE = "Value";
 export const FIELD_PERIODS = "Periods";
 export const FIELD_FREQUENCY = "Frequency";
 export const FIELD_SKIP_PERIODS = "Skip-periods";
+export const FIELD_SCHEDULE = "Schedule";
 export const FIELD_PARAMETERS = "Parameter-list";
 export const FIELD_DESCRIPTORS = "Descriptor-list";
 
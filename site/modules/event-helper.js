@@ -170,7 +170,10 @@ export function eventValueChanged(self, e) {
     let tokens = self.engine.set_event_value(
         colDef.col_name_index, colDef.col_type, colDef.code, 
         self.activeTabIndex, e.rowIndex, e.newValue).split('|');
-    if (tokens.length !== 3) return;
+    if (tokens.length !== 3) {
+        if (tokens[0].length > 0) toaster.toastError(tokens[0]);
+        return;
+    }
 
     let eventDate = tokens[0];
     let sortOrder = parseInt(tokens[1]);
@@ -186,6 +189,9 @@ export function eventValueChanged(self, e) {
             sortOrder = value;
             refreshEvts = true;
             break;
+        case constant.FIELD_SCHEDULE:
+            refreshEvts = true;
+            break;
     }
 
     if (refreshEvts) {
@@ -0,0 +1,317 @@
+//! Interest allocation methods (actuarial and Rule of 78s).
+// Copyright (c) 2021 ShiftLeft Software
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use rust_decimal::prelude::*;
+
+use amfnengine::*;
+
+use crate::effective_interest::*;
+
+/// Cashflow preferences descriptor group for the interest allocation method.
+pub const GROUP_ALLOCATION: &str = "Allocation";
+/// Cashflow preferences descriptor name for the interest allocation method.
+pub const NAME_ALLOCATION_METHOD: &str = "Method";
+
+/// Interest allocation method.
+#[derive(Clone, Copy, PartialEq)]
+pub enum AllocationMethod {
+    /// Interest earned on the outstanding balance of each period.
+    Actuarial,
+    /// Precomputed interest earned by the sum of the digits
+    /// of the remaining payments (Rule of 78s).
+    RuleOf78s,
+}
+
+/// Interest allocation row (one for each payment).
+pub struct ElemAllocationRow {
+    /// Date of the payment (YYYYMMDD).
+    event_date: usize,
+    /// Payment amount.
+    payment: Decimal,
+    /// Interest earned by the payment.
+    interest: Decimal,
+    /// Principal repaid by the payment.
+    principal: Decimal,
+    /// Principal balance after the payment.
+    balance: Decimal,
+}
+
+/// Interest allocation row implementation.
+impl ElemAllocationRow {
+    /// Get the date of the payment.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn event_date(&self) -> usize {
+        self.event_date
+    }
+
+    /// Get the payment amount.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn payment(&self) -> Decimal {
+        self.payment
+    }
+
+    /// Get the interest earned by the payment.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn interest(&self) -> Decimal {
+        self.interest
+    }
+
+    /// Get the principal repaid by the payment.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn principal(&self) -> Decimal {
+        self.principal
+    }
+
+    /// Get the principal balance after the payment.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn balance(&self) -> Decimal {
+        self.balance
+    }
+}
+
+/// Early payoff rebate as of a date.
+pub struct ElemPayoffRebate {
+    /// Number of payments made on or before the date.
+    payments_made: usize,
+    /// Number of payments remaining.
+    payments_remaining: usize,
+    /// Total finance charge (total of payments less the amount financed).
+    finance_charge: Decimal,
+    /// Interest earned through the payments made.
+    earned_interest: Decimal,
+    /// Unearned interest rebated.
+    rebate: Decimal,
+    /// Payoff amount (remaining payments less the rebate).
+    payoff: Decimal,
+}
+
+/// Early payoff rebate implementation.
+impl ElemPayoffRebate {
+    /// Get the number of payments made.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn payments_made(&self) -> usize {
+        self.payments_made
+    }
+
+    /// Get the number of payments remaining.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn payments_remaining(&self) -> usize {
+        self.payments_remaining
+    }
+
+    /// Get the total finance charge.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn finance_charge(&self) -> Decimal {
+        self.finance_charge
+    }
+
+    /// Get the interest earned.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn earned_interest(&self) -> Decimal {
+        self.earned_interest
+    }
+
+    /// Get the unearned interest rebated.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn rebate(&self) -> Decimal {
+        self.rebate
+    }
+
+    /// Get the payoff amount.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn payoff(&self) -> Decimal {
+        self.payoff
+    }
+}
+
+/// Interest allocation utility.
+pub struct AllocationUtility {}
+
+/// Interest allocation utility implementation.
+impl AllocationUtility {
+    /// Parse an interest allocation method mnemonic.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The mnemonic (actuarial or rule-of-78s).
+    ///
+    /// # Return
+    ///
+    /// * See description or None if not recognized.
+
+    pub fn parse_method(value: &str) -> Option<AllocationMethod> {
+        match value.trim().to_lowercase().as_str() {
+            "actuarial" => Some(AllocationMethod::Actuarial),
+            "rule-of-78s" | "rule-of-78" | "78s" => Some(AllocationMethod::RuleOf78s),
+            _ => None,
+        }
+    }
+
+    /// Return the mnemonic of an interest allocation method.
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - The interest allocation method.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn method_mnemonic(method: AllocationMethod) -> &'static str {
+        match method {
+            AllocationMethod::Actuarial => "actuarial",
+            AllocationMethod::RuleOf78s => "rule-of-78s",
+        }
+    }
+
+    /// Allocate the finance charge of a precomputed loan to its
+    /// payments. The amount financed is the initial disbursement
+    /// and the payments are the cash flows that follow it.
+    ///
+    /// # Arguments
+    ///
+    /// * `flows` - The dated cash flows (see ElemCashFlow::from_rows).
+    /// * `method` - The interest allocation method.
+    /// * `decimal_digits` - Number of decimal digits for rounding.
+    ///
+    /// # Return
+    ///
+    /// * One row for each payment; the final row absorbs rounding.
+
+    pub fn schedule(
+        flows: &[ElemCashFlow],
+        method: AllocationMethod,
+        decimal_digits: u32,
+    ) -> Vec<ElemAllocationRow> {
+        let amount_financed = match flows.first() {
+            None => return Vec::new(),
+            Some(o) => -o.cash(),
+        };
+        let payments: Vec<&ElemCashFlow> = flows.iter().skip(1).collect();
+        let count = payments.len();
+        let finance_charge: Decimal =
+            payments.iter().map(|o| o.cash()).sum::<Decimal>() - amount_financed;
+        let digits = Decimal::from(count * (count + 1) / 2);
+
+        let mut result: Vec<ElemAllocationRow> = Vec::new();
+        let mut balance = amount_financed;
+        let mut allocated = dec!(0.0);
+        for (payment_index, payment) in payments.iter().enumerate() {
+            let interest = if payment_index == count - 1 {
+                finance_charge - allocated
+            } else {
+                match method {
+                    AllocationMethod::Actuarial => payment.interest().round_dp(decimal_digits),
+                    AllocationMethod::RuleOf78s => {
+                        (finance_charge * Decimal::from(count - payment_index) / digits)
+                            .round_dp(decimal_digits)
+                    }
+                }
+            };
+            allocated += interest;
+            balance -= payment.cash() - interest;
+
+            result.push(ElemAllocationRow {
+                event_date: payment.event_date(),
+                payment: payment.cash(),
+                interest,
+                principal: payment.cash() - interest,
+                balance,
+            });
+        }
+
+        result
+    }
+
+    /// Calculate the early payoff rebate of unearned interest as of
+    /// a date. The Rule of 78s rebate is the finance charge times
+    /// the sum of the digits of the remaining payments divided by
+    /// the sum of the digits of all payments.
+    ///
+    /// # Arguments
+    ///
+    /// * `flows` - The dated cash flows (see ElemCashFlow::from_rows).
+    /// * `method` - The interest allocation method.
+    /// * `as_of` - The payoff date (YYYYMMDD).
+    /// * `decimal_digits` - Number of decimal digits for rounding.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn rebate(
+        flows: &[ElemCashFlow],
+        method: AllocationMethod,
+        as_of: usize,
+        decimal_digits: u32,
+    ) -> ElemPayoffRebate {
+        let rows = AllocationUtility::schedule(flows, method, decimal_digits);
+        let payments_made = rows.iter().filter(|o| o.event_date() <= as_of).count();
+        let payments_remaining = rows.len() - payments_made;
+
+        let finance_charge: Decimal = rows.iter().map(|o| o.interest()).sum();
+        let earned_interest: Decimal = rows.iter().take(payments_made).map(|o| o.interest()).sum();
+        let remaining_payments: Decimal =
+            rows.iter().skip(payments_made).map(|o| o.payment()).sum();
+        let rebate = finance_charge - earned_interest;
+
+        ElemPayoffRebate {
+            payments_made,
+            payments_remaining,
+            finance_charge,
+            earned_interest,
+            rebate,
+            payoff: remaining_payments - rebate,
+        }
+    }
+}
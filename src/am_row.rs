@@ -0,0 +1,345 @@
+//! Amortization row snapshot.
+// Copyright (c) 2021 ShiftLeft Software
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use rust_decimal::prelude::*;
+
+use amfnengine::core::*;
+use amfnengine::*;
+
+/// Snapshot of a single amortization row. Reports and simulations
+/// operate upon these snapshots so that the engine's amortization
+/// list is not held while the results are computed.
+#[derive(Clone)]
+pub struct ElemAmRow {
+    /// Event type.
+    event_type: String,
+    /// Date of the row (YYYYMMDD).
+    event_date: usize,
+    /// Sort order of the row.
+    sort_order: usize,
+    /// Value of the row.
+    value: Decimal,
+    /// Principal decrease.
+    principal_decrease: Decimal,
+    /// Principal increase.
+    principal_increase: Decimal,
+    /// Compounded interest.
+    interest: Decimal,
+    /// Straight-line interest.
+    sl_interest: Decimal,
+    /// Value to interest.
+    value_to_interest: Decimal,
+    /// Value to principal.
+    value_to_principal: Decimal,
+    /// Accrued balance.
+    acc_balance: Decimal,
+    /// Balance.
+    balance: Decimal,
+}
+
+/// Amortization row snapshot implementation.
+impl ElemAmRow {
+    /// Create and return an amortization row snapshot.
+    ///
+    /// # Arguments
+    ///
+    /// * `event_type_param` - Event type.
+    /// * `event_date_param` - Date of the row.
+    /// * `sort_order_param` - Sort order of the row.
+    /// * `value_param` - Value of the row.
+    /// * `principal_decrease_param` - Principal decrease.
+    /// * `principal_increase_param` - Principal increase.
+    /// * `interest_param` - Compounded interest.
+    /// * `sl_interest_param` - Straight-line interest.
+    /// * `value_to_interest_param` - Value to interest.
+    /// * `value_to_principal_param` - Value to principal.
+    /// * `acc_balance_param` - Accrued balance.
+    /// * `balance_param` - Balance.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        event_type_param: &str,
+        event_date_param: usize,
+        sort_order_param: usize,
+        value_param: Decimal,
+        principal_decrease_param: Decimal,
+        principal_increase_param: Decimal,
+        interest_param: Decimal,
+        sl_interest_param: Decimal,
+        value_to_interest_param: Decimal,
+        value_to_principal_param: Decimal,
+        acc_balance_param: Decimal,
+        balance_param: Decimal,
+    ) -> ElemAmRow {
+        ElemAmRow {
+            event_type: String::from(event_type_param),
+            event_date: event_date_param,
+            sort_order: sort_order_param,
+            value: value_param,
+            principal_decrease: principal_decrease_param,
+            principal_increase: principal_increase_param,
+            interest: interest_param,
+            sl_interest: sl_interest_param,
+            value_to_interest: value_to_interest_param,
+            value_to_principal: value_to_principal_param,
+            acc_balance: acc_balance_param,
+            balance: balance_param,
+        }
+    }
+
+    /// Create a snapshot of the currently selected amortization element.
+    ///
+    /// # Arguments
+    ///
+    /// * `list_am` - The amortization list.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn from_list(list_am: &ListAmortization) -> ElemAmRow {
+        ElemAmRow::new(
+            list_am.event_type(),
+            list_am.event_date(),
+            list_am.sort_order(),
+            list_am.value(),
+            list_am.principal_decrease(),
+            list_am.principal_increase(),
+            list_am.interest(),
+            list_am.sl_interest(),
+            list_am.value_to_interest(),
+            list_am.value_to_principal(),
+            list_am.acc_balance(),
+            list_am.balance(),
+        )
+    }
+
+    /// Create snapshots of all elements within an amortization list.
+    /// The list's selected element is preserved.
+    ///
+    /// # Arguments
+    ///
+    /// * `list_am` - The amortization list.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn from_list_all(list_am: &ListAmortization) -> Vec<ElemAmRow> {
+        let mut rows: Vec<ElemAmRow> = Vec::new();
+        let orig_index = list_am.index();
+        let mut index: usize = 0;
+        loop {
+            if !list_am.get_element(index) {
+                break;
+            }
+            rows.push(ElemAmRow::from_list(list_am));
+            index += 1;
+        }
+        list_am.get_element(orig_index);
+
+        rows
+    }
+
+    /// Return a copy of the row with the amounts multiplied by
+    /// a factor (e.g., an exchange rate).
+    ///
+    /// # Arguments
+    ///
+    /// * `factor` - The factor.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn scaled(&self, factor: Decimal) -> ElemAmRow {
+        ElemAmRow::new(
+            self.event_type.as_str(),
+            self.event_date,
+            self.sort_order,
+            self.value * factor,
+            self.principal_decrease * factor,
+            self.principal_increase * factor,
+            self.interest * factor,
+            self.sl_interest * factor,
+            self.value_to_interest * factor,
+            self.value_to_principal * factor,
+            self.acc_balance * factor,
+            self.balance * factor,
+        )
+    }
+
+    /// Return the direction of the payments of amortization rows.
+    /// Payments may be modeled as either principal increases or
+    /// decreases (e.g., principal increases for the loan templates);
+    /// the direction of the payments is the direction of the final
+    /// principal change.
+    ///
+    /// # Arguments
+    ///
+    /// * `rows` - The amortization rows.
+    ///
+    /// # Return
+    ///
+    /// * 1 if the payments are principal decreases, -1 if they are
+    ///   principal increases or 0 if there is no principal change.
+
+    pub fn payment_direction(rows: &[ElemAmRow]) -> Decimal {
+        match rows
+            .iter()
+            .map(|o| o.principal_decrease - o.principal_increase)
+            .filter(|o| !o.is_zero())
+            .last()
+        {
+            None => dec!(0.0),
+            Some(o) if o.is_sign_negative() => dec!(-1.0),
+            Some(_o) => dec!(1.0),
+        }
+    }
+
+    /// Return the principal change of the row in the direction
+    /// of the payments (see payment_direction).
+    ///
+    /// # Arguments
+    ///
+    /// * `direction` - The direction of the payments.
+    ///
+    /// # Return
+    ///
+    /// * See description (negative if the principal
+    ///   changes in the other direction).
+
+    pub fn payment(&self, direction: Decimal) -> Decimal {
+        (self.principal_decrease - self.principal_increase) * direction
+    }
+
+    /// Get the event type.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn event_type(&self) -> &str {
+        self.event_type.as_str()
+    }
+
+    /// Get the date of the row.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn event_date(&self) -> usize {
+        self.event_date
+    }
+
+    /// Get the sort order of the row.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn sort_order(&self) -> usize {
+        self.sort_order
+    }
+
+    /// Get the value of the row.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn value(&self) -> Decimal {
+        self.value
+    }
+
+    /// Get the principal decrease.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn principal_decrease(&self) -> Decimal {
+        self.principal_decrease
+    }
+
+    /// Get the principal increase.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn principal_increase(&self) -> Decimal {
+        self.principal_increase
+    }
+
+    /// Get the compounded interest.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn interest(&self) -> Decimal {
+        self.interest
+    }
+
+    /// Get the straight-line interest.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn sl_interest(&self) -> Decimal {
+        self.sl_interest
+    }
+
+    /// Get the value to interest.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn value_to_interest(&self) -> Decimal {
+        self.value_to_interest
+    }
+
+    /// Get the value to principal.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn value_to_principal(&self) -> Decimal {
+        self.value_to_principal
+    }
+
+    /// Get the accrued balance.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn acc_balance(&self) -> Decimal {
+        self.acc_balance
+    }
+
+    /// Get the balance.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn balance(&self) -> Decimal {
+        self.balance
+    }
+}
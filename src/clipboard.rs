@@ -0,0 +1,268 @@
+//! Event copies for inserting, moving and pasting events.
+// Copyright (c) 2021 ShiftLeft Software
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use rust_decimal::prelude::*;
+
+use amfnengine::core::*;
+use amfnengine::*;
+
+use crate::date_utility::*;
+
+/// Clipboard format identifier.
+pub const CLIPBOARD_FORMAT: &str = "amfn-events";
+/// Base name of the scratch cashflow that events are pasted from.
+pub const CLIPBOARD_SCRATCH_NAME: &str = "~Clipboard";
+
+/// Copy of an event. Events are copied before they are added
+/// so that the engine's event list is not held while adding.
+pub struct ElemEventCopy {
+    /// Date of the event (YYYYMMDD).
+    event_date: usize,
+    /// Date expression of the event.
+    date_expr: String,
+    /// Sort order of the event.
+    sort_order: usize,
+    /// Value of the event.
+    value: Decimal,
+    /// Value expression of the event.
+    value_expr: String,
+    /// Value expression is balanced.
+    value_expr_balance: bool,
+    /// Number of periods.
+    periods: usize,
+    /// Periods expression of the event.
+    periods_expr: String,
+    /// Skip periods mask (in display format).
+    skip_periods: String,
+    /// Number of intervals per period.
+    intervals: usize,
+    /// Frequency of the periods.
+    frequency: FrequencyType,
+    /// Event type extension.
+    extension: ElemExtension,
+    /// Event parameters.
+    list_parameter: Option<ListParameter>,
+    /// Event descriptors.
+    list_descriptor: Option<ListDescriptor>,
+    /// Event name.
+    event_name: String,
+    /// Next event name.
+    next_name: String,
+}
+
+/// Event copy implementation.
+impl ElemEventCopy {
+    /// Copy the selected event of an event list.
+    ///
+    /// # Arguments
+    ///
+    /// * `list_event` - The event list.
+    /// * `skip_periods` - The skip periods mask of the event
+    ///   (the event's Skip-periods column value).
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn from_list(list_event: &ListEvent, skip_periods: &str) -> ElemEventCopy {
+        ElemEventCopy {
+            event_date: list_event.event_date(),
+            date_expr: String::from(list_event.date_expr()),
+            sort_order: list_event.sort_order(),
+            value: list_event.value(),
+            value_expr: String::from(list_event.value_expr()),
+            value_expr_balance: list_event.value_expr_balance(),
+            periods: list_event.periods(),
+            periods_expr: String::from(list_event.periods_expr()),
+            skip_periods: String::from(skip_periods),
+            intervals: list_event.intervals(),
+            frequency: list_event.frequency(),
+            extension: list_event.elem_extension().clone(),
+            list_parameter: list_event.list_parameter().map(|o| o.copy(false)),
+            list_descriptor: list_event.list_descriptor().map(|o| o.copy(false)),
+            event_name: String::from(list_event.event_name()),
+            next_name: String::from(list_event.next_name()),
+        }
+    }
+
+    /// Get the date of the event.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn event_date(&self) -> usize {
+        self.event_date
+    }
+
+    /// Get the sort order of the event.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn sort_order(&self) -> usize {
+        self.sort_order
+    }
+
+    /// Get the skip periods mask of the event.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn skip_periods(&self) -> &str {
+        self.skip_periods.as_str()
+    }
+
+    /// Add a copy of the event to an event list. The skip periods
+    /// are set afterwards through the event's Skip-periods column
+    /// (see skip_periods).
+    ///
+    /// # Arguments
+    ///
+    /// * `list_event` - The event list.
+    /// * `event_date` - The date of the new event.
+    /// * `sort_order` - The sort order of the new event.
+    ///
+    /// # Return
+    ///
+    /// * True if successful, otherwise false.
+
+    pub fn add_to(&self, list_event: &mut ListEvent, event_date: usize, sort_order: usize) -> bool {
+        list_event.add_event(
+            event_date,
+            self.date_expr.as_str(),
+            sort_order,
+            self.value,
+            self.value_expr.as_str(),
+            self.value_expr_balance,
+            self.periods,
+            self.periods_expr.as_str(),
+            0,
+            None,
+            self.intervals,
+            self.frequency,
+            self.extension.clone(),
+            self.list_parameter.as_ref().map(|o| o.copy(false)),
+            self.list_descriptor.as_ref().map(|o| o.copy(false)),
+            self.event_name.as_str(),
+            self.next_name.as_str(),
+        )
+    }
+}
+
+/// Event clipboard utility.
+pub struct ClipboardUtility {}
+
+/// Event clipboard utility implementation.
+impl ClipboardUtility {
+    /// Return the date that a copied event is pasted on. The
+    /// events are shifted by the number of days between the
+    /// first copied event and the paste date.
+    ///
+    /// # Arguments
+    ///
+    /// * `event_date` - The date of the copied event.
+    /// * `first_date` - The date of the first copied event.
+    /// * `paste_date` - The paste date (zero to keep the dates).
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn paste_date(event_date: usize, first_date: usize, paste_date: usize) -> usize {
+        if paste_date == 0 {
+            return event_date;
+        }
+
+        DateUtility::add_days(
+            event_date,
+            DateUtility::days_between(first_date, paste_date),
+        )
+    }
+
+    /// Return the first unused sort order of a date.
+    ///
+    /// # Arguments
+    ///
+    /// * `used` - The dates and sort orders of the events.
+    /// * `event_date` - The date.
+    /// * `sort_order` - The preferred sort order.
+    ///
+    /// # Return
+    ///
+    /// * The preferred sort order or the next unused sort order.
+
+    pub fn free_sort_order(used: &[(usize, usize)], event_date: usize, sort_order: usize) -> usize {
+        let mut result = sort_order;
+        while used.contains(&(event_date, result)) {
+            result += 1;
+        }
+
+        result
+    }
+
+    /// Return a name for the scratch cashflow that is not used
+    /// by any of the cashflows.
+    ///
+    /// # Arguments
+    ///
+    /// * `names` - The names of the cashflows.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn scratch_name(names: &[String]) -> String {
+        let mut result = String::from(CLIPBOARD_SCRATCH_NAME);
+        let mut suffix: usize = 1;
+        while names.contains(&result) {
+            suffix += 1;
+            result = format!("{}{}", CLIPBOARD_SCRATCH_NAME, suffix);
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paste_date_shifts_by_first_event() {
+        assert_eq!(
+            ClipboardUtility::paste_date(20240215, 20240201, 20240301),
+            20240315
+        );
+        assert_eq!(
+            ClipboardUtility::paste_date(20240215, 20240201, 0),
+            20240215
+        );
+        assert_eq!(
+            ClipboardUtility::paste_date(20231231, 20231215, 20240215),
+            20240302
+        );
+    }
+
+    #[test]
+    fn free_sort_order_skips_used() {
+        let used = vec![(20240101, 1), (20240101, 2), (20240201, 3)];
+        assert_eq!(ClipboardUtility::free_sort_order(&used, 20240101, 1), 3);
+        assert_eq!(ClipboardUtility::free_sort_order(&used, 20240201, 1), 1);
+    }
+
+    #[test]
+    fn scratch_name_is_unused() {
+        assert_eq!(ClipboardUtility::scratch_name(&[]), "~Clipboard");
+        let names = vec![String::from("~Clipboard"), String::from("~Clipboard2")];
+        assert_eq!(ClipboardUtility::scratch_name(&names), "~Clipboard3");
+    }
+}
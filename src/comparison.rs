@@ -0,0 +1,194 @@
+//! Side-by-side cashflow comparison.
+// Copyright (c) 2021 ShiftLeft Software
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use rust_decimal::prelude::*;
+
+use crate::json_utility::*;
+
+/// Comparison row name for the total interest.
+pub const COMPARE_TOTAL_INTEREST: &str = "Total-interest";
+/// Comparison row name for the final balance.
+pub const COMPARE_FINAL_BALANCE: &str = "Final-balance";
+/// Comparison row name for the term (number of payments).
+pub const COMPARE_TERM: &str = "Term";
+/// Comparison row name for the payoff date.
+pub const COMPARE_PAYOFF_DATE: &str = "Payoff-date";
+
+/// Comparison row element. Each row holds one item (a summary
+/// result or a computed metric) for every compared cashflow.
+pub struct ElemComparisonRow {
+    /// Name of the item.
+    name: String,
+    /// Label of the item.
+    label: String,
+    /// Value for each cashflow (empty if not present).
+    values: Vec<String>,
+    /// Numeric value for each cashflow (None if not numeric).
+    numbers: Vec<Option<Decimal>>,
+}
+
+/// Comparison row implementation.
+impl ElemComparisonRow {
+    /// Create and return a comparison row.
+    ///
+    /// # Arguments
+    ///
+    /// * `name_param` - Name of the item.
+    /// * `label_param` - Label of the item.
+    /// * `count` - Number of compared cashflows.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn new(name_param: &str, label_param: &str, count: usize) -> ElemComparisonRow {
+        ElemComparisonRow {
+            name: String::from(name_param),
+            label: String::from(label_param),
+            values: vec![String::from(""); count],
+            numbers: vec![None; count],
+        }
+    }
+
+    /// Get the name of the item.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    /// Get the label of the item.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn label(&self) -> &str {
+        self.label.as_str()
+    }
+
+    /// Set the value of the item for a cashflow.
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - Position of the cashflow within the comparison.
+    /// * `value` - The value (as displayed).
+    /// * `number` - The numeric value or None if not numeric.
+
+    pub fn set_value(&mut self, position: usize, value: &str, number: Option<Decimal>) {
+        if position < self.values.len() {
+            self.values[position] = String::from(value);
+            self.numbers[position] = number;
+        }
+    }
+
+    /// Return the difference of a cashflow's value relative
+    /// to the baseline cashflow's value.
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - Position of the cashflow within the comparison.
+    /// * `baseline` - Position of the baseline cashflow.
+    ///
+    /// # Return
+    ///
+    /// * See description or None if either value is not numeric.
+
+    pub fn difference(&self, position: usize, baseline: usize) -> Option<Decimal> {
+        match (self.numbers.get(position), self.numbers.get(baseline)) {
+            (Some(Some(o1)), Some(Some(o2))) => Some(*o1 - *o2),
+            _ => None,
+        }
+    }
+}
+
+/// Cashflow comparison utility.
+pub struct ComparisonUtility {}
+
+/// Cashflow comparison utility implementation.
+impl ComparisonUtility {
+    /// Find a comparison row by name or append a new row.
+    ///
+    /// # Arguments
+    ///
+    /// * `rows` - The comparison rows.
+    /// * `name` - Name of the item.
+    /// * `label` - Label of the item (used for a new row).
+    /// * `count` - Number of compared cashflows.
+    ///
+    /// # Return
+    ///
+    /// * The row.
+
+    pub fn find_row<'a>(
+        rows: &'a mut Vec<ElemComparisonRow>,
+        name: &str,
+        label: &str,
+        count: usize,
+    ) -> &'a mut ElemComparisonRow {
+        let position = match rows.iter().position(|o| o.name() == name) {
+            Some(o) => o,
+            None => {
+                rows.push(ElemComparisonRow::new(name, label, count));
+                rows.len() - 1
+            }
+        };
+
+        &mut rows[position]
+    }
+
+    /// Return the comparison rows as a json string. Each row has the
+    /// item's "Name" and "Label" and, for each compared cashflow at
+    /// position n, "Value-n" and "Difference-n" (relative to the baseline).
+    ///
+    /// # Arguments
+    ///
+    /// * `rows` - The comparison rows.
+    /// * `count` - Number of compared cashflows.
+    /// * `baseline` - Position of the baseline cashflow.
+    ///
+    /// # Return
+    ///
+    /// * Return a string that can be directly loaded into ag-grid.
+
+    pub fn to_json(rows: &[ElemComparisonRow], count: usize, baseline: usize) -> String {
+        let mut result = String::from("");
+
+        for (row_index, row) in rows.iter().enumerate() {
+            let mut values = format!(
+                "\"Name\":\"{}\",\"Label\":\"{}\"",
+                JsonUtility::escape(row.name()),
+                JsonUtility::escape(row.label())
+            );
+
+            for position in 0..count {
+                let difference = match row.difference(position, baseline) {
+                    None => String::from(""),
+                    Some(o) => o.to_string(),
+                };
+                values = format!(
+                    "{},\"Value-{}\":\"{}\",\"Difference-{}\":\"{}\"",
+                    values,
+                    position,
+                    JsonUtility::escape(row.values[position].as_str()),
+                    position,
+                    difference
+                );
+            }
+
+            let delimiter = if row_index == 0 { "" } else { "," };
+            result = format!("{}{}{{{}}}", result, delimiter, values);
+        }
+
+        format!("[{}]", result)
+    }
+}
@@ -0,0 +1,297 @@
+//! Conditional (expression triggered) events.
+// Copyright (c) 2021 ShiftLeft Software
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use rust_decimal::prelude::*;
+
+use amfnengine::*;
+
+use crate::am_row::*;
+use crate::delinquency::*;
+use crate::effective_interest::*;
+
+/// Event descriptor group for a conditional event.
+pub const GROUP_CONDITION: &str = "Condition";
+/// Event descriptor name for the condition rule.
+pub const NAME_CONDITION_RULE: &str = "Rule";
+/// Event descriptor name for the skip periods entered by hand.
+pub const NAME_CONDITION_MASK: &str = "Mask";
+
+/// Maximum number of balancing passes while the conditions settle.
+pub const MAX_CONDITION_PASSES: usize = 10;
+
+/// Condition symbol for the balance before the period.
+pub const SYM_BALANCE: &str = "decBalance";
+/// Condition symbol for the date of the period (YYYYMMDD).
+pub const SYM_DATE: &str = "intDate";
+/// Condition symbol for the zero based period of the event.
+pub const SYM_SEQUENCE: &str = "intSequence";
+/// Condition symbol for the event value.
+pub const SYM_VALUE: &str = "decValue";
+/// Condition symbol for the number of scheduled payments before the period.
+pub const SYM_PAYMENTS: &str = "intPayments";
+/// Condition symbol for the number of actual payments made on time
+/// (see DelinquencyUtility::records) before the period.
+pub const SYM_ON_TIME: &str = "intOnTime";
+
+/// Action taken by a condition.
+#[derive(Clone, Copy, PartialEq)]
+pub enum ConditionAction {
+    /// The event is skipped until the condition first holds.
+    Enable,
+    /// The event is skipped whenever the condition holds.
+    Disable,
+    /// The event is skipped once the condition first holds.
+    Terminate,
+}
+
+/// Condition of an event.
+#[derive(Clone)]
+pub struct ElemCondition {
+    /// Action taken by the condition.
+    action: ConditionAction,
+    /// Condition expression.
+    expression: String,
+}
+
+/// Condition implementation.
+impl ElemCondition {
+    /// Parse a condition from its descriptor value (action~expression,
+    /// where action is enable, disable or terminate, e.g.,
+    /// "terminate~decBalance < 10000" or "enable~intOnTime >= 36").
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The descriptor value.
+    ///
+    /// # Return
+    ///
+    /// * See description or None if the value is invalid.
+
+    pub fn parse(value: &str) -> Option<ElemCondition> {
+        let mut tokens = value.splitn(2, '~');
+
+        let action = match tokens.next()?.trim().to_lowercase().as_str() {
+            "enable" => ConditionAction::Enable,
+            "disable" => ConditionAction::Disable,
+            "terminate" => ConditionAction::Terminate,
+            _ => return None,
+        };
+
+        let expression = tokens.next()?.trim();
+        if expression.is_empty() {
+            return None;
+        }
+
+        Some(ElemCondition {
+            action,
+            expression: String::from(expression),
+        })
+    }
+
+    /// Return the descriptor value of the condition.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn descriptor_value(&self) -> String {
+        format!(
+            "{}~{}",
+            match self.action {
+                ConditionAction::Enable => "enable",
+                ConditionAction::Disable => "disable",
+                ConditionAction::Terminate => "terminate",
+            },
+            self.expression
+        )
+    }
+
+    /// Get the action taken by the condition.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn action(&self) -> ConditionAction {
+        self.action
+    }
+
+    /// Get the condition expression.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn expression(&self) -> &str {
+        self.expression.as_str()
+    }
+
+    /// Create the skip periods mask of the event from the
+    /// condition results of its periods.
+    ///
+    /// # Arguments
+    ///
+    /// * `results` - The condition result of each period.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn mask(&self, results: &[bool]) -> String {
+        let first = results.iter().position(|o| *o).unwrap_or(results.len());
+
+        results
+            .iter()
+            .enumerate()
+            .map(|(index, result)| {
+                let skipped = match self.action {
+                    ConditionAction::Enable => index < first,
+                    ConditionAction::Disable => *result,
+                    ConditionAction::Terminate => index >= first,
+                };
+                if skipped {
+                    '1'
+                } else {
+                    '0'
+                }
+            })
+            .collect()
+    }
+}
+
+/// Conditional event utility.
+pub struct ConditionUtility {}
+
+/// Conditional event utility implementation.
+impl ConditionUtility {
+    /// Replace the symbols found within an expression (outside of
+    /// string literals) with their literal values (negative values
+    /// are parenthesized).
+    ///
+    /// # Arguments
+    ///
+    /// * `expr` - The expression.
+    /// * `symbols` - The symbol names and values.
+    ///
+    /// # Return
+    ///
+    /// * The expression with the symbols replaced.
+
+    pub fn substitute(expr: &str, symbols: &[(&str, String)]) -> String {
+        let mut result = String::from("");
+        let mut name = String::from("");
+        let mut quoted = false;
+
+        for ch in expr.chars().chain(std::iter::once(' ')) {
+            if !quoted && (ch.is_ascii_alphanumeric() || ch == '_') {
+                name.push(ch);
+                continue;
+            }
+
+            if !name.is_empty() {
+                match symbols.iter().find(|o| o.0 == name) {
+                    None => result.push_str(name.as_str()),
+                    Some(o) if o.1.starts_with('-') => {
+                        result.push_str(format!("({})", o.1).as_str())
+                    }
+                    Some(o) => result.push_str(o.1.as_str()),
+                }
+                name.clear();
+            }
+
+            if ch == '"' {
+                quoted = !quoted;
+            }
+            result.push(ch);
+        }
+
+        result.pop();
+        result
+    }
+
+    /// Return the balance before a period of an event.
+    ///
+    /// # Arguments
+    ///
+    /// * `rows` - The expanded amortization rows.
+    /// * `event_date` - The date of the period.
+    /// * `sort_order` - The sort order of the event.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn balance_before(rows: &[ElemAmRow], event_date: usize, sort_order: usize) -> Decimal {
+        rows.iter()
+            .take_while(|o| (o.event_date(), o.sort_order()) < (event_date, sort_order))
+            .last()
+            .map_or(dec!(0.0), |o| o.balance())
+    }
+
+    /// Return the number of scheduled payments before a date.
+    ///
+    /// # Arguments
+    ///
+    /// * `flows` - The scheduled cash flows (see ElemCashFlow::from_rows).
+    /// * `event_date` - The date.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn payments_before(flows: &[ElemCashFlow], event_date: usize) -> usize {
+        flows
+            .iter()
+            .skip(1)
+            .filter(|o| o.cash() > dec!(0.0) && o.event_date() < event_date)
+            .count()
+    }
+
+    /// Return the number of scheduled payments due before
+    /// a date that were paid on time.
+    ///
+    /// # Arguments
+    ///
+    /// * `records` - The payment records (see DelinquencyUtility::records).
+    /// * `event_date` - The date.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn on_time_before(records: &[ElemDelinquencyRecord], event_date: usize) -> usize {
+        records
+            .iter()
+            .filter(|o| o.due_date() < event_date && o.status() == PaymentStatus::OnTime)
+            .count()
+    }
+
+    /// Determine whether the result of a condition holds.
+    ///
+    /// # Arguments
+    ///
+    /// * `result` - The result of the condition expression.
+    ///
+    /// # Return
+    ///
+    /// * True if the result is true or a non-zero number, otherwise false.
+
+    pub fn holds(result: &str) -> bool {
+        let result = result.trim();
+
+        if result.eq_ignore_ascii_case("true") {
+            return true;
+        }
+
+        match result.parse::<Decimal>() {
+            Err(_e) => false,
+            Ok(o) => !o.is_zero(),
+        }
+    }
+}
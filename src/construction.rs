@@ -0,0 +1,306 @@
+//! Construction loan draws with an interest reserve.
+// Copyright (c) 2021 ShiftLeft Software
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use rust_decimal::prelude::*;
+
+use amfnengine::*;
+
+use crate::am_row::*;
+use crate::expression::*;
+use crate::revolving::*;
+
+/// Cashflow preferences descriptor group for a construction loan.
+pub const GROUP_CONSTRUCTION: &str = "Construction";
+/// Cashflow preferences descriptor name for the conversion date.
+pub const NAME_CONVERSION_DATE: &str = "Conversion";
+/// Construction loan expression function.
+pub const FUNC_CONSTRUCTION: &str = "construction";
+
+/// Construction loan row (one for each date).
+pub struct ElemConstructionRow {
+    /// Date of the row (YYYYMMDD).
+    event_date: usize,
+    /// Principal advanced on the draw schedule.
+    draw: Decimal,
+    /// Interest accrued through the date.
+    interest: Decimal,
+    /// Interest funded from the interest reserve.
+    reserve: Decimal,
+    /// Payments on the date.
+    payment: Decimal,
+    /// Balance after the row.
+    balance: Decimal,
+}
+
+/// Construction loan row implementation.
+impl ElemConstructionRow {
+    /// Get the date of the row.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn event_date(&self) -> usize {
+        self.event_date
+    }
+
+    /// Get the principal advanced.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn draw(&self) -> Decimal {
+        self.draw
+    }
+
+    /// Get the interest accrued.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn interest(&self) -> Decimal {
+        self.interest
+    }
+
+    /// Get the interest funded from the interest reserve.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn reserve(&self) -> Decimal {
+        self.reserve
+    }
+
+    /// Get the payments.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn payment(&self) -> Decimal {
+        self.payment
+    }
+
+    /// Get the balance.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn balance(&self) -> Decimal {
+        self.balance
+    }
+}
+
+/// Construction loan summary.
+pub struct ElemConstructionSummary {
+    /// Date of the conversion to the permanent loan (YYYYMMDD).
+    conversion_date: usize,
+    /// Total principal advanced through the conversion date.
+    total_draws: Decimal,
+    /// Total interest reserve needed.
+    reserve: Decimal,
+    /// Peak balance.
+    peak_balance: Decimal,
+    /// Balance converted to the permanent loan.
+    conversion_balance: Decimal,
+}
+
+/// Construction loan summary implementation.
+impl ElemConstructionSummary {
+    /// Get the date of the conversion.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn conversion_date(&self) -> usize {
+        self.conversion_date
+    }
+
+    /// Get the total principal advanced.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn total_draws(&self) -> Decimal {
+        self.total_draws
+    }
+
+    /// Get the total interest reserve needed.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn reserve(&self) -> Decimal {
+        self.reserve
+    }
+
+    /// Get the peak balance.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn peak_balance(&self) -> Decimal {
+        self.peak_balance
+    }
+
+    /// Get the balance converted to the permanent loan.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn conversion_balance(&self) -> Decimal {
+        self.conversion_balance
+    }
+}
+
+/// Construction loan utility.
+pub struct ConstructionUtility {}
+
+/// Construction loan utility implementation.
+impl ConstructionUtility {
+    /// Create the construction loan schedule from amortization rows.
+    /// Principal is advanced by the draw events (see
+    /// RevolvingUtility::activity) and the interest accrued through
+    /// the conversion date, less any payments, is funded from the
+    /// interest reserve. The loan amortizes after the conversion date.
+    ///
+    /// # Arguments
+    ///
+    /// * `rows` - The expanded amortization rows.
+    /// * `conversion` - The conversion date (YYYYMMDD; zero for the
+    ///   date before the first payment).
+    ///
+    /// # Return
+    ///
+    /// * One row for each date and the summary.
+
+    pub fn schedule(
+        rows: &[ElemAmRow],
+        conversion: usize,
+    ) -> (Vec<ElemConstructionRow>, ElemConstructionSummary) {
+        let activity = RevolvingUtility::activity(rows);
+
+        let conversion_date = if conversion > 0 {
+            conversion
+        } else {
+            match activity.iter().position(|o| o.payment() > dec!(0.0)) {
+                None => activity.last().map_or(0, |o| o.event_date()),
+                Some(0) => activity[0].event_date(),
+                Some(o) => activity[o - 1].event_date(),
+            }
+        };
+
+        let mut result: Vec<ElemConstructionRow> = Vec::new();
+        let mut total_draws = dec!(0.0);
+        let mut reserve = dec!(0.0);
+        let mut peak_balance = dec!(0.0);
+        let mut conversion_balance = dec!(0.0);
+
+        for row in activity.iter() {
+            let row_reserve = if row.event_date() <= conversion_date {
+                total_draws += row.draw();
+                conversion_balance = row.balance();
+                (row.interest() - row.payment()).max(dec!(0.0))
+            } else {
+                dec!(0.0)
+            };
+            reserve += row_reserve;
+            peak_balance = peak_balance.max(row.balance());
+
+            result.push(ElemConstructionRow {
+                event_date: row.event_date(),
+                draw: row.draw(),
+                interest: row.interest(),
+                reserve: row_reserve,
+                payment: row.payment(),
+                balance: row.balance(),
+            });
+        }
+
+        (
+            result,
+            ElemConstructionSummary {
+                conversion_date,
+                total_draws,
+                reserve,
+                peak_balance,
+                conversion_balance,
+            },
+        )
+    }
+
+    /// Return a construction loan value by name.
+    ///
+    /// # Arguments
+    ///
+    /// * `summary` - The construction loan summary.
+    /// * `name` - The value name (reserve, peak-balance, draws
+    ///   or conversion-balance).
+    ///
+    /// # Return
+    ///
+    /// * See description or None if the name is not recognized.
+
+    pub fn value(summary: &ElemConstructionSummary, name: &str) -> Option<Decimal> {
+        match name.trim().to_lowercase().as_str() {
+            "reserve" => Some(summary.reserve()),
+            "peak-balance" => Some(summary.peak_balance()),
+            "draws" => Some(summary.total_draws()),
+            "conversion-balance" => Some(summary.conversion_balance()),
+            _ => None,
+        }
+    }
+
+    /// Determine whether an expression contains any construction loan functions.
+    ///
+    /// # Arguments
+    ///
+    /// * `expr` - The expression to search.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn has_functions(expr: &str) -> bool {
+        expr.to_ascii_lowercase()
+            .contains(format!("{}(", FUNC_CONSTRUCTION).as_str())
+    }
+
+    /// Expand the construction loan functions found within an expression
+    /// (e.g., construction("reserve")) into their literal results.
+    ///
+    /// # Arguments
+    ///
+    /// * `expr` - The expression to expand.
+    /// * `eval` - Evaluates an argument expression and returns its value.
+    /// * `summary` - The construction loan summary.
+    ///
+    /// # Return
+    ///
+    /// * The expanded expression.
+
+    pub fn expand_expression(
+        expr: &str,
+        eval: &dyn Fn(&str) -> String,
+        summary: &ElemConstructionSummary,
+    ) -> String {
+        ExpressionUtility::expand_value_function(expr, FUNC_CONSTRUCTION, eval, &|name, _date| {
+            ConstructionUtility::value(summary, name)
+        })
+    }
+}
@@ -0,0 +1,423 @@
+//! Credit card minimum payment payoff simulation.
+// Copyright (c) 2021 ShiftLeft Software
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use chrono::Datelike;
+use rust_decimal::prelude::*;
+
+use amfnengine::*;
+
+use crate::am_row::*;
+use crate::date_utility::*;
+
+/// Maximum number of months simulated before a balance is
+/// considered never to be paid off.
+pub const MAX_PAYOFF_MONTHS: usize = 1200;
+
+/// Minimum payment formula (a percentage of the balance plus
+/// the interest of the month, subject to a minimum amount).
+#[derive(Clone, Copy)]
+pub struct ElemMinimumPaymentFormula {
+    /// Percentage of the balance.
+    percent: Decimal,
+    /// Minimum payment amount.
+    floor: Decimal,
+}
+
+/// Minimum payment formula implementation.
+impl ElemMinimumPaymentFormula {
+    /// Create and return a minimum payment formula.
+    ///
+    /// # Arguments
+    ///
+    /// * `percent_param` - Percentage of the balance.
+    /// * `floor_param` - Minimum payment amount.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn new(percent_param: Decimal, floor_param: Decimal) -> ElemMinimumPaymentFormula {
+        ElemMinimumPaymentFormula {
+            percent: percent_param,
+            floor: floor_param,
+        }
+    }
+
+    /// Parse a minimum payment formula (percent~floor, e.g., "1~25"
+    /// for one percent of the balance plus interest or 25 if greater).
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The formula.
+    ///
+    /// # Return
+    ///
+    /// * See description or None if not valid.
+
+    pub fn parse(value: &str) -> Option<ElemMinimumPaymentFormula> {
+        let mut fields = value.split('~').map(|o| o.trim());
+
+        let percent = fields.next()?.parse::<Decimal>().ok()?;
+        let floor = match fields.next() {
+            None => dec!(0.0),
+            Some(o) if o.is_empty() => dec!(0.0),
+            Some(o) => o.parse::<Decimal>().ok()?,
+        };
+
+        if percent < dec!(0.0) || floor < dec!(0.0) {
+            return None;
+        }
+
+        Some(ElemMinimumPaymentFormula::new(percent, floor))
+    }
+
+    /// Get the percentage of the balance.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn percent(&self) -> Decimal {
+        self.percent
+    }
+
+    /// Get the minimum payment amount.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn floor(&self) -> Decimal {
+        self.floor
+    }
+
+    /// Calculate the minimum payment of a month. The payment
+    /// never exceeds the balance plus the interest.
+    ///
+    /// # Arguments
+    ///
+    /// * `balance` - The balance at the start of the month.
+    /// * `interest` - The interest of the month.
+    /// * `decimal_digits` - Number of decimal digits for rounding.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn payment(&self, balance: Decimal, interest: Decimal, decimal_digits: u32) -> Decimal {
+        ((balance * self.percent / dec!(100.0)).round_dp(decimal_digits) + interest)
+            .max(self.floor)
+            .min(balance + interest)
+    }
+}
+
+/// Credit card payoff row (one for each month).
+pub struct ElemCardRow {
+    /// Date of the payment (YYYYMMDD).
+    event_date: usize,
+    /// Payment amount.
+    payment: Decimal,
+    /// Interest of the month.
+    interest: Decimal,
+    /// Principal repaid.
+    principal: Decimal,
+    /// Balance after the payment.
+    balance: Decimal,
+}
+
+/// Credit card payoff row implementation.
+impl ElemCardRow {
+    /// Get the date of the payment.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn event_date(&self) -> usize {
+        self.event_date
+    }
+
+    /// Get the payment amount.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn payment(&self) -> Decimal {
+        self.payment
+    }
+
+    /// Get the interest of the month.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn interest(&self) -> Decimal {
+        self.interest
+    }
+
+    /// Get the principal repaid.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn principal(&self) -> Decimal {
+        self.principal
+    }
+
+    /// Get the balance after the payment.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn balance(&self) -> Decimal {
+        self.balance
+    }
+}
+
+/// Credit card payoff.
+pub struct ElemCardPayoff {
+    /// Monthly payoff rows.
+    rows: Vec<ElemCardRow>,
+    /// Total interest paid.
+    total_interest: Decimal,
+    /// Total of the payments.
+    total_paid: Decimal,
+}
+
+/// Credit card payoff implementation.
+impl ElemCardPayoff {
+    /// Get the monthly payoff rows.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn rows(&self) -> &[ElemCardRow] {
+        &self.rows
+    }
+
+    /// Get the number of months to payoff.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn months(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Get the total interest paid.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn total_interest(&self) -> Decimal {
+        self.total_interest
+    }
+
+    /// Get the total of the payments.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn total_paid(&self) -> Decimal {
+        self.total_paid
+    }
+}
+
+/// Credit card simulation utility.
+pub struct CreditCardUtility {}
+
+/// Credit card simulation utility implementation.
+impl CreditCardUtility {
+    /// Simulate the monthly payoff of a balance. Interest accrues
+    /// monthly at the APR divided by twelve.
+    ///
+    /// # Arguments
+    ///
+    /// * `balance` - The starting balance.
+    /// * `apr` - The annual percentage rate.
+    /// * `start_date` - The date of the starting balance (YYYYMMDD);
+    ///   payments are made monthly thereafter.
+    /// * `payment` - Returns the payment of a month from the balance
+    ///   and the interest of the month.
+    /// * `decimal_digits` - Number of decimal digits for rounding.
+    ///
+    /// # Return
+    ///
+    /// * See description or None if the balance is not paid off
+    ///   within the maximum number of months.
+
+    pub fn simulate(
+        balance: Decimal,
+        apr: Decimal,
+        start_date: usize,
+        payment: &dyn Fn(Decimal, Decimal) -> Decimal,
+        decimal_digits: u32,
+    ) -> Option<ElemCardPayoff> {
+        let monthly_rate = apr / dec!(100.0) / dec!(12.0);
+        let eom = DateUtility::to_naive(start_date).map_or(false, |o| {
+            o.day() == DateUtility::days_in_month(o.year(), o.month())
+        });
+
+        let mut balance = balance;
+        let mut rows: Vec<ElemCardRow> = Vec::new();
+        let mut total_interest = dec!(0.0);
+        let mut total_paid = dec!(0.0);
+
+        while balance > dec!(0.0) {
+            if rows.len() >= MAX_PAYOFF_MONTHS {
+                return None;
+            }
+
+            let interest = (balance * monthly_rate).round_dp(decimal_digits);
+            let amount = payment(balance, interest).min(balance + interest);
+            if amount <= interest && amount < balance + interest {
+                return None;
+            }
+
+            balance = balance + interest - amount;
+            total_interest += interest;
+            total_paid += amount;
+
+            rows.push(ElemCardRow {
+                event_date: DateUtility::add_months(start_date, rows.len() as i32 + 1, eom),
+                payment: amount,
+                interest,
+                principal: amount - interest,
+                balance,
+            });
+        }
+
+        Some(ElemCardPayoff {
+            rows,
+            total_interest,
+            total_paid,
+        })
+    }
+
+    /// Simulate the payoff of a balance with minimum payments.
+    ///
+    /// # Arguments
+    ///
+    /// * `balance` - The starting balance.
+    /// * `apr` - The annual percentage rate.
+    /// * `start_date` - The date of the starting balance (YYYYMMDD).
+    /// * `formula` - The minimum payment formula.
+    /// * `decimal_digits` - Number of decimal digits for rounding.
+    ///
+    /// # Return
+    ///
+    /// * See description or None if the balance is not paid off.
+
+    pub fn minimum_payoff(
+        balance: Decimal,
+        apr: Decimal,
+        start_date: usize,
+        formula: &ElemMinimumPaymentFormula,
+        decimal_digits: u32,
+    ) -> Option<ElemCardPayoff> {
+        CreditCardUtility::simulate(
+            balance,
+            apr,
+            start_date,
+            &|balance, interest| formula.payment(balance, interest, decimal_digits),
+            decimal_digits,
+        )
+    }
+
+    /// Simulate the payoff of a balance with a fixed payment.
+    ///
+    /// # Arguments
+    ///
+    /// * `balance` - The starting balance.
+    /// * `apr` - The annual percentage rate.
+    /// * `start_date` - The date of the starting balance (YYYYMMDD).
+    /// * `payment` - The fixed payment.
+    /// * `decimal_digits` - Number of decimal digits for rounding.
+    ///
+    /// # Return
+    ///
+    /// * See description or None if the balance is not paid off
+    ///   (the payment does not exceed the interest).
+
+    pub fn fixed_payoff(
+        balance: Decimal,
+        apr: Decimal,
+        start_date: usize,
+        payment: Decimal,
+        decimal_digits: u32,
+    ) -> Option<ElemCardPayoff> {
+        CreditCardUtility::simulate(
+            balance,
+            apr,
+            start_date,
+            &|_balance, _interest| payment,
+            decimal_digits,
+        )
+    }
+
+    /// Return the payoff totals of a balanced credit card cashflow.
+    /// The payments are the principal increases of the rows.
+    ///
+    /// # Arguments
+    ///
+    /// * `rows` - The expanded amortization rows.
+    ///
+    /// # Return
+    ///
+    /// * The number of monthly payments, the total interest
+    ///   and the total of the payments.
+
+    pub fn am_totals(rows: &[ElemAmRow]) -> (usize, Decimal, Decimal) {
+        let mut months: usize = 0;
+        let mut total_interest = dec!(0.0);
+        let mut total_paid = dec!(0.0);
+
+        for row in rows.iter() {
+            total_interest += row.interest();
+            if row.principal_increase() > dec!(0.0) {
+                months += 1;
+                total_paid += row.principal_increase();
+            }
+        }
+
+        (months, total_interest.abs(), total_paid)
+    }
+
+    /// Group consecutive payments of the same amount.
+    ///
+    /// # Arguments
+    ///
+    /// * `rows` - The payoff rows.
+    ///
+    /// # Return
+    ///
+    /// * The date of the first payment, the payment amount and
+    ///   the number of payments of each group.
+
+    pub fn payment_runs(rows: &[ElemCardRow]) -> Vec<(usize, Decimal, usize)> {
+        let mut result: Vec<(usize, Decimal, usize)> = Vec::new();
+
+        for row in rows.iter() {
+            match result.last_mut() {
+                Some(o) if o.1 == row.payment() => o.2 += 1,
+                _ => result.push((row.event_date(), row.payment(), 1)),
+            }
+        }
+
+        result
+    }
+}
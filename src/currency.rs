@@ -0,0 +1,191 @@
+//! Currency precision and rounding.
+// Copyright (c) 2021 ShiftLeft Software
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use rust_decimal::prelude::*;
+use rust_decimal::RoundingStrategy;
+
+/// Cashflow preferences descriptor group for the rounding mode.
+pub const GROUP_ROUNDING: &str = "Rounding";
+/// Cashflow preferences descriptor name for the rounding mode.
+pub const NAME_ROUNDING_MODE: &str = "Mode";
+/// Cashflow preferences descriptor name for decimal digits
+/// set explicitly in the cashflow preferences.
+pub const NAME_DECIMAL_DIGITS: &str = "Decimal-digits";
+/// Event descriptor name marking balance rounding set by hand.
+pub const NAME_ROUNDING_OVERRIDE: &str = "Override";
+
+/// Currencies (ISO 4217) without minor units.
+const CURRENCIES_DIGITS_0: [&str; 17] = [
+    "BIF", "CLP", "DJF", "GNF", "ISK", "JPY", "KMF", "KRW", "PYG", "RWF", "UGX", "UYI", "VND",
+    "VUV", "XAF", "XOF", "XPF",
+];
+/// Currencies (ISO 4217) with three decimal digits.
+const CURRENCIES_DIGITS_3: [&str; 7] = ["BHD", "IQD", "JOD", "KWD", "LYD", "OMR", "TND"];
+/// Currencies (ISO 4217) with four decimal digits.
+const CURRENCIES_DIGITS_4: [&str; 2] = ["CLF", "UYW"];
+
+/// Rounding mode (the mnemonics match the Rounding_* resources).
+#[derive(Clone, Copy, PartialEq)]
+pub enum RoundingMode {
+    /// No rounding.
+    None,
+    /// Round half away from zero.
+    BiasUp,
+    /// Round half toward zero.
+    BiasDown,
+    /// Round away from zero.
+    Up,
+    /// Round toward zero.
+    Truncate,
+    /// Round half to even.
+    Bankers,
+}
+
+/// Currency precision and rounding utility.
+pub struct CurrencyUtility {}
+
+/// Currency precision and rounding utility implementation.
+impl CurrencyUtility {
+    /// Return the number of decimal digits (minor units) of a currency.
+    ///
+    /// # Arguments
+    ///
+    /// * `currency_code` - The international currency code (e.g., USD).
+    ///
+    /// # Return
+    ///
+    /// * See description or None if the code is not a currency code.
+
+    pub fn decimal_digits(currency_code: &str) -> Option<u32> {
+        let code = currency_code.trim().to_uppercase();
+        if code.len() != 3 || !code.chars().all(|c| c.is_ascii_alphabetic()) {
+            return None;
+        }
+
+        if CURRENCIES_DIGITS_0.contains(&code.as_str()) {
+            Some(0)
+        } else if CURRENCIES_DIGITS_3.contains(&code.as_str()) {
+            Some(3)
+        } else if CURRENCIES_DIGITS_4.contains(&code.as_str()) {
+            Some(4)
+        } else {
+            Some(2)
+        }
+    }
+
+    /// Parse a rounding mode mnemonic.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The mnemonic (none, bias-up, bias-down, up,
+    ///   truncate or bankers; half-up and half-down are also accepted).
+    ///
+    /// # Return
+    ///
+    /// * See description or None if not recognized.
+
+    pub fn parse_rounding_mode(value: &str) -> Option<RoundingMode> {
+        match value.trim().to_lowercase().as_str() {
+            "none" => Some(RoundingMode::None),
+            "bias-up" | "half-up" => Some(RoundingMode::BiasUp),
+            "bias-down" | "half-down" => Some(RoundingMode::BiasDown),
+            "up" => Some(RoundingMode::Up),
+            "truncate" | "down" => Some(RoundingMode::Truncate),
+            "bankers" => Some(RoundingMode::Bankers),
+            _ => None,
+        }
+    }
+
+    /// Return the mnemonic of a rounding mode.
+    ///
+    /// # Arguments
+    ///
+    /// * `mode` - The rounding mode.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn rounding_mode_mnemonic(mode: RoundingMode) -> &'static str {
+        match mode {
+            RoundingMode::None => "none",
+            RoundingMode::BiasUp => "bias-up",
+            RoundingMode::BiasDown => "bias-down",
+            RoundingMode::Up => "up",
+            RoundingMode::Truncate => "truncate",
+            RoundingMode::Bankers => "bankers",
+        }
+    }
+
+    /// Round a value to a number of decimal digits.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value to round.
+    /// * `decimal_digits` - The number of decimal digits.
+    /// * `mode` - The rounding mode.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn round(value: Decimal, decimal_digits: u32, mode: RoundingMode) -> Decimal {
+        let strategy = match mode {
+            RoundingMode::None => return value,
+            RoundingMode::BiasUp => RoundingStrategy::RoundHalfUp,
+            RoundingMode::BiasDown => RoundingStrategy::RoundHalfDown,
+            RoundingMode::Up => RoundingStrategy::RoundUp,
+            RoundingMode::Truncate => RoundingStrategy::RoundDown,
+            RoundingMode::Bankers => RoundingStrategy::BankersRounding,
+        };
+
+        value.round_dp_with_strategy(decimal_digits, strategy)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use amfnengine::*;
+
+    #[test]
+    fn decimal_digits_of_currencies() {
+        assert_eq!(CurrencyUtility::decimal_digits("usd"), Some(2));
+        assert_eq!(CurrencyUtility::decimal_digits(" JPY "), Some(0));
+        assert_eq!(CurrencyUtility::decimal_digits("KWD"), Some(3));
+        assert_eq!(CurrencyUtility::decimal_digits("CLF"), Some(4));
+        assert_eq!(CurrencyUtility::decimal_digits("US"), None);
+        assert_eq!(CurrencyUtility::decimal_digits("U$D"), None);
+    }
+
+    #[test]
+    fn rounding_mode_mnemonics_round_trip() {
+        for value in ["none", "bias-up", "bias-down", "up", "truncate", "bankers"].iter() {
+            let mode = CurrencyUtility::parse_rounding_mode(value).unwrap();
+            assert_eq!(CurrencyUtility::rounding_mode_mnemonic(mode), *value);
+        }
+        assert!(CurrencyUtility::parse_rounding_mode("half-up") == Some(RoundingMode::BiasUp));
+        assert!(CurrencyUtility::parse_rounding_mode("down") == Some(RoundingMode::Truncate));
+        assert!(CurrencyUtility::parse_rounding_mode("ceiling").is_none());
+    }
+
+    #[test]
+    fn round_by_mode() {
+        let round = |value, mode| CurrencyUtility::round(value, 2, mode);
+        assert_eq!(round(dec!(1.005), RoundingMode::None), dec!(1.005));
+        assert_eq!(round(dec!(1.005), RoundingMode::BiasUp), dec!(1.01));
+        assert_eq!(round(dec!(-1.005), RoundingMode::BiasUp), dec!(-1.01));
+        assert_eq!(round(dec!(1.005), RoundingMode::BiasDown), dec!(1.00));
+        assert_eq!(round(dec!(1.001), RoundingMode::Up), dec!(1.01));
+        assert_eq!(round(dec!(1.009), RoundingMode::Truncate), dec!(1.00));
+        assert_eq!(round(dec!(1.015), RoundingMode::Bankers), dec!(1.02));
+        assert_eq!(round(dec!(1.025), RoundingMode::Bankers), dec!(1.02));
+    }
+}
@@ -0,0 +1,358 @@
+//! Daily simple interest on actual payment dates.
+// Copyright (c) 2021 ShiftLeft Software
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use rust_decimal::prelude::*;
+
+use amfnengine::*;
+
+use crate::date_utility::*;
+use crate::effective_interest::*;
+
+/// Cashflow preferences descriptor group for actual transactions.
+pub const GROUP_ACTUAL: &str = "Actual";
+/// Cashflow preferences descriptor name for the ledger of actual payments.
+pub const NAME_ACTUAL_PAYMENTS: &str = "Payments";
+
+/// Actual payment received (a ledger transaction).
+#[derive(Clone)]
+pub struct ElemActualPayment {
+    /// Date the payment was received (YYYYMMDD).
+    event_date: usize,
+    /// Amount of the payment.
+    amount: Decimal,
+    /// Memo of the payment.
+    memo: String,
+}
+
+/// Actual payment implementation.
+impl ElemActualPayment {
+    /// Create and return an actual payment.
+    ///
+    /// # Arguments
+    ///
+    /// * `event_date_param` - Date the payment was received.
+    /// * `amount_param` - Amount of the payment.
+    /// * `memo_param` - Memo of the payment (the '~' and '|'
+    ///   separators are replaced by spaces).
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn new(
+        event_date_param: usize,
+        amount_param: Decimal,
+        memo_param: &str,
+    ) -> ElemActualPayment {
+        ElemActualPayment {
+            event_date: event_date_param,
+            amount: amount_param,
+            memo: memo_param
+                .replace(|c| c == '~' || c == '|', " ")
+                .trim()
+                .to_string(),
+        }
+    }
+
+    /// Get the date the payment was received.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn event_date(&self) -> usize {
+        self.event_date
+    }
+
+    /// Get the amount of the payment.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn amount(&self) -> Decimal {
+        self.amount
+    }
+
+    /// Get the memo of the payment.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn memo(&self) -> &str {
+        self.memo.as_str()
+    }
+}
+
+/// Daily simple interest row (one for each actual payment).
+pub struct ElemDailyInterestRow {
+    /// Date the payment was received (YYYYMMDD).
+    event_date: usize,
+    /// Amount of the payment.
+    payment: Decimal,
+    /// Days of interest accrued since the prior payment.
+    days: i64,
+    /// Interest accrued since the prior payment.
+    interest: Decimal,
+    /// Principal repaid by the payment.
+    principal: Decimal,
+    /// Accrued interest remaining unpaid after the payment.
+    unpaid_interest: Decimal,
+    /// Actual principal balance after the payment.
+    balance: Decimal,
+    /// Scheduled principal balance as of the date.
+    scheduled_balance: Decimal,
+}
+
+/// Daily simple interest row implementation.
+impl ElemDailyInterestRow {
+    /// Get the date the payment was received.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn event_date(&self) -> usize {
+        self.event_date
+    }
+
+    /// Get the amount of the payment.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn payment(&self) -> Decimal {
+        self.payment
+    }
+
+    /// Get the days of interest accrued.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn days(&self) -> i64 {
+        self.days
+    }
+
+    /// Get the interest accrued.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn interest(&self) -> Decimal {
+        self.interest
+    }
+
+    /// Get the principal repaid.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn principal(&self) -> Decimal {
+        self.principal
+    }
+
+    /// Get the accrued interest remaining unpaid.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn unpaid_interest(&self) -> Decimal {
+        self.unpaid_interest
+    }
+
+    /// Get the actual principal balance.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn balance(&self) -> Decimal {
+        self.balance
+    }
+
+    /// Get the scheduled principal balance.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn scheduled_balance(&self) -> Decimal {
+        self.scheduled_balance
+    }
+
+    /// Get the difference between the actual and scheduled balances.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn difference(&self) -> Decimal {
+        self.balance - self.scheduled_balance
+    }
+}
+
+/// Daily simple interest utility.
+pub struct DailyInterestUtility {}
+
+/// Daily simple interest utility implementation.
+impl DailyInterestUtility {
+    /// Parse the actual payments from their descriptor value
+    /// (date~amount~memo entries separated by '|' where the memo
+    /// is optional, e.g., "2022-02-03~500.00~Check 1021|2022-03-01~500.00").
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The descriptor value.
+    ///
+    /// # Return
+    ///
+    /// * The valid payments sorted by date.
+
+    pub fn parse_payments(value: &str) -> Vec<ElemActualPayment> {
+        let mut result: Vec<ElemActualPayment> = Vec::new();
+
+        for token in value.split('|') {
+            let mut fields = token.split('~');
+            let event_date = fields
+                .next()
+                .and_then(|o| o.trim().replace('-', "").parse::<usize>().ok())
+                .unwrap_or(0);
+            let amount = fields.next().and_then(|o| o.trim().parse::<Decimal>().ok());
+            let memo = fields.next().unwrap_or("");
+
+            if let (true, Some(o)) = (DateUtility::to_naive(event_date).is_some(), amount) {
+                result.push(ElemActualPayment::new(event_date, o, memo));
+            }
+        }
+
+        result.sort_by_key(|o| o.event_date());
+        result
+    }
+
+    /// Return the descriptor value of actual payments.
+    ///
+    /// # Arguments
+    ///
+    /// * `payments` - The actual payments.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn payments_value(payments: &[ElemActualPayment]) -> String {
+        payments
+            .iter()
+            .map(|o| {
+                if o.memo().is_empty() {
+                    format!(
+                        "{}~{}",
+                        DateUtility::format_date(o.event_date()),
+                        o.amount()
+                    )
+                } else {
+                    format!(
+                        "{}~{}~{}",
+                        DateUtility::format_date(o.event_date()),
+                        o.amount(),
+                        o.memo()
+                    )
+                }
+            })
+            .collect::<Vec<String>>()
+            .join("|")
+    }
+
+    /// Return the scheduled principal balance as of a date.
+    ///
+    /// # Arguments
+    ///
+    /// * `flows` - The scheduled cash flows (see ElemCashFlow::from_rows).
+    /// * `date` - The date (YYYYMMDD).
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn scheduled_balance(flows: &[ElemCashFlow], date: usize) -> Decimal {
+        flows
+            .iter()
+            .filter(|o| o.event_date() <= date)
+            .map(|o| o.interest() - o.cash())
+            .sum()
+    }
+
+    /// Recompute the amortization with daily simple interest (actual/365)
+    /// accrued between the actual payment dates. Each payment is applied
+    /// to the accrued interest first and then to principal.
+    ///
+    /// # Arguments
+    ///
+    /// * `flows` - The scheduled cash flows (see ElemCashFlow::from_rows).
+    /// * `payments` - The actual payments sorted by date.
+    /// * `rate` - The annual interest rate (percentage).
+    /// * `decimal_digits` - Number of decimal digits for rounding.
+    ///
+    /// # Return
+    ///
+    /// * One row for each actual payment or None if there is
+    ///   no disbursement.
+
+    pub fn schedule(
+        flows: &[ElemCashFlow],
+        payments: &[ElemActualPayment],
+        rate: Decimal,
+        decimal_digits: u32,
+    ) -> Option<Vec<ElemDailyInterestRow>> {
+        let first = flows.first()?;
+        let daily_rate = rate / dec!(100.0) / dec!(365.0);
+        let mut balance = -first.cash();
+        let mut unpaid_interest = dec!(0.0);
+        let mut prior_date = first.event_date();
+        let mut result: Vec<ElemDailyInterestRow> = Vec::new();
+
+        for payment in payments
+            .iter()
+            .filter(|o| o.event_date() >= first.event_date())
+        {
+            let days = DateUtility::days_between(prior_date, payment.event_date());
+            let interest = (balance * daily_rate * Decimal::from(days)).round_dp(decimal_digits);
+            unpaid_interest += interest;
+
+            let interest_paid = payment.amount().min(unpaid_interest);
+            let principal = payment.amount() - interest_paid;
+            unpaid_interest -= interest_paid;
+            balance -= principal;
+
+            result.push(ElemDailyInterestRow {
+                event_date: payment.event_date(),
+                payment: payment.amount(),
+                days,
+                interest,
+                principal,
+                unpaid_interest,
+                balance,
+                scheduled_balance: DailyInterestUtility::scheduled_balance(
+                    flows,
+                    payment.event_date(),
+                ),
+            });
+
+            prior_date = payment.event_date();
+        }
+
+        Some(result)
+    }
+}
@@ -0,0 +1,224 @@
+//! Calendar date utilities.
+// Copyright (c) 2021 ShiftLeft Software
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use chrono::{Datelike, Duration, NaiveDate};
+
+/// Calendar date utility. Dates are represented as usize values
+/// in YYYYMMDD format (the same representation used by the engine).
+pub struct DateUtility {}
+
+/// Calendar date utility implementation.
+impl DateUtility {
+    /// Convert a YYYYMMDD date to a calendar date.
+    ///
+    /// # Arguments
+    ///
+    /// * `date` - The date in YYYYMMDD format.
+    ///
+    /// # Return
+    ///
+    /// * See description or None if the date is invalid.
+
+    pub fn to_naive(date: usize) -> Option<NaiveDate> {
+        NaiveDate::from_ymd_opt(
+            (date / 10000) as i32,
+            (date / 100 % 100) as u32,
+            (date % 100) as u32,
+        )
+    }
+
+    /// Convert a calendar date to a YYYYMMDD date.
+    ///
+    /// # Arguments
+    ///
+    /// * `date` - The calendar date.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn from_naive(date: NaiveDate) -> usize {
+        date.year() as usize * 10000 + date.month() as usize * 100 + date.day() as usize
+    }
+
+    /// Format a YYYYMMDD date in the internal YYYY-MM-DD format.
+    ///
+    /// # Arguments
+    ///
+    /// * `date` - The date in YYYYMMDD format.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn format_date(date: usize) -> String {
+        format!(
+            "{:04}-{:02}-{:02}",
+            date / 10000,
+            date / 100 % 100,
+            date % 100
+        )
+    }
+
+    /// Calculate the number of days between two dates.
+    /// If date2 is greater than or equal to date1, the
+    /// result will be positive, otherwise the result
+    /// will be negative.
+    ///
+    /// # Arguments
+    ///
+    /// * `date1` - First date in YYYYMMDD format.
+    /// * `date2` - Second date in YYYYMMDD format.
+    ///
+    /// # Return
+    ///
+    /// * See description (zero if either date is invalid).
+
+    pub fn days_between(date1: usize, date2: usize) -> i64 {
+        match (DateUtility::to_naive(date1), DateUtility::to_naive(date2)) {
+            (Some(o1), Some(o2)) => (o2 - o1).num_days(),
+            _ => 0,
+        }
+    }
+
+    /// Add a number of days to a date.
+    ///
+    /// # Arguments
+    ///
+    /// * `date` - Date in YYYYMMDD format.
+    /// * `days` - Number of days (positive or negative).
+    ///
+    /// # Return
+    ///
+    /// * See description (the original date if invalid).
+
+    pub fn add_days(date: usize, days: i64) -> usize {
+        match DateUtility::to_naive(date) {
+            None => date,
+            Some(o) => DateUtility::from_naive(o + Duration::days(days)),
+        }
+    }
+
+    /// Add a number of months to a date. The day of month is
+    /// limited to the last day of the resulting month.
+    ///
+    /// # Arguments
+    ///
+    /// * `date` - Date in YYYYMMDD format.
+    /// * `months` - Number of months (positive or negative).
+    /// * `eom` - Adjust the result to the end of the month.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn add_months(date: usize, months: i32, eom: bool) -> usize {
+        let total = (date / 10000) as i32 * 12 + (date / 100 % 100) as i32 - 1 + months;
+        let year = total.div_euclid(12);
+        let month = total.rem_euclid(12) + 1;
+        let last_day = DateUtility::days_in_month(year, month as u32);
+
+        let day = if eom {
+            last_day
+        } else {
+            ((date % 100) as u32).min(last_day)
+        };
+
+        year as usize * 10000 + month as usize * 100 + day as usize
+    }
+
+    /// Return the number of days within a month.
+    ///
+    /// # Arguments
+    ///
+    /// * `year` - The year.
+    /// * `month` - The month (1 through 12).
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn days_in_month(year: i32, month: u32) -> u32 {
+        let (next_year, next_month) = if month >= 12 {
+            (year + 1, 1)
+        } else {
+            (year, month + 1)
+        };
+
+        match (
+            NaiveDate::from_ymd_opt(year, month, 1),
+            NaiveDate::from_ymd_opt(next_year, next_month, 1),
+        ) {
+            (Some(o1), Some(o2)) => (o2 - o1).num_days() as u32,
+            _ => 30,
+        }
+    }
+
+    /// Return the last day of the fiscal year containing a date.
+    ///
+    /// # Arguments
+    ///
+    /// * `date` - Date in YYYYMMDD format.
+    /// * `fiscal_year_start` - Start of the fiscal year in MMDD
+    ///   format (zero or invalid for January 1).
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn fiscal_year_end(date: usize, fiscal_year_start: usize) -> usize {
+        let (month, day) = if fiscal_year_start / 100 >= 1
+            && fiscal_year_start / 100 <= 12
+            && fiscal_year_start % 100 >= 1
+        {
+            (fiscal_year_start / 100, fiscal_year_start % 100)
+        } else {
+            (1, 1)
+        };
+
+        let year = date / 10000;
+        let day = day.min(DateUtility::days_in_month(year as i32, month as u32) as usize);
+        let start = year * 10000 + month * 100 + day;
+        let next_start = if date >= start {
+            DateUtility::add_months(start, 12, false)
+        } else {
+            start
+        };
+
+        DateUtility::add_days(next_start, -1)
+    }
+
+    /// Estimate the number of periods per year between two
+    /// successive dates of a periodic series.
+    ///
+    /// # Arguments
+    ///
+    /// * `date1` - First date in YYYYMMDD format.
+    /// * `date2` - Second date in YYYYMMDD format.
+    ///
+    /// # Return
+    ///
+    /// * See description or None if the dates are not increasing.
+
+    pub fn periods_in_year(date1: usize, date2: usize) -> Option<u32> {
+        match DateUtility::days_between(date1, date2) {
+            i64::MIN..=0 => None,
+            1 => Some(365),
+            2..=10 => Some(52),
+            11..=14 => Some(26),
+            15..=16 => Some(24),
+            17..=45 => Some(12),
+            46..=75 => Some(6),
+            76..=105 => Some(4),
+            106..=135 => Some(3),
+            136..=250 => Some(2),
+            _ => Some(1),
+        }
+    }
+}
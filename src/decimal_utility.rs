@@ -0,0 +1,205 @@
+//! Decimal math utilities.
+// Copyright (c) 2021 ShiftLeft Software
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use rust_decimal::prelude::*;
+
+use amfnengine::*;
+
+/// Maximum number of series terms to evaluate.
+const MAX_SERIES_TERMS: usize = 100;
+
+/// Decimal math utility.
+pub struct DecimalUtility {}
+
+/// Decimal math utility implementation.
+impl DecimalUtility {
+    /// Calculate e raised to the power of a value.
+    ///
+    /// # Arguments
+    ///
+    /// * `val` - The exponent.
+    ///
+    /// # Return
+    ///
+    /// * See description or None if the result overflows.
+
+    pub fn exp(val: Decimal) -> Option<Decimal> {
+        if val.is_zero() {
+            return Some(dec!(1.0));
+        }
+
+        let mut reduced = val;
+        let mut halvings: usize = 0;
+        while reduced.abs() > dec!(0.5) {
+            reduced /= dec!(2.0);
+            halvings += 1;
+        }
+
+        let mut result = dec!(1.0);
+        let mut term = dec!(1.0);
+        let mut index: usize = 1;
+        while index < MAX_SERIES_TERMS {
+            term = term * reduced / Decimal::from(index);
+            if term.is_zero() {
+                break;
+            }
+            result += term;
+            index += 1;
+        }
+
+        while halvings > 0 {
+            result = result.checked_mul(result)?;
+            halvings -= 1;
+        }
+
+        Some(result)
+    }
+
+    /// Calculate the natural logarithm of a value.
+    ///
+    /// # Arguments
+    ///
+    /// * `val` - The value (must be greater than zero).
+    ///
+    /// # Return
+    ///
+    /// * See description or None if the value is not positive.
+
+    pub fn ln(val: Decimal) -> Option<Decimal> {
+        if val <= dec!(0.0) {
+            return None;
+        }
+
+        let mut mantissa = val;
+        let mut power: i64 = 0;
+        while mantissa > dec!(2.0) {
+            mantissa /= dec!(2.0);
+            power += 1;
+        }
+        while mantissa < dec!(0.5) {
+            mantissa *= dec!(2.0);
+            power -= 1;
+        }
+
+        let z = (mantissa - dec!(1.0)) / (mantissa + dec!(1.0));
+        let z_squared = z * z;
+        let mut series = dec!(0.0);
+        let mut numerator = z;
+        let mut index: usize = 0;
+        while index < MAX_SERIES_TERMS {
+            let term = numerator / Decimal::from(2 * index + 1);
+            if term.is_zero() {
+                break;
+            }
+            series += term;
+            numerator *= z_squared;
+            index += 1;
+        }
+
+        Some(Decimal::from(power) * dec!(0.6931471805599453094172321215) + dec!(2.0) * series)
+    }
+
+    /// Raise a value to an integer power.
+    ///
+    /// # Arguments
+    ///
+    /// * `base` - The base value.
+    /// * `exponent` - The integer exponent (positive or negative).
+    ///
+    /// # Return
+    ///
+    /// * See description or None if the result overflows or is
+    ///   undefined (zero raised to a negative power).
+
+    pub fn powi(base: Decimal, exponent: i64) -> Option<Decimal> {
+        let mut result = dec!(1.0);
+        let mut factor = base;
+        let mut remaining = exponent.unsigned_abs();
+
+        while remaining > 0 {
+            if remaining & 1 == 1 {
+                result = result.checked_mul(factor)?;
+            }
+            remaining >>= 1;
+            if remaining > 0 {
+                factor = factor.checked_mul(factor)?;
+            }
+        }
+
+        if exponent < 0 {
+            if result.is_zero() {
+                return None;
+            }
+            return dec!(1.0).checked_div(result);
+        }
+
+        Some(result)
+    }
+
+    /// Raise a value to a decimal power.
+    ///
+    /// # Arguments
+    ///
+    /// * `base` - The base value.
+    /// * `exponent` - The decimal exponent.
+    ///
+    /// # Return
+    ///
+    /// * See description or None if the result is undefined.
+
+    pub fn powd(base: Decimal, exponent: Decimal) -> Option<Decimal> {
+        if exponent.fract().is_zero() {
+            return exponent
+                .to_i64()
+                .and_then(|o| DecimalUtility::powi(base, o));
+        }
+
+        DecimalUtility::exp(exponent.checked_mul(DecimalUtility::ln(base)?)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn powi_positive_and_negative_exponents() {
+        assert_eq!(DecimalUtility::powi(dec!(2.0), 10), Some(dec!(1024)));
+        assert_eq!(DecimalUtility::powi(dec!(2.0), -2), Some(dec!(0.25)));
+        assert_eq!(DecimalUtility::powi(dec!(5.0), 0), Some(dec!(1)));
+    }
+
+    #[test]
+    fn powi_zero_to_negative_power_is_undefined() {
+        assert_eq!(DecimalUtility::powi(dec!(0.0), -1), None);
+        assert_eq!(DecimalUtility::powi(dec!(0.0), 2), Some(dec!(0)));
+    }
+
+    #[test]
+    fn powi_overflow() {
+        assert_eq!(DecimalUtility::powi(dec!(10.0), 40), None);
+    }
+
+    #[test]
+    fn exp_and_ln_are_inverse() {
+        let e = DecimalUtility::exp(dec!(1.0)).unwrap();
+        assert_eq!(e.round_dp(10), dec!(2.7182818285));
+        assert_eq!(
+            DecimalUtility::ln(e).unwrap().round_dp(10),
+            dec!(1.0000000000)
+        );
+        assert_eq!(DecimalUtility::ln(dec!(0.0)), None);
+    }
+
+    #[test]
+    fn powd_fractional_exponent() {
+        let root = DecimalUtility::powd(dec!(4.0), dec!(0.5)).unwrap();
+        assert_eq!(root.round_dp(8), dec!(2.00000000));
+    }
+}
@@ -0,0 +1,608 @@
+//! Delinquency, grace period and late-fee modeling.
+// Copyright (c) 2021 ShiftLeft Software
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use rust_decimal::prelude::*;
+
+use amfnengine::*;
+
+use crate::daily_interest::*;
+use crate::date_utility::*;
+use crate::effective_interest::*;
+use crate::expression::*;
+
+/// Cashflow preferences descriptor group for delinquency.
+pub const GROUP_DELINQUENCY: &str = "Delinquency";
+/// Cashflow preferences descriptor name for the grace period and late-fee rules.
+pub const NAME_DELINQUENCY_RULES: &str = "Rules";
+/// Delinquency expression function.
+pub const FUNC_DELINQUENCY: &str = "delinquency";
+
+/// Late-fee type.
+#[derive(Clone, Copy, PartialEq)]
+pub enum LateFeeType {
+    /// Flat amount for each late payment.
+    Flat,
+    /// Percentage of each late payment's amount due.
+    Percent,
+}
+
+/// Status of a scheduled payment.
+#[derive(Clone, Copy, PartialEq)]
+pub enum PaymentStatus {
+    /// Not yet due.
+    Scheduled,
+    /// Paid in full within the grace period.
+    OnTime,
+    /// Paid in full after the grace period.
+    Late,
+    /// Past due but still within the grace period.
+    Grace,
+    /// Past due beyond the grace period.
+    Missed,
+}
+
+/// Grace period and late-fee rules.
+#[derive(Clone, Copy)]
+pub struct ElemDelinquencyRules {
+    /// Days after the due date before a payment is late.
+    grace_days: i64,
+    /// Late-fee type.
+    fee_type: LateFeeType,
+    /// Flat amount or percentage of the late fee.
+    fee: Decimal,
+    /// Maximum late fee for each payment (zero for no maximum).
+    cap: Decimal,
+}
+
+/// Grace period and late-fee rules implementation.
+impl ElemDelinquencyRules {
+    /// Create and return the default rules (no grace period and no late fees).
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn new() -> ElemDelinquencyRules {
+        ElemDelinquencyRules {
+            grace_days: 0,
+            fee_type: LateFeeType::Flat,
+            fee: dec!(0.0),
+            cap: dec!(0.0),
+        }
+    }
+
+    /// Parse the rules from their descriptor value (grace~type~fee~cap,
+    /// e.g., "15~flat~25" or "10~percent~5~50").
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The descriptor value.
+    ///
+    /// # Return
+    ///
+    /// * See description or None if not valid.
+
+    pub fn parse(value: &str) -> Option<ElemDelinquencyRules> {
+        let fields: Vec<&str> = value.split('~').map(|o| o.trim()).collect();
+
+        let grace_days = fields.first()?.parse::<i64>().ok()?;
+        let fee_type = match fields.get(1).map(|o| o.to_lowercase()) {
+            None => LateFeeType::Flat,
+            Some(o) if o.is_empty() || o == "flat" => LateFeeType::Flat,
+            Some(o) if o == "percent" || o == "%" => LateFeeType::Percent,
+            _ => return None,
+        };
+        let fee = match fields.get(2) {
+            None => dec!(0.0),
+            Some(o) => o.parse::<Decimal>().ok()?,
+        };
+        let cap = match fields.get(3) {
+            None => dec!(0.0),
+            Some(o) => o.parse::<Decimal>().ok()?,
+        };
+
+        if grace_days < 0 || fee < dec!(0.0) || cap < dec!(0.0) {
+            return None;
+        }
+
+        Some(ElemDelinquencyRules {
+            grace_days,
+            fee_type,
+            fee,
+            cap,
+        })
+    }
+
+    /// Return the descriptor value of the rules.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn descriptor_value(&self) -> String {
+        format!(
+            "{}~{}~{}~{}",
+            self.grace_days,
+            match self.fee_type {
+                LateFeeType::Flat => "flat",
+                LateFeeType::Percent => "percent",
+            },
+            self.fee,
+            self.cap
+        )
+    }
+
+    /// Get the days of the grace period.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn grace_days(&self) -> i64 {
+        self.grace_days
+    }
+
+    /// Get the late-fee type.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn fee_type(&self) -> LateFeeType {
+        self.fee_type
+    }
+
+    /// Get the flat amount or percentage of the late fee.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn fee(&self) -> Decimal {
+        self.fee
+    }
+
+    /// Get the maximum late fee for each payment.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn cap(&self) -> Decimal {
+        self.cap
+    }
+
+    /// Calculate the late fee of a payment.
+    ///
+    /// # Arguments
+    ///
+    /// * `amount_due` - The amount due of the payment.
+    /// * `decimal_digits` - Number of decimal digits for rounding.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn late_fee(&self, amount_due: Decimal, decimal_digits: u32) -> Decimal {
+        let fee = match self.fee_type {
+            LateFeeType::Flat => self.fee,
+            LateFeeType::Percent => (amount_due * self.fee / dec!(100.0)).round_dp(decimal_digits),
+        };
+
+        if self.cap > dec!(0.0) {
+            fee.min(self.cap)
+        } else {
+            fee
+        }
+    }
+}
+
+impl Default for ElemDelinquencyRules {
+    /// Create and return the default rules.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    fn default() -> Self {
+        ElemDelinquencyRules::new()
+    }
+}
+
+/// Missed or late payment record of a scheduled payment.
+pub struct ElemDelinquencyRecord {
+    /// Due date of the scheduled payment (YYYYMMDD).
+    due_date: usize,
+    /// Amount due.
+    amount_due: Decimal,
+    /// Amount paid on or before the as of date.
+    amount_paid: Decimal,
+    /// Date the payment was paid in full (zero if not paid in full).
+    paid_date: usize,
+    /// Days past the due date the payment was paid in full
+    /// (or remains unpaid as of the date).
+    days_late: i64,
+    /// Late fee assessed.
+    late_fee: Decimal,
+    /// Status of the payment.
+    status: PaymentStatus,
+}
+
+/// Missed or late payment record implementation.
+impl ElemDelinquencyRecord {
+    /// Get the due date of the scheduled payment.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn due_date(&self) -> usize {
+        self.due_date
+    }
+
+    /// Get the amount due.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn amount_due(&self) -> Decimal {
+        self.amount_due
+    }
+
+    /// Get the amount paid.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn amount_paid(&self) -> Decimal {
+        self.amount_paid
+    }
+
+    /// Get the date the payment was paid in full.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn paid_date(&self) -> usize {
+        self.paid_date
+    }
+
+    /// Get the days past the due date.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn days_late(&self) -> i64 {
+        self.days_late
+    }
+
+    /// Get the late fee assessed.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn late_fee(&self) -> Decimal {
+        self.late_fee
+    }
+
+    /// Get the status of the payment.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn status(&self) -> PaymentStatus {
+        self.status
+    }
+}
+
+/// Delinquency as of a date.
+pub struct ElemDelinquency {
+    /// Unpaid amount of the payments past their due dates.
+    past_due: Decimal,
+    /// Days since the due date of the oldest unpaid payment.
+    days_delinquent: i64,
+    /// Total late fees assessed.
+    late_fees: Decimal,
+    /// Number of payments past due beyond the grace period.
+    missed_payments: usize,
+    /// Number of payments paid in full after the grace period.
+    late_payments: usize,
+}
+
+/// Delinquency implementation.
+impl ElemDelinquency {
+    /// Get the unpaid amount past due.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn past_due(&self) -> Decimal {
+        self.past_due
+    }
+
+    /// Get the days delinquent.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn days_delinquent(&self) -> i64 {
+        self.days_delinquent
+    }
+
+    /// Get the total late fees assessed.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn late_fees(&self) -> Decimal {
+        self.late_fees
+    }
+
+    /// Get the number of missed payments.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn missed_payments(&self) -> usize {
+        self.missed_payments
+    }
+
+    /// Get the number of late payments.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn late_payments(&self) -> usize {
+        self.late_payments
+    }
+}
+
+/// Delinquency utility.
+pub struct DelinquencyUtility {}
+
+/// Delinquency utility implementation.
+impl DelinquencyUtility {
+    /// Return the mnemonic of a payment status.
+    ///
+    /// # Arguments
+    ///
+    /// * `status` - The payment status.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn status_mnemonic(status: PaymentStatus) -> &'static str {
+        match status {
+            PaymentStatus::Scheduled => "scheduled",
+            PaymentStatus::OnTime => "on-time",
+            PaymentStatus::Late => "late",
+            PaymentStatus::Grace => "grace",
+            PaymentStatus::Missed => "missed",
+        }
+    }
+
+    /// Create the missed or late payment record of each scheduled
+    /// payment as of a date. The actual payments received on or
+    /// before the date are applied to the oldest scheduled payments
+    /// first. A late fee is assessed once for each scheduled payment
+    /// not paid in full by the end of its grace period.
+    ///
+    /// # Arguments
+    ///
+    /// * `flows` - The scheduled cash flows (see ElemCashFlow::from_rows).
+    /// * `payments` - The actual payments sorted by date.
+    /// * `rules` - The grace period and late-fee rules.
+    /// * `as_of` - The as of date (YYYYMMDD).
+    /// * `decimal_digits` - Number of decimal digits for rounding.
+    ///
+    /// # Return
+    ///
+    /// * One record for each scheduled payment.
+
+    pub fn records(
+        flows: &[ElemCashFlow],
+        payments: &[ElemActualPayment],
+        rules: &ElemDelinquencyRules,
+        as_of: usize,
+        decimal_digits: u32,
+    ) -> Vec<ElemDelinquencyRecord> {
+        let mut received = payments
+            .iter()
+            .filter(|o| o.event_date() <= as_of && o.amount() > dec!(0.0))
+            .map(|o| (o.event_date(), o.amount()));
+        let mut current = received.next();
+        let mut result: Vec<ElemDelinquencyRecord> = Vec::new();
+
+        for flow in flows.iter().skip(1).filter(|o| o.cash() > dec!(0.0)) {
+            let amount_due = flow.cash();
+            let mut amount_paid = dec!(0.0);
+            let mut paid_date: usize = 0;
+
+            while let Some((date, amount)) = current {
+                let applied = amount.min(amount_due - amount_paid);
+                amount_paid += applied;
+                if amount == applied {
+                    current = received.next();
+                } else {
+                    current = Some((date, amount - applied));
+                }
+                if amount_paid == amount_due {
+                    paid_date = date;
+                    break;
+                }
+            }
+
+            let grace_end = DateUtility::add_days(flow.event_date(), rules.grace_days());
+            let (days_late, status) = if paid_date > 0 {
+                let days = DateUtility::days_between(flow.event_date(), paid_date).max(0);
+                if paid_date > grace_end {
+                    (days, PaymentStatus::Late)
+                } else {
+                    (days, PaymentStatus::OnTime)
+                }
+            } else if flow.event_date() >= as_of {
+                (0, PaymentStatus::Scheduled)
+            } else {
+                let days = DateUtility::days_between(flow.event_date(), as_of);
+                if as_of > grace_end {
+                    (days, PaymentStatus::Missed)
+                } else {
+                    (days, PaymentStatus::Grace)
+                }
+            };
+
+            let late_fee = match status {
+                PaymentStatus::Late | PaymentStatus::Missed => {
+                    rules.late_fee(amount_due, decimal_digits)
+                }
+                _ => dec!(0.0),
+            };
+
+            result.push(ElemDelinquencyRecord {
+                due_date: flow.event_date(),
+                amount_due,
+                amount_paid,
+                paid_date,
+                days_late,
+                late_fee,
+                status,
+            });
+        }
+
+        result
+    }
+
+    /// Summarize the delinquency of payment records.
+    ///
+    /// # Arguments
+    ///
+    /// * `records` - The payment records (see records).
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn summary(records: &[ElemDelinquencyRecord]) -> ElemDelinquency {
+        let past_due = records
+            .iter()
+            .filter(|o| o.status() == PaymentStatus::Grace || o.status() == PaymentStatus::Missed);
+
+        ElemDelinquency {
+            past_due: past_due
+                .clone()
+                .map(|o| o.amount_due() - o.amount_paid())
+                .sum(),
+            days_delinquent: past_due.map(|o| o.days_late()).max().unwrap_or(0),
+            late_fees: records.iter().map(|o| o.late_fee()).sum(),
+            missed_payments: records
+                .iter()
+                .filter(|o| o.status() == PaymentStatus::Missed)
+                .count(),
+            late_payments: records
+                .iter()
+                .filter(|o| o.status() == PaymentStatus::Late)
+                .count(),
+        }
+    }
+
+    /// Return a delinquency value by name as of a date.
+    ///
+    /// # Arguments
+    ///
+    /// * `flows` - The scheduled cash flows (see ElemCashFlow::from_rows).
+    /// * `payments` - The actual payments sorted by date.
+    /// * `rules` - The grace period and late-fee rules.
+    /// * `name` - The value name (past-due, days, fees, missed or late).
+    /// * `date` - The as of date (zero for the date of the
+    ///   last actual payment).
+    /// * `decimal_digits` - Number of decimal digits for rounding.
+    ///
+    /// # Return
+    ///
+    /// * See description or None if the name is not recognized.
+
+    pub fn value(
+        flows: &[ElemCashFlow],
+        payments: &[ElemActualPayment],
+        rules: &ElemDelinquencyRules,
+        name: &str,
+        date: usize,
+        decimal_digits: u32,
+    ) -> Option<Decimal> {
+        let as_of = if date == 0 {
+            payments.last().map_or(0, |o| o.event_date())
+        } else {
+            date
+        };
+        let summary = DelinquencyUtility::summary(&DelinquencyUtility::records(
+            flows,
+            payments,
+            rules,
+            as_of,
+            decimal_digits,
+        ));
+
+        match name.trim().to_lowercase().as_str() {
+            "past-due" => Some(summary.past_due()),
+            "days" => Some(Decimal::from(summary.days_delinquent())),
+            "fees" => Some(summary.late_fees()),
+            "missed" => Some(Decimal::from(summary.missed_payments())),
+            "late" => Some(Decimal::from(summary.late_payments())),
+            _ => None,
+        }
+    }
+
+    /// Determine whether an expression contains any delinquency functions.
+    ///
+    /// # Arguments
+    ///
+    /// * `expr` - The expression to search.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn has_functions(expr: &str) -> bool {
+        expr.to_ascii_lowercase()
+            .contains(format!("{}(", FUNC_DELINQUENCY).as_str())
+    }
+
+    /// Expand the delinquency functions found within an expression
+    /// (e.g., delinquency("past-due", date)) into their literal results.
+    ///
+    /// # Arguments
+    ///
+    /// * `expr` - The expression to expand.
+    /// * `eval` - Evaluates an argument expression and returns its value.
+    /// * `value` - Returns a delinquency value from a name and date.
+    ///
+    /// # Return
+    ///
+    /// * The expanded expression.
+
+    pub fn expand_expression(
+        expr: &str,
+        eval: &dyn Fn(&str) -> String,
+        value: &dyn Fn(&str, usize) -> Option<Decimal>,
+    ) -> String {
+        ExpressionUtility::expand_value_function(expr, FUNC_DELINQUENCY, eval, value)
+    }
+}
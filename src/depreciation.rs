@@ -0,0 +1,680 @@
+//! Depreciation (SL, DB, SYD and MACRS) schedules.
+// Copyright (c) 2021 ShiftLeft Software
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use rust_decimal::prelude::*;
+
+use amfnengine::*;
+
+use crate::date_utility::*;
+use crate::expression::*;
+
+/// Event descriptor group for depreciation.
+pub const GROUP_DEPRECIATION: &str = "Depreciation";
+/// Event descriptor name for the depreciation method.
+pub const NAME_DEPRECIATION_METHOD: &str = "Method";
+
+/// Depreciation expression function name.
+pub const FUNC_DEPRECIATION: &str = "depreciation";
+
+/// MACRS general depreciation system half-year convention
+/// percentages (in thousandths of a percent) by recovery period.
+const MACRS_3_YEAR: [i64; 4] = [33330, 44450, 14810, 7410];
+const MACRS_5_YEAR: [i64; 6] = [20000, 32000, 19200, 11520, 11520, 5760];
+const MACRS_7_YEAR: [i64; 8] = [14290, 24490, 17490, 12490, 8930, 8920, 8930, 4460];
+const MACRS_10_YEAR: [i64; 11] = [
+    10000, 18000, 14400, 11520, 9220, 7370, 6550, 6550, 6560, 6550, 3280,
+];
+const MACRS_15_YEAR: [i64; 16] = [
+    5000, 9500, 8550, 7700, 6930, 6230, 5900, 5900, 5910, 5900, 5910, 5900, 5910, 5900, 5910, 2950,
+];
+const MACRS_20_YEAR: [i64; 21] = [
+    3750, 7219, 6677, 6177, 5713, 5285, 4888, 4522, 4462, 4461, 4462, 4461, 4462, 4461, 4462, 4461,
+    4462, 4461, 4462, 4461, 2231,
+];
+
+/// Depreciation method.
+#[derive(Clone, Copy, PartialEq)]
+pub enum DepreciationMethod {
+    /// Straight-line.
+    StraightLine,
+    /// Declining balance (switching to straight-line).
+    DecliningBalance,
+    /// Sum-of-years-digits.
+    SumOfYearsDigits,
+    /// Modified accelerated cost recovery system (GDS tables).
+    Macrs,
+}
+
+/// First year convention.
+#[derive(Clone, Copy, PartialEq)]
+pub enum DepreciationConvention {
+    /// Depreciation starts with the month placed in service.
+    FullMonth,
+    /// One half year of depreciation in the first fiscal year.
+    HalfYear,
+}
+
+/// Depreciation method element.
+#[derive(Clone, Copy)]
+pub struct ElemDepreciation {
+    /// Depreciation method.
+    method: DepreciationMethod,
+    /// Useful life (or MACRS recovery period) in years.
+    life: u32,
+    /// Salvage value (ignored for MACRS).
+    salvage: Decimal,
+    /// First year convention.
+    convention: DepreciationConvention,
+    /// Declining balance percentage (e.g., 200 for double declining).
+    factor: Decimal,
+}
+
+/// Depreciation method implementation.
+impl ElemDepreciation {
+    /// Parse a depreciation method from its descriptor value
+    /// (method~life~salvage~convention~factor, e.g., "SL~5~1000",
+    /// "DB~7~0~HY~150", "SYD~10" or "MACRS~5").
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The descriptor value.
+    ///
+    /// # Return
+    ///
+    /// * See description or None if the value is invalid.
+
+    pub fn parse(value: &str) -> Option<ElemDepreciation> {
+        let tokens: Vec<&str> = value.split('~').map(|o| o.trim()).collect();
+
+        let method = match tokens.first()?.to_uppercase().as_str() {
+            "SL" => DepreciationMethod::StraightLine,
+            "DB" => DepreciationMethod::DecliningBalance,
+            "SYD" => DepreciationMethod::SumOfYearsDigits,
+            "MACRS" => DepreciationMethod::Macrs,
+            _ => return None,
+        };
+
+        let life = tokens.get(1)?.parse::<u32>().ok()?;
+        if life == 0 {
+            return None;
+        }
+
+        let salvage = match tokens.get(2) {
+            None => dec!(0.0),
+            Some(o) if o.is_empty() => dec!(0.0),
+            Some(o) => o.parse::<Decimal>().ok()?,
+        };
+
+        let convention = match tokens.get(3).map(|o| o.to_uppercase()) {
+            None => DepreciationConvention::FullMonth,
+            Some(o) => match o.as_str() {
+                "" | "FM" => DepreciationConvention::FullMonth,
+                "HY" => DepreciationConvention::HalfYear,
+                _ => return None,
+            },
+        };
+
+        let factor = match tokens.get(4) {
+            None => dec!(200.0),
+            Some(o) if o.is_empty() => dec!(200.0),
+            Some(o) => o.parse::<Decimal>().ok()?,
+        };
+
+        if method == DepreciationMethod::Macrs {
+            ElemDepreciation::macrs_table(life)?;
+            return Some(ElemDepreciation {
+                method,
+                life,
+                salvage: dec!(0.0),
+                convention: DepreciationConvention::HalfYear,
+                factor: dec!(200.0),
+            });
+        }
+
+        if salvage < dec!(0.0) || factor <= dec!(0.0) {
+            return None;
+        }
+
+        Some(ElemDepreciation {
+            method,
+            life,
+            salvage,
+            convention,
+            factor,
+        })
+    }
+
+    /// Get the depreciation method.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn method(&self) -> DepreciationMethod {
+        self.method
+    }
+
+    /// Get the useful life in years.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn life(&self) -> u32 {
+        self.life
+    }
+
+    /// Get the salvage value.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn salvage(&self) -> Decimal {
+        self.salvage
+    }
+
+    /// Get the first year convention.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn convention(&self) -> DepreciationConvention {
+        self.convention
+    }
+
+    /// Get the declining balance percentage.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn factor(&self) -> Decimal {
+        self.factor
+    }
+
+    /// Return the descriptor value for the method.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn descriptor_value(&self) -> String {
+        let mnemonic = match self.method {
+            DepreciationMethod::StraightLine => "SL",
+            DepreciationMethod::DecliningBalance => "DB",
+            DepreciationMethod::SumOfYearsDigits => "SYD",
+            DepreciationMethod::Macrs => return format!("MACRS~{}", self.life),
+        };
+        let convention = match self.convention {
+            DepreciationConvention::FullMonth => "FM",
+            DepreciationConvention::HalfYear => "HY",
+        };
+
+        if self.method == DepreciationMethod::DecliningBalance {
+            format!(
+                "{}~{}~{}~{}~{}",
+                mnemonic, self.life, self.salvage, convention, self.factor
+            )
+        } else {
+            format!("{}~{}~{}~{}", mnemonic, self.life, self.salvage, convention)
+        }
+    }
+
+    /// Return the MACRS percentages of a recovery period.
+    ///
+    /// # Arguments
+    ///
+    /// * `life` - The recovery period in years.
+    ///
+    /// # Return
+    ///
+    /// * See description or None if the recovery period is not supported.
+
+    fn macrs_table(life: u32) -> Option<&'static [i64]> {
+        match life {
+            3 => Some(&MACRS_3_YEAR),
+            5 => Some(&MACRS_5_YEAR),
+            7 => Some(&MACRS_7_YEAR),
+            10 => Some(&MACRS_10_YEAR),
+            15 => Some(&MACRS_15_YEAR),
+            20 => Some(&MACRS_20_YEAR),
+            _ => None,
+        }
+    }
+}
+
+/// Depreciable asset (a depreciation event).
+#[derive(Clone, Copy)]
+pub struct ElemDepreciationAsset {
+    /// Date the asset is placed in service (YYYYMMDD).
+    event_date: usize,
+    /// Cost of the asset.
+    cost: Decimal,
+    /// Depreciation method.
+    depreciation: ElemDepreciation,
+}
+
+/// Depreciable asset implementation.
+impl ElemDepreciationAsset {
+    /// Create and return a depreciable asset.
+    ///
+    /// # Arguments
+    ///
+    /// * `event_date_param` - Date the asset is placed in service.
+    /// * `cost_param` - Cost of the asset.
+    /// * `depreciation_param` - Depreciation method.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn new(
+        event_date_param: usize,
+        cost_param: Decimal,
+        depreciation_param: ElemDepreciation,
+    ) -> ElemDepreciationAsset {
+        ElemDepreciationAsset {
+            event_date: event_date_param,
+            cost: cost_param,
+            depreciation: depreciation_param,
+        }
+    }
+
+    /// Get the date the asset is placed in service.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn event_date(&self) -> usize {
+        self.event_date
+    }
+
+    /// Get the cost of the asset.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn cost(&self) -> Decimal {
+        self.cost
+    }
+
+    /// Get the depreciation method.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn depreciation(&self) -> &ElemDepreciation {
+        &self.depreciation
+    }
+}
+
+/// Depreciation schedule row (one for each fiscal year).
+pub struct ElemDepreciationRow {
+    /// Last day of the fiscal year (YYYYMMDD).
+    event_date: usize,
+    /// Depreciation of the fiscal year.
+    depreciation: Decimal,
+    /// Accumulated depreciation at the end of the fiscal year.
+    accumulated: Decimal,
+    /// Book value (cost less accumulated depreciation).
+    book_value: Decimal,
+}
+
+/// Depreciation schedule row implementation.
+impl ElemDepreciationRow {
+    /// Get the last day of the fiscal year.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn event_date(&self) -> usize {
+        self.event_date
+    }
+
+    /// Get the depreciation of the fiscal year.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn depreciation(&self) -> Decimal {
+        self.depreciation
+    }
+
+    /// Get the accumulated depreciation.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn accumulated(&self) -> Decimal {
+        self.accumulated
+    }
+
+    /// Get the book value.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn book_value(&self) -> Decimal {
+        self.book_value
+    }
+}
+
+/// Depreciation utility.
+pub struct DepreciationUtility {}
+
+/// Depreciation utility implementation.
+impl DepreciationUtility {
+    /// Calculate the depreciation of each fiscal year of an asset.
+    /// Straight-line and sum-of-years-digits depreciation of each
+    /// year of the asset's life is prorated across the fiscal years
+    /// it spans; declining balance depreciation switches to
+    /// straight-line when that is larger; MACRS applies the table
+    /// percentages to the cost. The last year absorbs rounding.
+    ///
+    /// # Arguments
+    ///
+    /// * `asset` - The depreciable asset.
+    /// * `fiscal_year_start` - Start of the fiscal year (MMDD).
+    /// * `decimal_digits` - Number of decimal digits for rounding.
+    ///
+    /// # Return
+    ///
+    /// * The fiscal year end dates and depreciation amounts.
+
+    pub fn asset_schedule(
+        asset: &ElemDepreciationAsset,
+        fiscal_year_start: usize,
+        decimal_digits: u32,
+    ) -> Vec<(usize, Decimal)> {
+        let depreciation = asset.depreciation();
+        let life = Decimal::from(depreciation.life());
+        let basis = (asset.cost() - depreciation.salvage()).max(dec!(0.0));
+        let first_year_end = DateUtility::fiscal_year_end(asset.event_date(), fiscal_year_start);
+
+        let first_fraction = match depreciation.convention() {
+            DepreciationConvention::HalfYear => dec!(0.5),
+            DepreciationConvention::FullMonth => {
+                let months = (first_year_end / 10000 * 12 + first_year_end / 100 % 100)
+                    - (asset.event_date() / 10000 * 12 + asset.event_date() / 100 % 100)
+                    + 1;
+                Decimal::from(months.min(12)) / dec!(12.0)
+            }
+        };
+
+        let mut amounts: Vec<Decimal> = Vec::new();
+        match depreciation.method() {
+            DepreciationMethod::StraightLine | DepreciationMethod::SumOfYearsDigits => {
+                let digits = life * (life + dec!(1.0)) / dec!(2.0);
+                let year_amount = |year: u32| -> Decimal {
+                    if year == 0 || year > depreciation.life() {
+                        return dec!(0.0);
+                    }
+                    if depreciation.method() == DepreciationMethod::StraightLine {
+                        basis / life
+                    } else {
+                        basis * (life - Decimal::from(year - 1)) / digits
+                    }
+                };
+
+                let years = if first_fraction < dec!(1.0) {
+                    depreciation.life() + 1
+                } else {
+                    depreciation.life()
+                };
+                for year in 1..=years {
+                    amounts.push(
+                        first_fraction * year_amount(year)
+                            + (dec!(1.0) - first_fraction) * year_amount(year - 1),
+                    );
+                }
+            }
+            DepreciationMethod::DecliningBalance => {
+                let rate = depreciation.factor() / dec!(100.0) / life;
+                let mut book_value = asset.cost();
+                let mut elapsed = dec!(0.0);
+                while elapsed < life && book_value > depreciation.salvage() {
+                    let portion = if elapsed.is_zero() {
+                        first_fraction
+                    } else {
+                        (life - elapsed).min(dec!(1.0))
+                    };
+                    let declining = book_value * rate * portion;
+                    let straight_line =
+                        (book_value - depreciation.salvage()) * portion / (life - elapsed);
+                    let amount = declining
+                        .max(straight_line)
+                        .min(book_value - depreciation.salvage());
+                    amounts.push(amount);
+                    book_value -= amount;
+                    elapsed += portion;
+                }
+            }
+            DepreciationMethod::Macrs => {
+                if let Some(o) = ElemDepreciation::macrs_table(depreciation.life()) {
+                    for percent in o.iter() {
+                        amounts.push(asset.cost() * Decimal::new(*percent, 3) / dec!(100.0));
+                    }
+                }
+            }
+        }
+
+        let mut result: Vec<(usize, Decimal)> = Vec::new();
+        let mut total = dec!(0.0);
+        let mut year_end = first_year_end;
+        for (year_index, amount) in amounts.iter().enumerate() {
+            let amount = if year_index == amounts.len() - 1 {
+                basis - total
+            } else {
+                amount.round_dp(decimal_digits)
+            };
+            total += amount;
+            result.push((year_end, amount));
+            year_end =
+                DateUtility::fiscal_year_end(DateUtility::add_days(year_end, 1), fiscal_year_start);
+        }
+
+        result
+    }
+
+    /// Create the consolidated depreciation schedule of several assets.
+    ///
+    /// # Arguments
+    ///
+    /// * `assets` - The depreciable assets.
+    /// * `fiscal_year_start` - Start of the fiscal year (MMDD).
+    /// * `decimal_digits` - Number of decimal digits for rounding.
+    ///
+    /// # Return
+    ///
+    /// * One row for each fiscal year.
+
+    pub fn schedule(
+        assets: &[ElemDepreciationAsset],
+        fiscal_year_start: usize,
+        decimal_digits: u32,
+    ) -> Vec<ElemDepreciationRow> {
+        let mut amounts: Vec<(usize, Decimal)> = assets
+            .iter()
+            .flat_map(|o| DepreciationUtility::asset_schedule(o, fiscal_year_start, decimal_digits))
+            .collect();
+        amounts.sort_by_key(|o| o.0);
+
+        let mut result: Vec<ElemDepreciationRow> = Vec::new();
+        let mut accumulated = dec!(0.0);
+        for (year_end, amount) in amounts.iter() {
+            accumulated += *amount;
+            let book_value = DepreciationUtility::cost(assets, *year_end) - accumulated;
+            match result.last_mut() {
+                Some(o) if o.event_date == *year_end => {
+                    o.depreciation += *amount;
+                    o.accumulated = accumulated;
+                    o.book_value = book_value;
+                }
+                _ => result.push(ElemDepreciationRow {
+                    event_date: *year_end,
+                    depreciation: *amount,
+                    accumulated,
+                    book_value,
+                }),
+            }
+        }
+
+        result
+    }
+
+    /// Return the cost of the assets placed in service on or before a date.
+    ///
+    /// # Arguments
+    ///
+    /// * `assets` - The depreciable assets.
+    /// * `date` - The date (YYYYMMDD) or zero for all assets.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn cost(assets: &[ElemDepreciationAsset], date: usize) -> Decimal {
+        assets
+            .iter()
+            .filter(|o| date == 0 || o.event_date() <= date)
+            .map(|o| o.cost())
+            .sum()
+    }
+
+    /// Return the accumulated depreciation as of a date.
+    ///
+    /// # Arguments
+    ///
+    /// * `rows` - The depreciation schedule.
+    /// * `date` - The date (YYYYMMDD) or zero for the full schedule.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn accumulated(rows: &[ElemDepreciationRow], date: usize) -> Decimal {
+        rows.iter()
+            .filter(|o| date == 0 || o.event_date() <= date)
+            .last()
+            .map_or(dec!(0.0), |o| o.accumulated())
+    }
+
+    /// Return the depreciation of the fiscal years ending after
+    /// one date and on or before another.
+    ///
+    /// # Arguments
+    ///
+    /// * `rows` - The depreciation schedule.
+    /// * `from_date` - The starting date (exclusive).
+    /// * `to_date` - The ending date (inclusive).
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn period(rows: &[ElemDepreciationRow], from_date: usize, to_date: usize) -> Decimal {
+        rows.iter()
+            .filter(|o| o.event_date() > from_date && o.event_date() <= to_date)
+            .map(|o| o.depreciation())
+            .sum()
+    }
+
+    /// Evaluate a depreciation function value.
+    ///
+    /// # Arguments
+    ///
+    /// * `assets` - The depreciable assets.
+    /// * `rows` - The depreciation schedule.
+    /// * `fiscal_year_start` - Start of the fiscal year (MMDD).
+    /// * `name` - The value name (cost, total, accumulated,
+    ///   book-value or expense).
+    /// * `date` - The date (YYYYMMDD) or zero for the full schedule.
+    ///
+    /// # Return
+    ///
+    /// * See description or None if the name is not recognized.
+
+    pub fn value(
+        assets: &[ElemDepreciationAsset],
+        rows: &[ElemDepreciationRow],
+        fiscal_year_start: usize,
+        name: &str,
+        date: usize,
+    ) -> Option<Decimal> {
+        match name.trim().to_lowercase().as_str() {
+            "cost" => Some(DepreciationUtility::cost(assets, date)),
+            "total" => Some(DepreciationUtility::accumulated(rows, 0)),
+            "accumulated" => Some(DepreciationUtility::accumulated(rows, date)),
+            "book-value" => Some(
+                DepreciationUtility::cost(assets, date)
+                    - DepreciationUtility::accumulated(rows, date),
+            ),
+            "expense" => {
+                let year_end = if date == 0 {
+                    rows.first().map_or(0, |o| o.event_date())
+                } else {
+                    DateUtility::fiscal_year_end(date, fiscal_year_start)
+                };
+                Some(
+                    rows.iter()
+                        .filter(|o| o.event_date() == year_end)
+                        .map(|o| o.depreciation())
+                        .sum(),
+                )
+            }
+            _ => None,
+        }
+    }
+
+    /// Determine whether an expression contains any depreciation functions.
+    ///
+    /// # Arguments
+    ///
+    /// * `expr` - The expression to search.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn has_functions(expr: &str) -> bool {
+        expr.to_ascii_lowercase()
+            .contains(format!("{}(", FUNC_DEPRECIATION).as_str())
+    }
+
+    /// Expand the depreciation functions found within an expression
+    /// (e.g., depreciation("book-value", date)) into their literal
+    /// results. Function arguments are evaluated with the given closure.
+    ///
+    /// # Arguments
+    ///
+    /// * `expr` - The expression to expand.
+    /// * `eval` - Evaluates an argument expression and returns its value.
+    /// * `value` - Returns a depreciation value from a name and date.
+    ///
+    /// # Return
+    ///
+    /// * The expanded expression.
+
+    pub fn expand_expression(
+        expr: &str,
+        eval: &dyn Fn(&str) -> String,
+        value: &dyn Fn(&str, usize) -> Option<Decimal>,
+    ) -> String {
+        ExpressionUtility::expand_value_function(expr, FUNC_DEPRECIATION, eval, value)
+    }
+}
@@ -0,0 +1,334 @@
+//! Effective interest method (amortized cost) schedules.
+// Copyright (c) 2021 ShiftLeft Software
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use rust_decimal::prelude::*;
+
+use amfnengine::*;
+
+use crate::am_row::*;
+use crate::date_utility::*;
+use crate::decimal_utility::*;
+
+/// Maximum number of iterations when solving for the effective rate.
+const EFFECTIVE_RATE_ITERATIONS: usize = 200;
+/// Lower bracket of the effective rate search (raised toward zero
+/// while the present value overflows for long schedules).
+const EFFECTIVE_RATE_LOW: Decimal = dec!(-0.5);
+
+/// Dated cash flow of a cashflow's amortization (lender's perspective).
+#[derive(Clone, Copy)]
+pub struct ElemCashFlow {
+    /// Date of the cash flow (YYYYMMDD).
+    event_date: usize,
+    /// Cash received (negative for cash disbursed).
+    cash: Decimal,
+    /// Contractual interest accrued up to the date.
+    interest: Decimal,
+}
+
+/// Dated cash flow implementation.
+impl ElemCashFlow {
+    /// Group amortization rows into dated cash flows. The flows are
+    /// oriented so that the initial disbursement is negative, whether
+    /// the cashflow models it as a principal increase or decrease.
+    ///
+    /// # Arguments
+    ///
+    /// * `rows` - The expanded amortization rows.
+    ///
+    /// # Return
+    ///
+    /// * One cash flow for each date.
+
+    pub fn from_rows(rows: &[ElemAmRow]) -> Vec<ElemCashFlow> {
+        let mut result: Vec<ElemCashFlow> = Vec::new();
+
+        for row in rows.iter() {
+            let cash = row.principal_decrease() - row.principal_increase();
+            match result.last_mut() {
+                Some(o) if o.event_date == row.event_date() => {
+                    o.cash += cash;
+                    o.interest += row.interest();
+                }
+                _ => result.push(ElemCashFlow {
+                    event_date: row.event_date(),
+                    cash,
+                    interest: row.interest(),
+                }),
+            }
+        }
+
+        if result.first().map_or(false, |o| o.cash > dec!(0.0)) {
+            for flow in result.iter_mut() {
+                flow.cash = -flow.cash;
+            }
+        }
+        for flow in result.iter_mut() {
+            flow.interest = flow.interest.abs();
+        }
+
+        result
+    }
+
+    /// Get the date of the cash flow.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn event_date(&self) -> usize {
+        self.event_date
+    }
+
+    /// Get the cash received.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn cash(&self) -> Decimal {
+        self.cash
+    }
+
+    /// Get the contractual interest.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn interest(&self) -> Decimal {
+        self.interest
+    }
+}
+
+/// Effective interest schedule row.
+pub struct ElemEffectiveRow {
+    /// Date of the row (YYYYMMDD).
+    event_date: usize,
+    /// Cash received.
+    cash: Decimal,
+    /// Contractual interest.
+    contractual_interest: Decimal,
+    /// Interest income at the effective rate.
+    interest_income: Decimal,
+    /// Fee amortization (interest income less contractual interest).
+    fee_amortization: Decimal,
+    /// Carrying amount (amortized cost) after the row.
+    carrying_amount: Decimal,
+}
+
+/// Effective interest schedule row implementation.
+impl ElemEffectiveRow {
+    /// Get the date of the row.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn event_date(&self) -> usize {
+        self.event_date
+    }
+
+    /// Get the cash received.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn cash(&self) -> Decimal {
+        self.cash
+    }
+
+    /// Get the contractual interest.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn contractual_interest(&self) -> Decimal {
+        self.contractual_interest
+    }
+
+    /// Get the interest income.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn interest_income(&self) -> Decimal {
+        self.interest_income
+    }
+
+    /// Get the fee amortization.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn fee_amortization(&self) -> Decimal {
+        self.fee_amortization
+    }
+
+    /// Get the carrying amount.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn carrying_amount(&self) -> Decimal {
+        self.carrying_amount
+    }
+}
+
+/// Effective interest method utility.
+pub struct EffectiveInterestUtility {}
+
+/// Effective interest method utility implementation.
+impl EffectiveInterestUtility {
+    /// Calculate the present value of cash flows at an annual
+    /// effective rate (actual/365 time basis). The cash flows of
+    /// the first date are reduced by the fees.
+    ///
+    /// # Arguments
+    ///
+    /// * `flows` - The dated cash flows.
+    /// * `fees` - The upfront fees received (negative for costs).
+    /// * `rate` - The annual effective rate (fraction).
+    ///
+    /// # Return
+    ///
+    /// * See description or None if the rate is not valid or
+    ///   the present value overflows.
+
+    pub fn present_value(flows: &[ElemCashFlow], fees: Decimal, rate: Decimal) -> Option<Decimal> {
+        let first = flows.first()?;
+        let log_growth = DecimalUtility::ln(dec!(1.0) + rate)?;
+        let mut result = first.cash() + fees;
+
+        for flow in flows.iter().skip(1) {
+            let years = Decimal::from(DateUtility::days_between(
+                first.event_date(),
+                flow.event_date(),
+            )) / dec!(365.0);
+            let discount = DecimalUtility::exp(years.checked_mul(-log_growth)?)?;
+            result = result.checked_add(flow.cash().checked_mul(discount)?)?;
+        }
+
+        Some(result)
+    }
+
+    /// Solve for the annual effective rate that discounts the cash
+    /// flows (including the fees) to zero.
+    ///
+    /// # Arguments
+    ///
+    /// * `flows` - The dated cash flows.
+    /// * `fees` - The upfront fees received (negative for costs).
+    ///
+    /// # Return
+    ///
+    /// * See description or None if the rate cannot be found.
+
+    pub fn effective_rate(flows: &[ElemCashFlow], fees: Decimal) -> Option<Decimal> {
+        let mut low = EFFECTIVE_RATE_LOW;
+        let mut high = dec!(10.0);
+        let pv_low = loop {
+            match EffectiveInterestUtility::present_value(flows, fees, low) {
+                Some(o) => break o,
+                None if low < dec!(-0.01) => low /= dec!(2.0),
+                None => return None,
+            }
+        };
+        let pv_high = EffectiveInterestUtility::present_value(flows, fees, high)?;
+
+        if pv_low.is_sign_negative() == pv_high.is_sign_negative() {
+            return None;
+        }
+
+        for _ in 0..EFFECTIVE_RATE_ITERATIONS {
+            let mid = (low + high) / dec!(2.0);
+            let pv_mid = EffectiveInterestUtility::present_value(flows, fees, mid)?;
+            if pv_mid.is_zero() || (high - low) < dec!(0.000000000001) {
+                return Some(mid);
+            }
+            if pv_mid.is_sign_negative() == pv_low.is_sign_negative() {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+
+        Some((low + high) / dec!(2.0))
+    }
+
+    /// Create the effective interest schedule. The carrying amount
+    /// starts at the cash disbursed less the fees and accrues interest
+    /// income at the effective rate; the final row absorbs the rounding
+    /// so that a fully repaid carrying amount ends at zero.
+    ///
+    /// # Arguments
+    ///
+    /// * `flows` - The dated cash flows.
+    /// * `fees` - The upfront fees received (negative for costs).
+    /// * `rate` - The annual effective rate (fraction).
+    /// * `decimal_digits` - Number of decimal digits for rounding.
+    ///
+    /// # Return
+    ///
+    /// * See description or None if the rate is not valid.
+
+    pub fn schedule(
+        flows: &[ElemCashFlow],
+        fees: Decimal,
+        rate: Decimal,
+        decimal_digits: u32,
+    ) -> Option<Vec<ElemEffectiveRow>> {
+        let first = flows.first()?;
+        let log_growth = DecimalUtility::ln(dec!(1.0) + rate)?;
+        let mut carrying_amount = -(first.cash() + fees);
+        let mut result: Vec<ElemEffectiveRow> = vec![ElemEffectiveRow {
+            event_date: first.event_date(),
+            cash: first.cash(),
+            contractual_interest: first.interest(),
+            interest_income: dec!(0.0),
+            fee_amortization: dec!(0.0),
+            carrying_amount,
+        }];
+
+        let tolerance = Decimal::new(flows.len() as i64, decimal_digits);
+        let mut prior_date = first.event_date();
+        for (flow_index, flow) in flows.iter().enumerate().skip(1) {
+            let years = Decimal::from(DateUtility::days_between(prior_date, flow.event_date()))
+                / dec!(365.0);
+            let growth = DecimalUtility::exp(years.checked_mul(log_growth)?)? - dec!(1.0);
+            let mut interest_income = carrying_amount
+                .checked_mul(growth)?
+                .round_dp(decimal_digits);
+
+            let residue = carrying_amount + interest_income - flow.cash();
+            if flow_index == flows.len() - 1 && residue.abs() <= tolerance {
+                interest_income -= residue;
+            }
+
+            carrying_amount += interest_income - flow.cash();
+            result.push(ElemEffectiveRow {
+                event_date: flow.event_date(),
+                cash: flow.cash(),
+                contractual_interest: flow.interest(),
+                interest_income,
+                fee_amortization: interest_income - flow.interest(),
+                carrying_amount,
+            });
+
+            prior_date = flow.event_date();
+        }
+
+        Some(result)
+    }
+}
@@ -0,0 +1,386 @@
+//! Dated currency exchange rates.
+// Copyright (c) 2021 ShiftLeft Software
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use rust_decimal::prelude::*;
+
+use amfnengine::*;
+
+use crate::date_utility::*;
+
+/// Cashflow preferences descriptor group for exchange rates.
+pub const GROUP_EXCHANGE: &str = "Exchange";
+/// Cashflow preferences descriptor name for the exchange rate table.
+pub const NAME_EXCHANGE_RATES: &str = "Rates";
+
+/// Number of decimal digits kept for derived exchange rates.
+pub const EXCHANGE_RATE_DECIMAL_DIGITS: u32 = 12;
+
+/// Exchange rate element.
+#[derive(Clone)]
+pub struct ElemExchangeRate {
+    /// Date the rate is effective (YYYYMMDD).
+    event_date: usize,
+    /// Currency code converted from (e.g., USD).
+    from_code: String,
+    /// Currency code converted to (e.g., EUR).
+    to_code: String,
+    /// Units of the to currency for one unit of the from currency.
+    rate: Decimal,
+}
+
+/// Exchange rate implementation.
+impl ElemExchangeRate {
+    /// Create and return an exchange rate.
+    ///
+    /// # Arguments
+    ///
+    /// * `event_date_param` - Date the rate is effective.
+    /// * `from_code_param` - Currency code converted from.
+    /// * `to_code_param` - Currency code converted to.
+    /// * `rate_param` - Units of the to currency for one unit of the from currency.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn new(
+        event_date_param: usize,
+        from_code_param: &str,
+        to_code_param: &str,
+        rate_param: Decimal,
+    ) -> ElemExchangeRate {
+        ElemExchangeRate {
+            event_date: event_date_param,
+            from_code: from_code_param.trim().to_uppercase(),
+            to_code: to_code_param.trim().to_uppercase(),
+            rate: rate_param,
+        }
+    }
+
+    /// Get the date the rate is effective.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn event_date(&self) -> usize {
+        self.event_date
+    }
+
+    /// Get the currency code converted from.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn from_code(&self) -> &str {
+        self.from_code.as_str()
+    }
+
+    /// Get the currency code converted to.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn to_code(&self) -> &str {
+        self.to_code.as_str()
+    }
+
+    /// Get the rate.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn rate(&self) -> Decimal {
+        self.rate
+    }
+}
+
+/// List of dated exchange rates.
+#[derive(Default)]
+pub struct ListExchangeRate {
+    /// Exchange rates sorted by date.
+    list_exchange_rate: Vec<ElemExchangeRate>,
+}
+
+/// List of dated exchange rates implementation.
+impl ListExchangeRate {
+    /// Create and return a new list of exchange rates.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn new() -> ListExchangeRate {
+        ListExchangeRate {
+            list_exchange_rate: Vec::new(),
+        }
+    }
+
+    /// Add an exchange rate. A rate with the same date and
+    /// currency codes is replaced.
+    ///
+    /// # Arguments
+    ///
+    /// * `elem` - The exchange rate.
+    ///
+    /// # Return
+    ///
+    /// * True if successful, otherwise false.
+
+    pub fn add_exchange_rate(&mut self, elem: ElemExchangeRate) -> bool {
+        if elem.rate() <= dec!(0.0) || elem.from_code().is_empty() || elem.to_code().is_empty() {
+            return false;
+        }
+
+        self.list_exchange_rate.retain(|o| {
+            o.event_date() != elem.event_date()
+                || o.from_code() != elem.from_code()
+                || o.to_code() != elem.to_code()
+        });
+
+        let position = self
+            .list_exchange_rate
+            .iter()
+            .position(|o| o.event_date() > elem.event_date())
+            .unwrap_or(self.list_exchange_rate.len());
+        self.list_exchange_rate.insert(position, elem);
+
+        true
+    }
+
+    /// Clear all exchange rates.
+
+    pub fn clear(&mut self) {
+        self.list_exchange_rate.clear();
+    }
+
+    /// Return the number of exchange rates.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn count(&self) -> usize {
+        self.list_exchange_rate.len()
+    }
+
+    /// Return the exchange rates.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn list(&self) -> &[ElemExchangeRate] {
+        &self.list_exchange_rate
+    }
+
+    /// Add the exchange rates of a descriptor value of the form
+    /// "date~from-code~to-code~rate|...". Invalid rates are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The descriptor value.
+
+    pub fn add_rates_value(&mut self, value: &str) {
+        for token in value.split('|') {
+            let fields: Vec<&str> = token.split('~').map(|o| o.trim()).collect();
+            if fields.len() != 4 {
+                continue;
+            }
+
+            let event_date = fields[0].replace('-', "").parse::<usize>().unwrap_or(0);
+            if DateUtility::to_naive(event_date).is_none() {
+                continue;
+            }
+
+            if let Ok(o) = fields[3].parse::<Decimal>() {
+                self.add_exchange_rate(ElemExchangeRate::new(event_date, fields[1], fields[2], o));
+            }
+        }
+    }
+
+    /// Return the descriptor value of the exchange rates.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn rates_value(&self) -> String {
+        self.list_exchange_rate
+            .iter()
+            .map(|o| {
+                format!(
+                    "{}~{}~{}~{}",
+                    DateUtility::format_date(o.event_date()),
+                    o.from_code(),
+                    o.to_code(),
+                    o.rate()
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("|")
+    }
+
+    /// Return the rate converting one currency to another on a date.
+    /// The most recent rate effective on or before the date is used,
+    /// either directly, inverted or crossed through a third currency.
+    ///
+    /// # Arguments
+    ///
+    /// * `from_code` - Currency code converted from.
+    /// * `to_code` - Currency code converted to.
+    /// * `date` - The date (YYYYMMDD) or zero for the latest rate.
+    ///
+    /// # Return
+    ///
+    /// * See description or None if no rate is available.
+
+    pub fn rate(&self, from_code: &str, to_code: &str, date: usize) -> Option<Decimal> {
+        let from = from_code.trim().to_uppercase();
+        let to = to_code.trim().to_uppercase();
+
+        if from == to {
+            return Some(dec!(1.0));
+        }
+
+        if let Some(o) = self.pair_rate(from.as_str(), to.as_str(), date) {
+            return Some(o);
+        }
+
+        let mut codes: Vec<&str> = Vec::new();
+        for elem in self.list_exchange_rate.iter() {
+            for code in [elem.from_code(), elem.to_code()].iter() {
+                if *code != from && *code != to && !codes.contains(code) {
+                    codes.push(code);
+                }
+            }
+        }
+
+        for code in codes.iter() {
+            if let (Some(o1), Some(o2)) = (
+                self.pair_rate(from.as_str(), code, date),
+                self.pair_rate(code, to.as_str(), date),
+            ) {
+                return Some((o1 * o2).round_dp(EXCHANGE_RATE_DECIMAL_DIGITS));
+            }
+        }
+
+        None
+    }
+
+    /// Convert a value from one currency to another on a date.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value to convert.
+    /// * `from_code` - Currency code converted from.
+    /// * `to_code` - Currency code converted to.
+    /// * `date` - The date (YYYYMMDD) or zero for the latest rate.
+    ///
+    /// # Return
+    ///
+    /// * See description or None if no rate is available.
+
+    pub fn convert(
+        &self,
+        value: Decimal,
+        from_code: &str,
+        to_code: &str,
+        date: usize,
+    ) -> Option<Decimal> {
+        Some(value * self.rate(from_code, to_code, date)?)
+    }
+
+    /// Return the direct or inverted rate of a currency pair.
+    ///
+    /// # Arguments
+    ///
+    /// * `from_code` - Currency code converted from.
+    /// * `to_code` - Currency code converted to.
+    /// * `date` - The date (YYYYMMDD) or zero for the latest rate.
+    ///
+    /// # Return
+    ///
+    /// * See description or None if no rate is available.
+
+    fn pair_rate(&self, from_code: &str, to_code: &str, date: usize) -> Option<Decimal> {
+        let elem = self
+            .list_exchange_rate
+            .iter()
+            .filter(|o| date == 0 || o.event_date() <= date)
+            .filter(|o| {
+                (o.from_code() == from_code && o.to_code() == to_code)
+                    || (o.from_code() == to_code && o.to_code() == from_code)
+            })
+            .last()?;
+
+        if elem.from_code() == from_code {
+            Some(elem.rate())
+        } else {
+            Some((dec!(1.0) / elem.rate()).round_dp(EXCHANGE_RATE_DECIMAL_DIGITS))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rates() -> ListExchangeRate {
+        let mut list = ListExchangeRate::new();
+        list.add_rates_value(
+            "2024-01-31~usd~eur~0.9|2024-02-29~USD~EUR~0.8|2024-01-31~EUR~GBP~0.5",
+        );
+        list
+    }
+
+    #[test]
+    fn add_exchange_rate_replaces_and_validates() {
+        let mut list = rates();
+        assert_eq!(list.count(), 3);
+        assert!(list.add_exchange_rate(ElemExchangeRate::new(20240131, "USD", "EUR", dec!(0.95))));
+        assert_eq!(list.count(), 3);
+        assert!(!list.add_exchange_rate(ElemExchangeRate::new(20240131, "USD", "EUR", dec!(0))));
+        assert!(!list.add_exchange_rate(ElemExchangeRate::new(20240131, "", "EUR", dec!(1))));
+        assert_eq!(list.rate("USD", "EUR", 20240131), Some(dec!(0.95)));
+    }
+
+    #[test]
+    fn rates_value_round_trips() {
+        let list = rates();
+        let value = list.rates_value();
+        assert_eq!(
+            value,
+            "2024-01-31~USD~EUR~0.9|2024-01-31~EUR~GBP~0.5|2024-02-29~USD~EUR~0.8"
+        );
+
+        let mut copy = ListExchangeRate::new();
+        copy.add_rates_value(format!("{}|bad~USD|2024-13-01~USD~EUR~1", value).as_str());
+        assert_eq!(copy.rates_value(), value);
+    }
+
+    #[test]
+    fn rate_by_date_inverted_and_crossed() {
+        let list = rates();
+        assert_eq!(list.rate("usd", "usd", 0), Some(dec!(1)));
+        assert_eq!(list.rate("USD", "EUR", 20240215), Some(dec!(0.9)));
+        assert_eq!(list.rate("USD", "EUR", 0), Some(dec!(0.8)));
+        assert_eq!(list.rate("USD", "EUR", 20240101), None);
+        assert_eq!(
+            list.rate("EUR", "USD", 20240215),
+            Some(dec!(1.111111111111))
+        );
+        assert_eq!(list.rate("USD", "GBP", 20240215), Some(dec!(0.45)));
+        assert_eq!(list.rate("USD", "JPY", 0), None);
+        assert_eq!(list.convert(dec!(100), "USD", "EUR", 0), Some(dec!(80.0)));
+    }
+}
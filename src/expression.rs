@@ -0,0 +1,171 @@
+//! Expression parsing utilities.
+// Copyright (c) 2021 ShiftLeft Software
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use rust_decimal::prelude::*;
+
+/// Expression parsing utility.
+pub struct ExpressionUtility {}
+
+/// Expression parsing utility implementation.
+impl ExpressionUtility {
+    /// Expand the calls of a value function (e.g., name("item", date))
+    /// found within an expression into their literal results. The
+    /// first argument names the value and the optional second argument
+    /// is a date (YYYYMMDD, zero if omitted). Function arguments are
+    /// evaluated with the given closure.
+    ///
+    /// # Arguments
+    ///
+    /// * `expr` - The expression to expand.
+    /// * `func` - The function name.
+    /// * `eval` - Evaluates an argument expression and returns its value.
+    /// * `value` - Returns a value from a name and date.
+    ///
+    /// # Return
+    ///
+    /// * The expanded expression.
+
+    pub fn expand_value_function(
+        expr: &str,
+        func: &str,
+        eval: &dyn Fn(&str) -> String,
+        value: &dyn Fn(&str, usize) -> Option<Decimal>,
+    ) -> String {
+        let mut result = String::from("");
+        let mut rest = expr;
+
+        loop {
+            let lower = rest.to_ascii_lowercase();
+            let start = match lower.find(format!("{}(", func).as_str()) {
+                None => {
+                    result.push_str(rest);
+                    break;
+                }
+                Some(o) => o,
+            };
+
+            let args_start = start + func.len() + 1;
+            let args_end = match ExpressionUtility::find_closing_paren(&rest[args_start..]) {
+                None => {
+                    result.push_str(rest);
+                    break;
+                }
+                Some(o) => args_start + o,
+            };
+
+            let preceded = rest[..start]
+                .chars()
+                .last()
+                .map_or(false, |c| c.is_alphanumeric() || c == '_');
+            if preceded {
+                result.push_str(&rest[..args_start]);
+                rest = &rest[args_start..];
+                continue;
+            }
+
+            let args: Vec<String> = ExpressionUtility::split_arguments(&rest[args_start..args_end])
+                .iter()
+                .map(|o| eval(o.as_str()))
+                .collect();
+            let name = args.first().map_or("", |o| ExpressionUtility::unquote(o));
+            let date = args
+                .get(1)
+                .and_then(|o| o.trim().parse::<usize>().ok())
+                .unwrap_or(0);
+
+            result.push_str(&rest[..start]);
+            match value(name, date) {
+                None => result.push_str("0.0"),
+                Some(o) => result.push_str(o.to_string().as_str()),
+            }
+
+            rest = &rest[args_end + 1..];
+        }
+
+        result
+    }
+
+    /// Find the parenthesis closing a function's argument list.
+    ///
+    /// # Arguments
+    ///
+    /// * `args` - The text following the opening parenthesis.
+    ///
+    /// # Return
+    ///
+    /// * The byte offset of the closing parenthesis or None.
+
+    pub fn find_closing_paren(args: &str) -> Option<usize> {
+        let mut depth: usize = 0;
+        let mut in_string = false;
+
+        for (index, c) in args.char_indices() {
+            match c {
+                '"' => in_string = !in_string,
+                '(' if !in_string => depth += 1,
+                ')' if !in_string => {
+                    if depth == 0 {
+                        return Some(index);
+                    }
+                    depth -= 1;
+                }
+                _ => {}
+            }
+        }
+
+        None
+    }
+
+    /// Split a function's argument list at the top level commas.
+    ///
+    /// # Arguments
+    ///
+    /// * `args` - The argument list (without parenthesis).
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn split_arguments(args: &str) -> Vec<String> {
+        let mut result: Vec<String> = Vec::new();
+        let mut depth: usize = 0;
+        let mut in_string = false;
+        let mut start: usize = 0;
+
+        for (index, c) in args.char_indices() {
+            match c {
+                '"' => in_string = !in_string,
+                '(' if !in_string => depth += 1,
+                ')' if !in_string => depth = depth.saturating_sub(1),
+                ',' if !in_string && depth == 0 => {
+                    result.push(String::from(args[start..index].trim()));
+                    start = index + 1;
+                }
+                _ => {}
+            }
+        }
+        result.push(String::from(args[start..].trim()));
+
+        result
+    }
+
+    /// Remove surrounding double quotes from a string value.
+    ///
+    /// # Arguments
+    ///
+    /// * `val` - The string value.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn unquote(val: &str) -> &str {
+        val.trim().trim_matches('"')
+    }
+}
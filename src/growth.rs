@@ -0,0 +1,243 @@
+//! Graduated and growing payment streams.
+// Copyright (c) 2021 ShiftLeft Software
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use rust_decimal::prelude::*;
+
+use amfnengine::*;
+
+/// Event descriptor group for a growing payment stream.
+pub const GROUP_GROWTH: &str = "Growth";
+/// Event descriptor name for the growth terms.
+pub const NAME_GROWTH_TERMS: &str = "Terms";
+
+/// Maximum number of trials when solving for the initial payment.
+pub const MAX_GROWTH_TRIALS: usize = 10;
+
+/// Growth type.
+#[derive(Clone, Copy, PartialEq)]
+pub enum GrowthType {
+    /// Each step grows the payment by a percentage.
+    Percent,
+    /// Each step grows the payment by an amount.
+    Amount,
+}
+
+/// Growth terms of a principal change event.
+#[derive(Clone, Copy)]
+pub struct ElemGrowth {
+    /// Growth type.
+    growth_type: GrowthType,
+    /// Growth percentage or amount of each step.
+    rate: Decimal,
+    /// Number of periods per step.
+    step_interval: usize,
+}
+
+/// Growth terms implementation.
+impl ElemGrowth {
+    /// Parse growth terms from their descriptor value
+    /// (percent|amount~rate~step-interval, e.g., "percent~7.5~12"
+    /// or "amount~50~12"; the step interval defaults to one period).
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The descriptor value.
+    ///
+    /// # Return
+    ///
+    /// * See description or None if the value is invalid.
+
+    pub fn parse(value: &str) -> Option<ElemGrowth> {
+        let tokens: Vec<&str> = value.split('~').map(|o| o.trim()).collect();
+
+        let growth_type = match tokens.first()?.to_lowercase().as_str() {
+            "percent" => GrowthType::Percent,
+            "amount" => GrowthType::Amount,
+            _ => return None,
+        };
+
+        let rate = tokens.get(1)?.parse::<Decimal>().ok()?;
+
+        let step_interval = match tokens.get(2) {
+            None => 1,
+            Some(o) if o.is_empty() => 1,
+            Some(o) => o.parse::<usize>().ok()?,
+        };
+
+        if step_interval == 0 || (growth_type == GrowthType::Percent && rate <= dec!(-100.0)) {
+            return None;
+        }
+
+        Some(ElemGrowth {
+            growth_type,
+            rate,
+            step_interval,
+        })
+    }
+
+    /// Return the descriptor value of the growth terms.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn descriptor_value(&self) -> String {
+        format!(
+            "{}~{}~{}",
+            match self.growth_type {
+                GrowthType::Percent => "percent",
+                GrowthType::Amount => "amount",
+            },
+            self.rate.normalize(),
+            self.step_interval
+        )
+    }
+
+    /// Get the growth type.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn growth_type(&self) -> GrowthType {
+        self.growth_type
+    }
+
+    /// Get the growth percentage or amount of each step.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn rate(&self) -> Decimal {
+        self.rate
+    }
+
+    /// Get the number of periods per step.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn step_interval(&self) -> usize {
+        self.step_interval
+    }
+
+    /// Return the event value expression that expands the event
+    /// into the growing payment stream. The event value is the
+    /// initial payment (the same expressions are used by the
+    /// graduated and step payment templates).
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn expression(&self) -> String {
+        match self.growth_type {
+            GrowthType::Percent => format!(
+                "set(decNewValue, default(decNewValue, decValue)), \
+                set(decNewValue, round(decNewValue + if(intSequence > 0 and \
+                intSequence mod {} = 0, decNewValue * ({} / 100), 0.0)))",
+                self.step_interval,
+                self.rate.normalize()
+            ),
+            GrowthType::Amount => format!(
+                "decValue + ((intSequence / {}) * {})",
+                self.step_interval,
+                self.rate.normalize()
+            ),
+        }
+    }
+
+    /// Estimate the initial payment that leaves a zero final balance
+    /// from two trial payments. The final balance of a growing stream
+    /// is (apart from rounding) linear in the initial payment.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The first trial payment.
+    /// * `balance` - The final balance with the first trial payment.
+    /// * `trial_value` - The second trial payment.
+    /// * `trial_balance` - The final balance with the second trial payment.
+    ///
+    /// # Return
+    ///
+    /// * See description or None if the payment has no effect
+    ///   on the final balance.
+
+    pub fn initial_payment(
+        value: Decimal,
+        balance: Decimal,
+        trial_value: Decimal,
+        trial_balance: Decimal,
+    ) -> Option<Decimal> {
+        let effect = trial_balance - balance;
+        if effect.is_zero() {
+            return None;
+        }
+
+        Some(value - balance * (trial_value - value) / effect)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_descriptor_value() {
+        let growth = ElemGrowth::parse("Percent ~ 7.50 ~ 12").unwrap();
+        assert!(growth.growth_type() == GrowthType::Percent);
+        assert_eq!(growth.rate(), dec!(7.5));
+        assert_eq!(growth.step_interval(), 12);
+        assert_eq!(growth.descriptor_value(), "percent~7.5~12");
+
+        let growth = ElemGrowth::parse("amount~50").unwrap();
+        assert!(growth.growth_type() == GrowthType::Amount);
+        assert_eq!(growth.step_interval(), 1);
+        assert_eq!(growth.descriptor_value(), "amount~50~1");
+    }
+
+    #[test]
+    fn parse_rejects_invalid_terms() {
+        for value in [
+            "",
+            "flat~1~12",
+            "percent",
+            "percent~abc",
+            "percent~-100",
+            "amount~50~0",
+        ]
+        .iter()
+        {
+            assert!(ElemGrowth::parse(value).is_none(), "{}", value);
+        }
+        assert!(ElemGrowth::parse("amount~-50").is_some());
+    }
+
+    #[test]
+    fn amount_expression() {
+        assert_eq!(
+            ElemGrowth::parse("amount~50~12").unwrap().expression(),
+            "decValue + ((intSequence / 12) * 50)"
+        );
+    }
+
+    #[test]
+    fn initial_payment_interpolates_zero_balance() {
+        assert_eq!(
+            ElemGrowth::initial_payment(dec!(100), dec!(-500), dec!(110), dec!(-300)),
+            Some(dec!(125))
+        );
+        assert_eq!(
+            ElemGrowth::initial_payment(dec!(100), dec!(-500), dec!(110), dec!(-500)),
+            None
+        );
+    }
+}
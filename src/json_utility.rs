@@ -0,0 +1,60 @@
+//! Json output utilities.
+// Copyright (c) 2021 ShiftLeft Software
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/// Json output utility.
+pub struct JsonUtility {}
+
+/// Json output utility implementation.
+impl JsonUtility {
+    /// Escape a string for inclusion within a json string value.
+    /// Quotes, backslashes and control characters are escaped.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The string to escape.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn escape(value: &str) -> String {
+        let mut result = String::with_capacity(value.len());
+
+        for c in value.chars() {
+            match c {
+                '"' => result.push_str("\\\""),
+                '\\' => result.push_str("\\\\"),
+                '\n' => result.push_str("\\n"),
+                '\r' => result.push_str("\\r"),
+                '\t' => result.push_str("\\t"),
+                c if (c as u32) < 0x20 => result.push_str(format!("\\u{:04x}", c as u32).as_str()),
+                c => result.push(c),
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_quotes_and_backslashes() {
+        assert_eq!(JsonUtility::escape("a \"b\" \\ c"), "a \\\"b\\\" \\\\ c");
+    }
+
+    #[test]
+    fn escape_control_characters() {
+        assert_eq!(JsonUtility::escape("a\nb\r\tc"), "a\\nb\\r\\tc");
+        assert_eq!(JsonUtility::escape("\u{0}\u{1f}"), "\\u0000\\u001f");
+        assert_eq!(JsonUtility::escape("\u{20}\u{7f}"), "\u{20}\u{7f}");
+    }
+}
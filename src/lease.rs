@@ -0,0 +1,411 @@
+//! Lease accounting (ASC 842 / IFRS 16) schedules.
+// Copyright (c) 2021 ShiftLeft Software
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use rust_decimal::prelude::*;
+
+use amfnengine::*;
+
+use crate::am_row::*;
+use crate::date_utility::*;
+use crate::decimal_utility::*;
+
+/// Lease classification.
+#[derive(Clone, Copy, PartialEq)]
+pub enum LeaseType {
+    /// Finance lease (interest plus straight-line asset amortization).
+    Finance,
+    /// Operating lease (single straight-line lease cost).
+    Operating,
+}
+
+/// Lease payment of a single date.
+#[derive(Clone, Copy)]
+pub struct ElemLeasePayment {
+    /// Date of the payment (YYYYMMDD).
+    event_date: usize,
+    /// Amount of the payment.
+    amount: Decimal,
+}
+
+/// Lease payment implementation.
+impl ElemLeasePayment {
+    /// Group the principal changes of amortization rows into dated
+    /// lease payments. Payments may be modeled as either principal
+    /// increases or decreases; the direction of the payments is the
+    /// direction of the final principal change and principal changes
+    /// in the other direction (e.g., the commencement disbursement)
+    /// are not lease payments.
+    ///
+    /// # Arguments
+    ///
+    /// * `rows` - The expanded amortization rows.
+    ///
+    /// # Return
+    ///
+    /// * One payment for each date with a principal change
+    ///   in the direction of the payments.
+
+    pub fn from_rows(rows: &[ElemAmRow]) -> Vec<ElemLeasePayment> {
+        let mut result: Vec<ElemLeasePayment> = Vec::new();
+
+        let direction = match rows
+            .iter()
+            .map(|o| o.principal_decrease() - o.principal_increase())
+            .filter(|o| !o.is_zero())
+            .last()
+        {
+            None => return result,
+            Some(o) if o.is_sign_negative() => dec!(-1.0),
+            Some(_o) => dec!(1.0),
+        };
+
+        for row in rows.iter() {
+            let amount = (row.principal_decrease() - row.principal_increase()) * direction;
+            if amount <= dec!(0.0) {
+                continue;
+            }
+            match result.last_mut() {
+                Some(o) if o.event_date == row.event_date() => {
+                    o.amount += amount;
+                }
+                _ => result.push(ElemLeasePayment {
+                    event_date: row.event_date(),
+                    amount,
+                }),
+            }
+        }
+
+        result
+    }
+
+    /// Get the date of the payment.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn event_date(&self) -> usize {
+        self.event_date
+    }
+
+    /// Get the amount of the payment.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn amount(&self) -> Decimal {
+        self.amount
+    }
+}
+
+/// Lease schedule row.
+pub struct ElemLeaseRow {
+    /// Date of the row (YYYYMMDD).
+    event_date: usize,
+    /// Lease payment.
+    payment: Decimal,
+    /// Interest on the lease liability.
+    interest: Decimal,
+    /// Reduction of the lease liability (payment less interest).
+    liability_reduction: Decimal,
+    /// Lease liability after the row.
+    liability: Decimal,
+    /// Reduction of the right-of-use asset.
+    amortization: Decimal,
+    /// Right-of-use asset after the row.
+    asset: Decimal,
+    /// Lease expense recognized for the period.
+    expense: Decimal,
+}
+
+/// Lease schedule row implementation.
+impl ElemLeaseRow {
+    /// Get the date of the row.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn event_date(&self) -> usize {
+        self.event_date
+    }
+
+    /// Get the lease payment.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn payment(&self) -> Decimal {
+        self.payment
+    }
+
+    /// Get the interest on the lease liability.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn interest(&self) -> Decimal {
+        self.interest
+    }
+
+    /// Get the reduction of the lease liability.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn liability_reduction(&self) -> Decimal {
+        self.liability_reduction
+    }
+
+    /// Get the lease liability.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn liability(&self) -> Decimal {
+        self.liability
+    }
+
+    /// Get the reduction of the right-of-use asset.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn amortization(&self) -> Decimal {
+        self.amortization
+    }
+
+    /// Get the right-of-use asset.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn asset(&self) -> Decimal {
+        self.asset
+    }
+
+    /// Get the lease expense.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn expense(&self) -> Decimal {
+        self.expense
+    }
+}
+
+/// Lease accounting utility.
+pub struct LeaseUtility {}
+
+/// Lease accounting utility implementation.
+impl LeaseUtility {
+    /// Parse a lease type mnemonic.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The mnemonic (finance or operating).
+    ///
+    /// # Return
+    ///
+    /// * See description or None if not recognized.
+
+    pub fn parse_type(value: &str) -> Option<LeaseType> {
+        match value.trim().to_lowercase().as_str() {
+            "finance" => Some(LeaseType::Finance),
+            "operating" => Some(LeaseType::Operating),
+            _ => None,
+        }
+    }
+
+    /// Return the mnemonic of a lease type.
+    ///
+    /// # Arguments
+    ///
+    /// * `lease_type` - The lease type.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn type_mnemonic(lease_type: LeaseType) -> &'static str {
+        match lease_type {
+            LeaseType::Finance => "finance",
+            LeaseType::Operating => "operating",
+        }
+    }
+
+    /// Return the discount period number of each payment. Payments
+    /// are assumed to be regular, so the first payment's period is
+    /// measured from the commencement date and each subsequent
+    /// payment adds one period.
+    ///
+    /// # Arguments
+    ///
+    /// * `payments` - The lease payments.
+    /// * `commencement` - The commencement date (YYYYMMDD).
+    /// * `periods_in_year` - The number of payment periods per year.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn period_numbers(
+        payments: &[ElemLeasePayment],
+        commencement: usize,
+        periods_in_year: u32,
+    ) -> Vec<i64> {
+        let first = match payments.first() {
+            None => return Vec::new(),
+            Some(o) => o,
+        };
+
+        let offset = (Decimal::from(DateUtility::days_between(commencement, first.event_date()))
+            * Decimal::from(periods_in_year)
+            / dec!(365.0))
+        .round()
+        .to_i64()
+        .unwrap_or(0)
+        .max(0);
+
+        (0..payments.len() as i64).map(|o| offset + o).collect()
+    }
+
+    /// Calculate the initial lease liability (the present value of
+    /// the lease payments at the periodic discount rate).
+    ///
+    /// # Arguments
+    ///
+    /// * `payments` - The lease payments.
+    /// * `period_numbers` - The discount period number of each payment.
+    /// * `periodic_rate` - The discount rate per period (fraction).
+    ///
+    /// # Return
+    ///
+    /// * See description or None if the discounting overflows.
+
+    pub fn liability(
+        payments: &[ElemLeasePayment],
+        period_numbers: &[i64],
+        periodic_rate: Decimal,
+    ) -> Option<Decimal> {
+        let mut result = dec!(0.0);
+        for (payment, period) in payments.iter().zip(period_numbers.iter()) {
+            let growth = DecimalUtility::powi(dec!(1.0) + periodic_rate, *period)?;
+            result = result.checked_add(payment.amount().checked_div(growth)?)?;
+        }
+
+        Some(result)
+    }
+
+    /// Create the lease schedule. The liability accrues interest at
+    /// the periodic rate and is reduced by each payment. A finance
+    /// lease amortizes the right-of-use asset straight-line and
+    /// expenses the interest separately; an operating lease expenses
+    /// the total lease cost straight-line and reduces the asset by
+    /// the expense less the interest. The final row absorbs rounding.
+    ///
+    /// # Arguments
+    ///
+    /// * `payments` - The lease payments.
+    /// * `commencement` - The commencement date (YYYYMMDD).
+    /// * `lease_type` - The lease type.
+    /// * `rate` - The annual discount rate (percentage) compounded
+    ///   at the payment frequency.
+    /// * `adjustments` - Initial direct costs and prepaid lease
+    ///   payments less lease incentives received.
+    /// * `decimal_digits` - Number of decimal digits for rounding.
+    ///
+    /// # Return
+    ///
+    /// * The initial liability, the initial right-of-use asset
+    ///   and the schedule rows or None if there are no payments
+    ///   or the discounting overflows.
+
+    pub fn schedule(
+        payments: &[ElemLeasePayment],
+        commencement: usize,
+        lease_type: LeaseType,
+        rate: Decimal,
+        adjustments: Decimal,
+        decimal_digits: u32,
+    ) -> Option<(Decimal, Decimal, Vec<ElemLeaseRow>)> {
+        let first = payments.first()?;
+        let periods_in_year = match payments.get(1) {
+            None => 1,
+            Some(o) => DateUtility::periods_in_year(first.event_date(), o.event_date())?,
+        };
+        let periodic_rate = rate / dec!(100.0) / Decimal::from(periods_in_year);
+        let period_numbers = LeaseUtility::period_numbers(payments, commencement, periods_in_year);
+
+        let initial_liability = LeaseUtility::liability(payments, &period_numbers, periodic_rate)?
+            .round_dp(decimal_digits);
+        let initial_asset = initial_liability + adjustments;
+        let count = Decimal::from(payments.len());
+        let total_payments: Decimal = payments.iter().map(|o| o.amount()).sum();
+        let straight_line = ((total_payments + adjustments) / count).round_dp(decimal_digits);
+        let asset_amortization = (initial_asset / count).round_dp(decimal_digits);
+
+        let mut liability = initial_liability;
+        let mut asset = initial_asset;
+        let mut prior_period: i64 = 0;
+        let mut result: Vec<ElemLeaseRow> = Vec::new();
+
+        for (payment_index, payment) in payments.iter().enumerate() {
+            let last = payment_index == payments.len() - 1;
+            let elapsed = period_numbers[payment_index] - prior_period;
+            let mut interest = (liability
+                * (DecimalUtility::powi(dec!(1.0) + periodic_rate, elapsed)? - dec!(1.0)))
+            .round_dp(decimal_digits);
+            if last {
+                interest = payment.amount() - liability;
+            }
+            let liability_reduction = payment.amount() - interest;
+            liability -= liability_reduction;
+
+            let (amortization, expense) = match lease_type {
+                LeaseType::Finance => {
+                    let amortization = if last { asset } else { asset_amortization };
+                    (amortization, interest + amortization)
+                }
+                LeaseType::Operating => {
+                    let amortization = if last {
+                        asset
+                    } else {
+                        straight_line - interest
+                    };
+                    (amortization, interest + amortization)
+                }
+            };
+            asset -= amortization;
+
+            result.push(ElemLeaseRow {
+                event_date: payment.event_date(),
+                payment: payment.amount(),
+                interest,
+                liability_reduction,
+                liability,
+                amortization,
+                asset,
+                expense,
+            });
+
+            prior_period = period_numbers[payment_index];
+        }
+
+        Some((initial_liability, initial_asset, result))
+    }
+}
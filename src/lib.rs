@@ -7,14 +7,77 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use js_sys::Array;
+use chrono::Datelike;
+use js_sys::{Array, Object, Reflect, JSON};
 use rust_decimal::prelude::*;
+use std::cell::RefCell;
+use std::ops::Range;
 use wasm_bindgen::prelude::*;
 
 use amfnengine::core::*;
 use amfnengine::engine::*;
 use amfnengine::*;
 
+pub mod allocation;
+pub mod am_row;
+pub mod clipboard;
+pub mod comparison;
+pub mod condition;
+pub mod construction;
+pub mod credit_card;
+pub mod currency;
+pub mod daily_interest;
+pub mod date_utility;
+pub mod decimal_utility;
+pub mod delinquency;
+pub mod depreciation;
+pub mod effective_interest;
+pub mod exchange;
+pub mod expression;
+pub mod growth;
+pub mod json_utility;
+pub mod lease;
+pub mod portfolio;
+pub mod prepayment;
+pub mod rate_utility;
+pub mod reconciliation;
+pub mod recurrence;
+pub mod relative_date;
+pub mod residue;
+pub mod revolving;
+pub mod sensitivity;
+pub mod skip_periods;
+pub mod strategy;
+
+use allocation::*;
+use am_row::*;
+use clipboard::*;
+use comparison::*;
+use condition::*;
+use construction::*;
+use credit_card::*;
+use currency::*;
+use daily_interest::*;
+use date_utility::*;
+use delinquency::*;
+use depreciation::*;
+use effective_interest::*;
+use exchange::*;
+use growth::*;
+use json_utility::*;
+use lease::*;
+use portfolio::*;
+use prepayment::*;
+use rate_utility::*;
+use reconciliation::*;
+use recurrence::*;
+use relative_date::*;
+use residue::*;
+use revolving::*;
+use sensitivity::*;
+use skip_periods::*;
+use strategy::*;
+
 /// Version message.
 pub const APP_VERSION: Option<&'static str> = option_env!("CARGO_PKG_VERSION");
 
@@ -23,6 +86,44 @@ pub const TABLE_EVENT: u32 = 0;
 /// Am table.
 pub const TABLE_AM: u32 = 1;
 
+/// Column formats (shared with the site as wasm_bindgen.ColumnFormat).
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColumnFormat {
+    /// String column format.
+    String = 0,
+    /// Date column format.
+    Date = 1,
+    /// Integer column format.
+    Integer = 2,
+    /// Decimal column format.
+    Decimal = 3,
+    /// Currency column format.
+    Currency = 4,
+}
+
+/// String column format.
+const FORMAT_STRING: usize = ColumnFormat::String as usize;
+/// Integer column format.
+const FORMAT_INTEGER: usize = ColumnFormat::Integer as usize;
+/// Decimal column format.
+const FORMAT_DECIMAL: usize = ColumnFormat::Decimal as usize;
+/// Currency column format.
+const FORMAT_CURRENCY: usize = ColumnFormat::Currency as usize;
+
+/// Serialize cashflow preferences.
+const JSON_SERIALIZE_CASHFLOW_PREFERENCES: usize = 8;
+/// Serialize selected cashflow.
+const JSON_SERIALIZE_CASHFLOW_SELECTED: usize = 16;
+/// Serialize cashflows with event list.
+const JSON_SERIALIZE_EVENT_LIST: usize = 32;
+
+/// Serialization options for a complete cashflow
+/// (cashflow preferences, selected cashflow and event list).
+const SERIALIZE_CASHFLOW: usize = JSON_SERIALIZE_CASHFLOW_PREFERENCES
+    | JSON_SERIALIZE_CASHFLOW_SELECTED
+    | JSON_SERIALIZE_EVENT_LIST;
+
 /// Wasm parameter element.
 #[wasm_bindgen]
 pub struct WasmParameter {
@@ -877,6 +978,9 @@ pub struct Engine {
 
     /// AmFn engine initialized.
     initialized: bool,
+
+    /// Dated exchange rates.
+    exchange_rates: RefCell<ListExchangeRate>,
 }
 
 /// Wasm amfn engine default implementation.
@@ -908,6 +1012,7 @@ impl Engine {
         Engine {
             engine: eng,
             initialized: false,
+            exchange_rates: RefCell::new(ListExchangeRate::new()),
         }
     }
 
@@ -974,12 +1079,272 @@ impl Engine {
         }
     }
 
+    /// Record an actual payment received against the selected
+    /// cashflow. The ledger of actual payments is kept as a descriptor
+    /// within the cashflow preferences and is serialized with them.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    /// * `payment_date` - The date the payment was received.
+    /// * `amount` - The amount of the payment.
+    /// * `memo` - The memo of the payment (may be empty).
+    ///
+    /// # Return
+    ///
+    /// * True if successful, otherwise false.
+
+    pub fn add_actual_payment(
+        &self,
+        cf_index: i32,
+        payment_date: &str,
+        amount: &str,
+        memo: &str,
+    ) -> bool {
+        if cf_index < 0 {
+            return false;
+        }
+
+        {
+            let calc_mgr = self.engine.calc_mgr();
+            if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
+                return false;
+            }
+        }
+
+        let event_date = CoreUtility::parse_date(self.engine.format_date_in(payment_date).as_str());
+        if event_date == 0 {
+            return false;
+        }
+
+        let amount = match self.display_number(amount) {
+            None => return false,
+            Some(o) => o,
+        };
+
+        let mut payments = self.cashflow_actual_payments();
+        payments.push(ElemActualPayment::new(event_date, amount, memo));
+        payments.sort_by_key(|o| o.event_date());
+
+        self.set_cashflow_descriptor(
+            GROUP_ACTUAL,
+            NAME_ACTUAL_PAYMENTS,
+            DailyInterestUtility::payments_value(&payments).as_str(),
+        )
+    }
+
+    /// Apply the selected cashflow's rounding residue policy to a
+    /// regular payment event. The residue is the final balance left
+    /// by the rounded payments.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    /// * `index` - The event index of the regular payment.
+    ///
+    /// # Return
+    ///
+    /// * A json string with the "policy", "residue-before",
+    ///   "adjustment" and "residue-after" or an empty string
+    ///   if not successful.
+
+    pub fn apply_residue_policy(&self, cf_index: i32, index: u32) -> String {
+        if cf_index < 0 {
+            return String::from("");
+        }
+
+        let event_date: usize;
+        let sort_order: usize;
+        let payment: Decimal;
+        let periods: usize;
+        let intervals: usize;
+        let frequency: FrequencyType;
+        let extension: ElemExtension;
+
+        {
+            let calc_mgr = self.engine.calc_mgr();
+
+            if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
+                return String::from("");
+            }
+
+            match calc_mgr.list_cashflow().list_event() {
+                None => return String::from(""),
+                Some(o) => {
+                    if !o.get_element(index as usize) {
+                        return String::from("");
+                    }
+                    event_date = o.event_date();
+                    sort_order = o.sort_order();
+                    payment = o.value();
+                    periods = o.periods();
+                    intervals = o.intervals();
+                    frequency = o.frequency();
+                    extension = o.elem_extension().clone();
+                }
+            }
+        }
+
+        let policy = self.cashflow_residue_policy();
+
+        let residue = match self.final_balance() {
+            None => return String::from(""),
+            Some(o) => o,
+        };
+
+        let mut adjustment = dec!(0.0);
+
+        if !residue.is_zero() && policy != ResiduePolicy::Carry {
+            let index = index as usize;
+            let result = match policy {
+                ResiduePolicy::AdjustFirst => {
+                    let trial = residue;
+                    let mut result = self.adjust_first_payment(
+                        index, event_date, sort_order, payment, periods, intervals, frequency,
+                        extension, trial,
+                    );
+                    if result {
+                        result = match self.final_balance() {
+                            None => false,
+                            Some(o) => {
+                                adjustment = self.round_currency(ResidueUtility::first_adjustment(
+                                    residue, trial, o,
+                                ));
+                                self.set_event_value_at(
+                                    event_date,
+                                    sort_order,
+                                    "Value",
+                                    payment + adjustment,
+                                )
+                            }
+                        };
+                    }
+                    result
+                }
+                ResiduePolicy::Spread => {
+                    let amount = ResidueUtility::spread_amount(
+                        residue,
+                        periods,
+                        self.cashflow_decimal_digits(),
+                        self.cashflow_rounding_mode(),
+                    );
+                    adjustment = amount * Decimal::from(periods);
+                    let mut result =
+                        self.set_event_column_decimal(index, "Value", payment + amount);
+                    if result {
+                        result = self.balance_events();
+                    }
+                    if result {
+                        result = match self.final_balance() {
+                            None => false,
+                            Some(o) => {
+                                adjustment += o;
+                                o.is_zero()
+                                    || self.adjust_last_payment(
+                                        index,
+                                        event_date,
+                                        sort_order,
+                                        payment + amount,
+                                        periods,
+                                        intervals,
+                                        frequency,
+                                        extension,
+                                        o,
+                                    )
+                            }
+                        };
+                    }
+                    result
+                }
+                _ => {
+                    adjustment = residue;
+                    self.adjust_last_payment(
+                        index, event_date, sort_order, payment, periods, intervals, frequency,
+                        extension, residue,
+                    )
+                }
+            };
+
+            if !result {
+                return String::from("");
+            }
+
+            self.engine.evaluate_cashflow_event_type_all();
+
+            if !self.balance_events() {
+                return String::from("");
+            }
+        }
+
+        let residue_after = self.final_balance().unwrap_or(residue);
+
+        format!(
+            "{{\"policy\":\"{}\",\"residue-before\":\"{}\",\"adjustment\":\"{}\",\"residue-after\":\"{}\"}}",
+            ResidueUtility::policy_mnemonic(policy),
+            residue,
+            adjustment,
+            residue_after
+        )
+    }
+
+    /// Build the skip periods mask of an event from a rule, so that
+    /// payments are made only in (or skipped in) a set of months or
+    /// weekdays (e.g., harvest months or a school year). The mask is
+    /// built from the dates of the event periods, so it follows the
+    /// event date, periods, intervals and frequency.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    /// * `index` - The event index.
+    /// * `rule` - The rule (pay|skip~months|weekdays~list, where the
+    ///   list contains numbers, names or ranges, e.g., "pay~months~9-11",
+    ///   "skip~months~jun,jul,aug" or "skip~weekdays~sat-sun").
+    ///
+    /// # Return
+    ///
+    /// * A json string with the "skip-periods" mask (set it with
+    ///   set_event_value) and a readable "error" (empty if successful)
+    ///   or an empty string if the event is not found.
+
+    pub fn build_skip_periods(&self, cf_index: i32, index: u32, rule: &str) -> String {
+        if cf_index < 0 {
+            return String::from("");
+        }
+
+        {
+            let calc_mgr = self.engine.calc_mgr();
+            if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
+                return String::from("");
+            }
+        }
+
+        let dates = match self.event_period_dates(index as usize) {
+            None => return String::from(""),
+            Some(o) => o,
+        };
+
+        let (mask, error) =
+            match ElemSkipRule::parse(rule).and_then(|o| SkipPeriodsUtility::build(&dates, &o)) {
+                Err(e) => (String::from(""), self.skip_periods_error(&e)),
+                Ok(o) => (o, String::from("")),
+            };
+
+        format!(
+            "{{\"skip-periods\":\"{}\",\"error\":\"{}\"}}",
+            mask,
+            JsonUtility::escape(error.as_str())
+        )
+    }
+
     /// Calculates the value for an event.
     /// Calculates either an interest amount or a principal amount
     /// (depending upon the selected event type) that will satisfy
     /// the condition that the remaining balance of the cashflow
     /// is the smallest amount greater than or equal to the given
-    /// parameter value.
+    /// parameter value. For an event with growth terms (see
+    /// set_growth), calculates the initial payment of the growing
+    /// payment stream that leaves the same remaining balance.
     ///
     /// # Arguments
     ///
@@ -1010,6 +1375,18 @@ impl Engine {
             }
         }
 
+        if ElemGrowth::parse(
+            self.event_descriptor(index as usize, GROUP_GROWTH, NAME_GROWTH_TERMS)
+                .as_str(),
+        )
+        .is_some()
+        {
+            return match self.calculate_growth_value(index as usize) {
+                None => String::from(""),
+                Some(o) => o.to_string(),
+            };
+        }
+
         match self.engine.calculate_value() {
             Err(_e) => String::from(""),
             Ok(o) => o.result_decimal().to_string(),
@@ -1057,6 +1434,119 @@ impl Engine {
         }
     }
 
+    /// Convert a currency value using the exchange rate table.
+    ///
+    /// # Arguments
+    ///
+    /// * `val` - The value to convert.
+    /// * `from_code` - Currency code converted from (e.g., USD).
+    /// * `to_code` - Currency code converted to (e.g., EUR).
+    /// * `date_param` - The date of the exchange rate (empty for the latest rate).
+    ///
+    /// # Return
+    ///
+    /// * The converted value or an empty string if a rate is not available.
+
+    pub fn convert_currency(
+        &self,
+        val: &str,
+        from_code: &str,
+        to_code: &str,
+        date_param: &str,
+    ) -> String {
+        let value = match val.parse::<Decimal>() {
+            Err(_e) => return String::from(""),
+            Ok(o) => o,
+        };
+
+        let date = if date_param.is_empty() {
+            0
+        } else {
+            CoreUtility::parse_date(self.engine.format_date_in(date_param).as_str())
+        };
+
+        match self
+            .exchange_rates
+            .borrow()
+            .convert(value, from_code, to_code, date)
+        {
+            None => String::from(""),
+            Some(o) => o.to_string(),
+        }
+    }
+
+    /// Converts a nominal rate from one compounding frequency to another.
+    /// Use a frequency of "1-year" for effective annual rates and
+    /// "continuous" for continuously compounded rates.
+    ///
+    /// # Arguments
+    ///
+    /// * `rate` - The rate (as a percentage) to convert.
+    /// * `from_frequency` - The compounding frequency of the given rate.
+    /// * `to_frequency` - The compounding frequency of the result.
+    ///
+    /// # Return
+    ///
+    /// * The converted rate or an empty string if undefined.
+
+    pub fn convert_rate(&self, rate: &str, from_frequency: &str, to_frequency: &str) -> String {
+        match rate.parse::<Decimal>() {
+            Err(_e) => String::from(""),
+            Ok(o) => match RateUtility::convert_rate(o, from_frequency, to_frequency) {
+                None => String::from(""),
+                Some(o2) => o2.to_string(),
+            },
+        }
+    }
+
+    /// Copy a range of events of the selected cashflow to a clipboard
+    /// string (see paste_events). The clipboard is a json string with
+    /// the "format", the "first" and "last" event indexes and the
+    /// serialized "cashflow" so that it may be saved or pasted into
+    /// another cashflow or session.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    /// * `first` - The index of the first event to copy.
+    /// * `last` - The index of the last event to copy.
+    ///
+    /// # Return
+    ///
+    /// * See description or an empty string if not successful.
+
+    pub fn copy_events(&self, cf_index: i32, first: u32, last: u32) -> String {
+        if cf_index < 0 || first > last {
+            return String::from("");
+        }
+
+        {
+            let calc_mgr = self.engine.calc_mgr();
+            if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
+                return String::from("");
+            }
+
+            match calc_mgr.list_cashflow().list_event() {
+                None => return String::from(""),
+                Some(o) => {
+                    if last as usize >= o.count() {
+                        return String::from("");
+                    }
+                }
+            }
+        }
+
+        let text = {
+            let json = CalcJsonSerialize::new(self.engine.calc_manager());
+            json.serialize(SERIALIZE_CASHFLOW)
+        };
+
+        format!(
+            "{{\"format\":\"{}\",\"first\":{},\"last\":{},\"cashflow\":{}}}",
+            CLIPBOARD_FORMAT, first, last, text
+        )
+    }
+
     /// Creates the events from the indicated template event list into
     /// the currently selected cashflow event list.
     ///
@@ -1076,51 +1566,58 @@ impl Engine {
         event_param: &str,
         cf_index: i32,
     ) -> String {
-        match self
-            .engine
-            .create_template_events(group_param, event_param, cf_index as usize)
-        {
-            Err(_e) => String::from(""),
-            Ok(o) => {
-                let mut events = String::from("");
-                let orig_index = o.index();
-                let mut index: usize = 0;
-                loop {
-                    if !o.get_element(index) {
-                        break;
-                    }
-
-                    if !events.is_empty() {
-                        events.push('|');
-                    }
-
-                    let new_date = o.event_date();
-                    events.push_str(
-                        format!(
-                            "{:04}-{:02}-{:02}",
-                            new_date / 10000,
-                            new_date / 100 % 100,
-                            new_date % 100
-                        )
-                        .as_str(),
-                    );
-
-                    events.push('~');
-                    events.push_str(o.sort_order().to_string().as_str());
-                    events.push('~');
+        let events =
+            match self
+                .engine
+                .create_template_events(group_param, event_param, cf_index as usize)
+            {
+                Err(_e) => return String::from(""),
+                Ok(o) => {
+                    let mut events = String::from("");
+                    let orig_index = o.index();
+                    let mut index: usize = 0;
+                    loop {
+                        if !o.get_element(index) {
+                            break;
+                        }
 
-                    let param_count: usize = match o.list_parameter() {
-                        None => 0,
-                        Some(o) => o.count(),
-                    };
+                        if !events.is_empty() {
+                            events.push('|');
+                        }
 
-                    events.push_str(param_count.to_string().as_str());
-                    index += 1;
+                        let new_date = o.event_date();
+                        events.push_str(
+                            format!(
+                                "{:04}-{:02}-{:02}",
+                                new_date / 10000,
+                                new_date / 100 % 100,
+                                new_date % 100
+                            )
+                            .as_str(),
+                        );
+
+                        events.push('~');
+                        events.push_str(o.sort_order().to_string().as_str());
+                        events.push('~');
+
+                        let param_count: usize = match o.list_parameter() {
+                            None => 0,
+                            Some(o) => o.count(),
+                        };
+
+                        events.push_str(param_count.to_string().as_str());
+                        index += 1;
+                    }
+                    o.get_element(orig_index);
+                    events
                 }
-                o.get_element(orig_index);
-                events
-            }
+            };
+
+        if !self.balance_events() {
+            return String::from("");
         }
+
+        events
     }
 
     /// Creates a new cashflow from a named template group.
@@ -1139,12 +1636,14 @@ impl Engine {
         group_param: &str,
         new_name_param: &str,
     ) -> String {
-        match self.engine.create_cashflow_from_template_group(
+        let count = self.cashflow_count();
+
+        let initial_name = match self.engine.create_cashflow_from_template_group(
             group_param,
             new_name_param,
             group_param,
         ) {
-            Err(_e) => String::from(""),
+            Err(_e) => return String::from(""),
             Ok(_o) => {
                 let mut initial_name = String::from("*");
                 let calc_mgr = self.engine.calc_mgr();
@@ -1164,7 +1663,13 @@ impl Engine {
                 list_template_event.get_element(orig_index);
                 initial_name
             }
+        };
+
+        if !self.apply_cashflow_rounding(count..self.cashflow_count()) {
+            return String::from("");
         }
+
+        initial_name
     }
 
     /// Calculates number of intervals between two dates.
@@ -1339,7 +1844,8 @@ impl Engine {
         }
     }
 
-    /// Format and return a currency string.
+    /// Format and return a currency string. The value is first rounded
+    /// with the selected cashflow's decimal digits and rounding mode.
     ///
     /// # Arguments
     ///
@@ -1352,7 +1858,7 @@ impl Engine {
     pub fn format_currency_out(&self, val: &str) -> String {
         match val.parse::<Decimal>() {
             Err(_e) => String::from("0.0"),
-            Ok(o) => self.engine.format_currency_out(o),
+            Ok(o) => self.engine.format_currency_out(self.round_currency(o)),
         }
     }
 
@@ -1367,290 +1873,606 @@ impl Engine {
         self.engine.calc_mgr_mut().list_template_group_mut().clear();
     }
 
-    /// Deserialize and ingest the json input.
+    /// Clear the actual payments recorded against the selected cashflow.
     ///
     /// # Arguments
     ///
-    /// * `json_input` - The json input string.
+    /// * `cf_index` - The cashflow index.
     ///
     /// # Return
     ///
-    /// * Returns empty string if successful or an error.
+    /// * True if successful, otherwise false.
 
-    pub fn deserialize(&self, json_input: &str) -> String {
-        let json = CalcJsonDeserialize::new(self.engine.calc_manager());
+    pub fn clear_actual_payments(&self, cf_index: i32) -> bool {
+        if cf_index < 0 {
+            return false;
+        }
 
-        match json.deserialize(String::from(json_input)) {
-            Err(_e) => {
-                return String::from("Json error");
+        {
+            let calc_mgr = self.engine.calc_mgr();
+            if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
+                return false;
             }
-            Ok(_o) => {}
         }
 
-        String::from("")
+        self.set_cashflow_descriptor(GROUP_ACTUAL, NAME_ACTUAL_PAYMENTS, "")
     }
 
-    /// Get the status string for the selected cashflow
-    /// as status expression.
+    /// Clear the exchange rate table.
+
+    pub fn clear_exchange_rates(&self) {
+        self.exchange_rates.borrow_mut().clear();
+    }
+
+    /// Clone the selected cashflow (preferences and events)
+    /// under a new name. The clone is appended to the cashflow
+    /// list and must be initialized with init_cashflow.
     ///
     /// # Arguments
     ///
     /// * `cf_index` - The cashflow index.
-    /// * `status` - Status expression to evaluate.
+    /// * `new_name` - The name of the cloned cashflow.
     ///
     /// # Return
     ///
-    /// * Returns the cashflow status string.
+    /// * The cashflow index of the clone or -1 if not successful.
 
-    pub fn get_cashflow_status(&self, cf_index: i32, status: &str) -> String {
-        let calc_mgr = self.engine.calc_mgr();
+    pub fn clone_cashflow(&self, cf_index: i32, new_name: &str) -> i32 {
+        if cf_index < 0 || new_name.trim().is_empty() {
+            return -1;
+        }
 
-        if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
-            return String::from("");
-        }
+        let count = self.cashflow_count();
 
-        let mut list_parameter: Option<&ListParameter> = None;
-        match calc_mgr.list_cashflow().preferences() {
-            None => {}
-            Some(o) => {
-                list_parameter = Option::from(o.list_parameter());
-            }
+        if !self
+            .engine
+            .calc_mgr()
+            .list_cashflow()
+            .get_element(cf_index as usize)
+        {
+            return -1;
         }
 
-        let result_symbol = self
-            .engine
-            .evaluate_expression(list_parameter, status, true);
+        let text = {
+            let json = CalcJsonSerialize::new(self.engine.calc_manager());
+            json.serialize(SERIALIZE_CASHFLOW)
+        };
 
-        match result_symbol.sym_type() {
-            amfnengine::TokenType::Integer => {
-                self.engine.format_integer_out(result_symbol.sym_integer())
-            }
-            amfnengine::TokenType::Decimal => {
-                self.engine.format_decimal_out(result_symbol.sym_decimal())
-            }
-            _ => String::from(result_symbol.sym_string()),
+        let text = match Engine::rename_serialized_cashflow(text.as_str(), new_name.trim()) {
+            None => return -1,
+            Some(o) => o,
+        };
+
+        if !self.deserialize(text.as_str()).is_empty() {
+            return -1;
+        }
+
+        if self.cashflow_count() != count + 1 {
+            return -1;
         }
+
+        count as i32
     }
 
-    /// Get the chart definitions for the selected cashflow.
+    /// Compare cashflows side by side. The summary results, total
+    /// interest, final balance, term and payoff date of each cashflow
+    /// are returned along with their differences relative to a
+    /// baseline cashflow.
     ///
     /// # Arguments
     ///
-    /// * `cf_index` - The cashflow index.
+    /// * `cf_indexes` - The cashflow indexes separated by '|' (e.g., "0|2|3").
+    /// * `baseline_index` - The cashflow index of the baseline.
     ///
     /// # Return
     ///
-    /// * Returns an array of chart definition elements.
-
-    pub fn get_chart_definitions(&self, cf_index: i32) -> Array {
-        let calc_mgr = self.engine.calc_mgr();
+    /// * Return a string that can be directly loaded into ag-grid
+    ///   (see ComparisonUtility::to_json) or an empty string if not successful.
 
-        if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
-            return Array::new();
+    pub fn compare_cashflows(&self, cf_indexes: &str, baseline_index: i32) -> String {
+        let mut indexes: Vec<usize> = Vec::new();
+        for token in cf_indexes.split('|') {
+            match token.trim().parse::<usize>() {
+                Err(_e) => return String::from(""),
+                Ok(o) => indexes.push(o),
+            }
         }
 
-        let mut ary_chart: Vec<WasmElemChart> = Vec::new();
-        let list_descriptor = calc_mgr.preferences().list_descriptor();
-        let mut index: usize = 0;
+        let baseline = indexes
+            .iter()
+            .position(|o| baseline_index >= 0 && *o == baseline_index as usize)
+            .unwrap_or(0);
 
-        loop {
-            if !list_descriptor.get_element(index) {
-                break;
+        let count = indexes.len();
+        let mut rows: Vec<ElemComparisonRow> = Vec::new();
+        let mut metric_rows: Vec<ElemComparisonRow> = {
+            let calc_mgr = self.engine.calc_mgr();
+            let list_locale = calc_mgr.list_locale();
+            [
+                (COMPARE_TOTAL_INTEREST, "Compare_Total_Interest"),
+                (COMPARE_FINAL_BALANCE, "Compare_Final_Balance"),
+                (COMPARE_TERM, "Compare_Term"),
+                (COMPARE_PAYOFF_DATE, "Compare_Payoff_Date"),
+            ]
+            .iter()
+            .map(|(name, key)| ElemComparisonRow::new(name, list_locale.get_resource(key), count))
+            .collect()
+        };
+
+        for (position, cf_index) in indexes.iter().enumerate() {
+            if !self
+                .engine
+                .calc_mgr()
+                .list_cashflow()
+                .get_element(*cf_index)
+            {
+                return String::from("");
             }
 
-            if list_descriptor.group() == "Chart" && list_descriptor.desc_type() == "custom" {
-                ary_chart.push(WasmElemChart::new(
-                    list_descriptor.name(),
-                    list_descriptor.value().as_str(),
-                ));
+            for summary in self.summary_results().iter() {
+                let result = summary.result();
+                let number = self.display_number(result.as_str());
+                let label = summary.label();
+                ComparisonUtility::find_row(
+                    &mut rows,
+                    summary.name().as_str(),
+                    label.as_str(),
+                    count,
+                )
+                .set_value(position, result.as_str(), number);
             }
 
-            index += 1;
+            let am_rows = match self.amortization_rows(true) {
+                None => return String::from(""),
+                Some(o) => o,
+            };
+            let metrics = ElemPayoffMetrics::new(&am_rows);
+            let final_balance = am_rows.last().map_or(dec!(0.0), |o| o.balance());
+
+            metric_rows[0].set_value(
+                position,
+                self.engine
+                    .format_currency_out(metrics.total_interest())
+                    .as_str(),
+                Some(metrics.total_interest()),
+            );
+            metric_rows[1].set_value(
+                position,
+                self.engine.format_currency_out(final_balance).as_str(),
+                Some(final_balance),
+            );
+            metric_rows[2].set_value(
+                position,
+                self.engine.format_integer_out(metrics.payments()).as_str(),
+                Some(Decimal::from(metrics.payments())),
+            );
+            metric_rows[3].set_value(
+                position,
+                self.engine.format_date_out(metrics.payoff_date()).as_str(),
+                None,
+            );
         }
 
-        match calc_mgr.list_cashflow().preferences() {
-            None => {}
-            Some(o) => {
-                let list_descriptor = o.list_descriptor();
-                let mut index: usize = 0;
-
-                loop {
-                    if !list_descriptor.get_element(index) {
-                        break;
-                    }
-
-                    if list_descriptor.group() == "Chart" && list_descriptor.desc_type() == "custom"
-                    {
-                        ary_chart.push(WasmElemChart::new(
-                            list_descriptor.name(),
-                            list_descriptor.value().as_str(),
-                        ));
-                    }
-
-                    index += 1;
-                }
-            }
-        }
+        rows.append(&mut metric_rows);
 
-        ary_chart.into_iter().map(JsValue::from).collect()
+        ComparisonUtility::to_json(&rows, count, baseline)
     }
 
-    /// Get the event by date and sort order.
+    /// Create the construction loan schedule of the selected cashflow.
+    /// Principal is advanced by its draw events and the interest
+    /// accrued through the conversion date is funded from an interest
+    /// reserve before the loan converts to a permanent amortizing loan.
     ///
     /// # Arguments
     ///
     /// * `cf_index` - The cashflow index.
-    /// * `date_param` - The date to select.
-    /// * `sort_param` - The sort order to select.
     ///
     /// # Return
     ///
-    /// * The event index or -1.
+    /// * A json string with the "conversion-date", "draws", "reserve",
+    ///   "peak-balance", "conversion-balance" and the schedule "rows"
+    ///   or an empty string if not successful.
 
-    pub fn get_event_by_date(&self, cf_index: i32, date_param: &str, sort_param: u32) -> u32 {
-        let calc_mgr = self.engine.calc_mgr();
+    pub fn construction_values(&self, cf_index: i32) -> String {
+        if cf_index < 0 {
+            return String::from("");
+        }
 
-        if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
-            return 0;
+        if !self
+            .engine
+            .calc_mgr()
+            .list_cashflow()
+            .get_element(cf_index as usize)
+        {
+            return String::from("");
         }
 
-        let date_in = CoreUtility::parse_date(self.engine.format_date_in(date_param).as_str());
+        let am_rows = match self.amortization_rows(true) {
+            None => return String::from(""),
+            Some(o) => o,
+        };
 
-        match calc_mgr.list_cashflow().list_event() {
-            None => 0,
-            Some(o) => {
-                if !o.get_element_by_date(date_in, sort_param as usize) {
-                    return 0;
-                }
-                o.index() as u32
-            }
+        let (schedule, summary) =
+            ConstructionUtility::schedule(&am_rows, self.cashflow_conversion_date());
+
+        let mut rows = String::from("");
+        for (row_index, row) in schedule.iter().enumerate() {
+            let delimiter = if row_index == 0 { "" } else { "," };
+            rows = format!(
+                "{}{}{{\"Date\":\"{}\",\"Draw\":\"{}\",\"Interest\":\"{}\",\"Reserve\":\"{}\",\
+                \"Payment\":\"{}\",\"Balance\":\"{}\"}}",
+                rows,
+                delimiter,
+                DateUtility::format_date(row.event_date()),
+                row.draw(),
+                row.interest(),
+                row.reserve(),
+                row.payment(),
+                row.balance()
+            );
         }
+
+        format!(
+            "{{\"conversion-date\":\"{}\",\"draws\":\"{}\",\"reserve\":\"{}\",\
+            \"peak-balance\":\"{}\",\"conversion-balance\":\"{}\",\"rows\":[{}]}}",
+            DateUtility::format_date(summary.conversion_date()),
+            summary.total_draws(),
+            summary.reserve(),
+            summary.peak_balance(),
+            summary.conversion_balance(),
+            rows
+        )
     }
 
-    /// Get selected user or cashflow preferences.
+    /// Simulate the payoff of a credit card balance with minimum
+    /// payments and compare it with paying a fixed amount. The balance,
+    /// the APR and the minimum payments are added to the selected
+    /// cashflow, which must have no events (e.g., a new cashflow), so
+    /// the payoff schedule renders in the amortization table. The
+    /// totals of the minimum payments are those of the balanced cashflow.
     ///
     /// # Arguments
     ///
-    /// * `cf_index` - The cashflow index or -1 for user preferences.
+    /// * `cf_index` - The cashflow index.
+    /// * `start_date` - The date of the starting balance.
+    /// * `balance` - The starting balance.
+    /// * `apr` - The annual percentage rate.
+    /// * `formula` - The minimum payment formula (percent~floor, e.g.,
+    ///   "1~25" for one percent of the balance plus interest or 25
+    ///   if greater).
+    /// * `fixed_payment` - The fixed payment to compare with
+    ///   (empty for none).
     ///
     /// # Return
     ///
-    /// * See description.
+    /// * A json string with the "months" to payoff, "total-interest"
+    ///   and "total-paid" of the minimum payments and the "fixed-months",
+    ///   "fixed-total-interest", "months-saved" and "interest-saved" of
+    ///   the fixed payment (empty if the fixed payment never pays off
+    ///   the balance) or an empty string if not successful.
 
-    pub fn get_preferences(&self, cf_index: i32) -> WasmElemPreferences {
-        let calc_mgr = self.engine.calc_mgr();
-        let prefs: &ElemPreferences;
+    pub fn credit_card_simulation(
+        &self,
+        cf_index: i32,
+        start_date: &str,
+        balance: &str,
+        apr: &str,
+        formula: &str,
+        fixed_payment: &str,
+    ) -> String {
+        if cf_index < 0 {
+            return String::from("");
+        }
 
-        if cf_index >= 0 {
-            calc_mgr.list_cashflow().get_element(cf_index as usize);
+        {
+            let calc_mgr = self.engine.calc_mgr();
+            if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
+                return String::from("");
+            }
+        }
 
-            match calc_mgr.list_cashflow().preferences() {
-                None => {
-                    prefs = calc_mgr.preferences();
-                }
+        if self
+            .engine
+            .calc_mgr()
+            .list_cashflow()
+            .list_event()
+            .map_or(false, |o| o.count() > 0)
+        {
+            return String::from("");
+        }
+
+        let start_date = CoreUtility::parse_date(self.engine.format_date_in(start_date).as_str());
+        let (balance, apr) = match (self.display_number(balance), self.display_number(apr)) {
+            (Some(o1), Some(o2)) if start_date > 0 && o1 > dec!(0.0) && o2 >= dec!(0.0) => (o1, o2),
+            _ => return String::from(""),
+        };
+        let formula = match ElemMinimumPaymentFormula::parse(formula) {
+            None => return String::from(""),
+            Some(o) => o,
+        };
+        let decimal_digits = self.cashflow_decimal_digits();
+
+        let minimum = match CreditCardUtility::minimum_payoff(
+            balance,
+            apr,
+            start_date,
+            &formula,
+            decimal_digits,
+        ) {
+            None => return String::from(""),
+            Some(o) => o,
+        };
+
+        let fixed = if fixed_payment.trim().is_empty() {
+            None
+        } else {
+            match self.display_number(fixed_payment) {
+                None => return String::from(""),
                 Some(o) => {
-                    prefs = o;
+                    CreditCardUtility::fixed_payoff(balance, apr, start_date, o, decimal_digits)
                 }
             }
-        } else {
-            prefs = calc_mgr.preferences();
+        };
+
+        let eom = DateUtility::to_naive(start_date).map_or(false, |o| {
+            o.day() == DateUtility::days_in_month(o.year(), o.month())
+        });
+        let (loan_extension, payment_extension, interest_extension) = match (
+            self.principal_extension(false, eom),
+            self.principal_extension(true, eom),
+            self.interest_extension(),
+        ) {
+            (Some(o1), Some(o2), Some(o3)) => (o1, o2, o3),
+            _ => return String::from(""),
+        };
+
+        let frequency = CoreUtility::get_frequency("1-month");
+        let mut result = self.add_event(start_date, 10, balance, 1, 1, frequency, loan_extension)
+            && self.add_event(start_date, 40, apr, 1, 1, frequency, interest_extension);
+        for (event_date, payment, periods) in CreditCardUtility::payment_runs(minimum.rows()) {
+            result = result
+                && self.add_event(
+                    event_date,
+                    12,
+                    payment,
+                    periods,
+                    1,
+                    frequency,
+                    payment_extension.clone(),
+                );
         }
 
-        WasmElemPreferences::new(
-            prefs.locale_str(),
-            prefs.group(),
-            prefs.cross_rate_code(),
-            prefs.default_encoding(),
-            prefs.fiscal_year_start() as u32,
-            prefs.decimal_digits() as u32,
-            prefs.target().to_string().as_str(),
+        self.engine.evaluate_cashflow_event_type_all();
+
+        let rows = if result && self.balance_events() {
+            self.amortization_rows(true)
+        } else {
+            None
+        };
+        let (months, total_interest, total_paid) = match rows {
+            None => {
+                if let Some(o) = self
+                    .engine
+                    .calc_mgr_mut()
+                    .list_cashflow_mut()
+                    .list_event_mut()
+                {
+                    while o.get_element(0) && o.remove() {}
+                }
+                return String::from("");
+            }
+            Some(o) => CreditCardUtility::am_totals(&o),
+        };
+
+        let fixed_values = match fixed {
+            None => String::from(
+                "\"fixed-months\":\"\",\"fixed-total-interest\":\"\",\
+                \"months-saved\":\"\",\"interest-saved\":\"\"",
+            ),
+            Some(o) => format!(
+                "\"fixed-months\":\"{}\",\"fixed-total-interest\":\"{}\",\
+                \"months-saved\":\"{}\",\"interest-saved\":\"{}\"",
+                o.months(),
+                o.total_interest(),
+                months as i64 - o.months() as i64,
+                total_interest - o.total_interest()
+            ),
+        };
+
+        format!(
+            "{{\"months\":\"{}\",\"total-interest\":\"{}\",\"total-paid\":\"{}\",{}}}",
+            months, total_interest, total_paid, fixed_values
         )
     }
 
-    /// Get a specific resource.
+    /// Recompute the amortization of the selected cashflow with
+    /// daily simple interest accrued between the dates the actual
+    /// payments were received. Each payment is applied to the accrued
+    /// interest first and then to principal, and the resulting balance
+    /// is compared with the scheduled balance as of the same date.
     ///
     /// # Arguments
     ///
     /// * `cf_index` - The cashflow index.
-    /// * `key` - The resource key.
+    /// * `rate` - The annual interest rate (empty for the rate of
+    ///   the cashflow's first interest change event).
     ///
     /// # Return
     ///
-    /// * See description.
+    /// * A json string with the "rate", the payment "rows", the final
+    ///   "balance", "scheduled-balance" and "difference" or an empty
+    ///   string if not successful.
 
-    pub fn get_resource(&self, cf_index: i32, key: &str) -> String {
-        let calc_mgr = self.engine.calc_mgr();
+    pub fn daily_interest_values(&self, cf_index: i32, rate: &str) -> String {
+        if cf_index < 0 {
+            return String::from("");
+        }
 
-        if cf_index >= 0 {
-            calc_mgr.list_cashflow().get_element(cf_index as usize);
+        if !self
+            .engine
+            .calc_mgr()
+            .list_cashflow()
+            .get_element(cf_index as usize)
+        {
+            return String::from("");
         }
 
-        let locale = calc_mgr.list_locale();
-        String::from(locale.get_resource(key))
-    }
+        let flows = match self.amortization_rows(true) {
+            None => return String::from(""),
+            Some(o) => ElemCashFlow::from_rows(&o),
+        };
 
-    /// Get the loaded template names.
-    ///
-    /// # Return
-    ///
-    /// * Returns an array of template group names.
+        let rate = if rate.trim().is_empty() {
+            self.interest_change_rate()
+        } else {
+            self.display_number(rate)
+        };
+        let rate = match rate {
+            None => return String::from(""),
+            Some(o) => o,
+        };
 
-    pub fn get_template_names(&self) -> Array {
-        let calc_mgr = self.engine.calc_mgr();
-        let list_template_group = calc_mgr.list_template_group();
-        let mut ary_template_groups: Vec<String> = Vec::new();
+        let schedule = match DailyInterestUtility::schedule(
+            &flows,
+            &self.cashflow_actual_payments(),
+            rate,
+            self.cashflow_decimal_digits(),
+        ) {
+            None => return String::from(""),
+            Some(o) => o,
+        };
 
-        let orig_index = list_template_group.index();
-        let mut index: usize = 0;
-        loop {
-            if !list_template_group.get_element(index) {
-                break;
-            }
-            ary_template_groups.push(String::from(list_template_group.group()));
-            index += 1;
+        let mut rows = String::from("");
+        for (row_index, row) in schedule.iter().enumerate() {
+            let delimiter = if row_index == 0 { "" } else { "," };
+            rows = format!(
+                "{}{}{{\"Date\":\"{}\",\"Payment\":\"{}\",\"Days\":\"{}\",\"Interest\":\"{}\",\
+                \"Principal\":\"{}\",\"Unpaid-interest\":\"{}\",\"Balance\":\"{}\",\
+                \"Scheduled-balance\":\"{}\",\"Difference\":\"{}\"}}",
+                rows,
+                delimiter,
+                DateUtility::format_date(row.event_date()),
+                row.payment(),
+                row.days(),
+                row.interest(),
+                row.principal(),
+                row.unpaid_interest(),
+                row.balance(),
+                row.scheduled_balance(),
+                row.difference()
+            );
         }
-        list_template_group.get_element(orig_index);
 
-        ary_template_groups.into_iter().map(JsValue::from).collect()
+        let (balance, scheduled_balance) = match schedule.last() {
+            None => match flows.first() {
+                None => (dec!(0.0), dec!(0.0)),
+                Some(o) => (-o.cash(), -o.cash()),
+            },
+            Some(o) => (o.balance(), o.scheduled_balance()),
+        };
+
+        format!(
+            "{{\"rate\":\"{}\",\"rows\":[{}],\"balance\":\"{}\",\
+            \"scheduled-balance\":\"{}\",\"difference\":\"{}\"}}",
+            rate,
+            rows,
+            balance,
+            scheduled_balance,
+            balance - scheduled_balance
+        )
     }
 
-    /// Get the loaded template event names for a template group.
+    /// Create the missed or late payment record of each scheduled
+    /// payment of the selected cashflow as of a date. The actual
+    /// payments recorded against the cashflow are applied to the
+    /// oldest scheduled payments first and late fees are assessed
+    /// with the cashflow's grace period and late-fee rules.
     ///
     /// # Arguments
     ///
-    /// * `group_param` - The template group name.
+    /// * `cf_index` - The cashflow index.
+    /// * `as_of_date` - The as of date.
     ///
     /// # Return
     ///
-    /// * Returns an array of template event names.
+    /// * A json string with the "rules", "past-due", "days-delinquent",
+    ///   "late-fees", "missed-payments", "late-payments" and the payment
+    ///   "rows" or an empty string if not successful.
 
-    pub fn get_template_event_names(&self, group_param: &str) -> Array {
-        let calc_mgr = self.engine.calc_mgr();
-        let list_template_group = calc_mgr.list_template_group();
+    pub fn delinquency_values(&self, cf_index: i32, as_of_date: &str) -> String {
+        if cf_index < 0 {
+            return String::from("");
+        }
 
-        let mut ary_template_events: Vec<String> = Vec::new();
+        if !self
+            .engine
+            .calc_mgr()
+            .list_cashflow()
+            .get_element(cf_index as usize)
+        {
+            return String::from("");
+        }
 
-        if !list_template_group.get_element_by_group(group_param, true) {
-            return ary_template_events.into_iter().map(JsValue::from).collect();
+        let as_of = CoreUtility::parse_date(self.engine.format_date_in(as_of_date).as_str());
+        if as_of == 0 {
+            return String::from("");
         }
 
-        let list_template_event = list_template_group.list_template_event();
+        let flows = match self.amortization_rows(true) {
+            None => return String::from(""),
+            Some(o) => ElemCashFlow::from_rows(&o),
+        };
 
-        let orig_index = list_template_event.index();
-        let mut index: usize = 0;
-        loop {
-            if !list_template_event.get_element(index) {
-                break;
-            }
-            ary_template_events.push(String::from(list_template_event.name()));
-            index += 1;
+        let rules = self.cashflow_delinquency_rules();
+        let records = DelinquencyUtility::records(
+            &flows,
+            &self.cashflow_actual_payments(),
+            &rules,
+            as_of,
+            self.cashflow_decimal_digits(),
+        );
+        let summary = DelinquencyUtility::summary(&records);
+
+        let mut rows = String::from("");
+        for (row_index, row) in records.iter().enumerate() {
+            let delimiter = if row_index == 0 { "" } else { "," };
+            rows = format!(
+                "{}{}{{\"Date\":\"{}\",\"Amount-due\":\"{}\",\"Amount-paid\":\"{}\",\
+                \"Paid-date\":\"{}\",\"Days-late\":\"{}\",\"Late-fee\":\"{}\",\"Status\":\"{}\"}}",
+                rows,
+                delimiter,
+                DateUtility::format_date(row.due_date()),
+                row.amount_due(),
+                row.amount_paid(),
+                if row.paid_date() == 0 {
+                    String::from("")
+                } else {
+                    DateUtility::format_date(row.paid_date())
+                },
+                row.days_late(),
+                row.late_fee(),
+                DelinquencyUtility::status_mnemonic(row.status())
+            );
         }
-        list_template_event.get_element(orig_index);
 
-        ary_template_events.into_iter().map(JsValue::from).collect()
+        format!(
+            "{{\"as-of-date\":\"{}\",\"rules\":\"{}\",\"past-due\":\"{}\",\
+            \"days-delinquent\":\"{}\",\"late-fees\":\"{}\",\"missed-payments\":\"{}\",\
+            \"late-payments\":\"{}\",\"rows\":[{}]}}",
+            DateUtility::format_date(as_of),
+            rules.descriptor_value(),
+            summary.past_due(),
+            summary.days_delinquent(),
+            summary.late_fees(),
+            summary.missed_payments(),
+            summary.late_payments(),
+            rows
+        )
     }
 
-    /// Initialize the selected cashflow.
+    /// Create the depreciation schedule of the selected cashflow's
+    /// depreciation events (events with a depreciation method). The
+    /// schedule is aligned with the cashflow's fiscal year.
     ///
     /// # Arguments
     ///
@@ -1658,340 +2480,311 @@ impl Engine {
     ///
     /// # Return
     ///
-    /// * Returns the cashflow's name.
+    /// * A json string with the depreciable "assets" and the fiscal
+    ///   year "rows" or an empty string if not successful.
 
-    pub fn init_cashflow(&self, cf_index: i32) -> String {
+    pub fn depreciation_values(&self, cf_index: i32) -> String {
         if cf_index < 0 {
             return String::from("");
         }
 
-        if !self.engine.init_cashflow(cf_index as u32) {
+        if !self
+            .engine
+            .calc_mgr()
+            .list_cashflow()
+            .get_element(cf_index as usize)
+        {
             return String::from("");
         }
 
-        let calc_mgr = self.engine.calc_mgr();
+        let assets = self.depreciation_assets();
+        let fiscal_year_start = self.fiscal_year_start();
+        let rows = DepreciationUtility::schedule(
+            &assets,
+            fiscal_year_start,
+            self.cashflow_decimal_digits(),
+        );
 
-        let locale_str: &str;
-        let group: &str;
-        match calc_mgr.list_cashflow().preferences() {
-            None => {
-                locale_str = calc_mgr.preferences().locale_str();
-                group = "";
-            }
-            Some(o) => {
-                if o.locale_str().is_empty() {
-                    locale_str = calc_mgr.preferences().locale_str();
-                } else {
-                    locale_str = o.locale_str();
-                }
-                group = o.group();
-            }
+        let mut asset_values = String::from("");
+        for (asset_index, asset) in assets.iter().enumerate() {
+            let delimiter = if asset_index == 0 { "" } else { "," };
+            asset_values = format!(
+                "{}{}{{\"Date\":\"{}\",\"Cost\":\"{}\",\"Method\":\"{}\"}}",
+                asset_values,
+                delimiter,
+                DateUtility::format_date(asset.event_date()),
+                asset.cost(),
+                asset.depreciation().descriptor_value()
+            );
+        }
+
+        let mut row_values = String::from("");
+        for (row_index, row) in rows.iter().enumerate() {
+            let delimiter = if row_index == 0 { "" } else { "," };
+            row_values = format!(
+                "{}{}{{\"Date\":\"{}\",\"Depreciation\":\"{}\",\
+                \"Accumulated-depreciation\":\"{}\",\"Book-value\":\"{}\"}}",
+                row_values,
+                delimiter,
+                DateUtility::format_date(row.event_date()),
+                row.depreciation(),
+                row.accumulated(),
+                row.book_value()
+            );
         }
 
         format!(
-            "{}|{}|{}",
-            calc_mgr.list_cashflow().name(),
-            locale_str,
-            group
+            "{{\"fiscal-year-start\":\"{:04}\",\"assets\":[{}],\"rows\":[{}]}}",
+            fiscal_year_start, asset_values, row_values
         )
     }
 
-    /// Initialize and return the selected cashflow's
-    /// status expression.
+    /// Deserialize and ingest the json input. The exchange rates
+    /// saved with the cashflows are added to the exchange rate table.
     ///
     /// # Arguments
     ///
-    /// * `cf_index` - The cashflow index.
+    /// * `json_input` - The json input string.
     ///
     /// # Return
     ///
-    /// * See description.
+    /// * Returns empty string if successful or an error.
 
-    pub fn init_cashflow_status(&self, cf_index: i32) -> String {
-        if cf_index < 0 {
-            return String::from("");
-        }
+    pub fn deserialize(&self, json_input: &str) -> String {
+        let count = self.cashflow_count();
 
-        let calc_mgr = self.engine.calc_mgr();
-        if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
-            return String::from("");
-        }
+        {
+            let json = CalcJsonDeserialize::new(self.engine.calc_manager());
 
-        let locale = calc_mgr.list_locale();
-        let cashflow_locale_str = locale.cashflow_locale().locale_str();
+            match json.deserialize(String::from(json_input)) {
+                Err(_e) => {
+                    return String::from("Json error");
+                }
+                Ok(_o) => {}
+            }
+        }
 
-        let mut status = self.engine.calc_mgr().descriptor_value(
-            amfnengine::GROUP_GENERAL,
-            amfnengine::NAME_STATUS,
-            amfnengine::TYPE_LOCALE,
-            cashflow_locale_str,
-            true,
-            false,
-        );
-        if status.is_empty() {
-            status = self.engine.calc_mgr().descriptor_value(
-                amfnengine::GROUP_GENERAL,
-                amfnengine::NAME_STATUS,
-                "",
-                "",
-                true,
-                false,
-            );
-            if status.is_empty() {
-                status = String::from(locale.get_resource(amfnengine::USER_STATUS));
+        for cf_index in count..self.cashflow_count() {
+            if self.engine.calc_mgr().list_cashflow().get_element(cf_index) {
+                let value = self.cashflow_descriptor(GROUP_EXCHANGE, NAME_EXCHANGE_RATES);
+                self.exchange_rates
+                    .borrow_mut()
+                    .add_rates_value(value.as_str());
             }
         }
 
-        status
+        if !self.apply_cashflow_rounding(count..self.cashflow_count()) {
+            return self.element_error();
+        }
+
+        String::from("")
     }
 
-    /// Parse and return an array of WasmElemColumns.
+    /// Duplicate an event of the selected cashflow. The duplicate
+    /// is added on the same date with the next unused sort order.
     ///
     /// # Arguments
     ///
     /// * `cf_index` - The cashflow index.
-    /// * `table_type_param` - The type of table (event or amortization).
+    /// * `index` - The event index.
     ///
     /// # Return
     ///
-    /// * See description.
+    /// * The index of the duplicate event or -1 if not successful.
 
-    pub fn parse_columns(&self, cf_index: i32, table_type_param: u32) -> Array {
+    pub fn duplicate_event(&self, cf_index: i32, index: u32) -> i32 {
         if cf_index < 0 {
-            return Array::new();
+            return -1;
         }
 
-        let calc_mgr = self.engine.calc_mgr();
-        if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
-            return Array::new();
+        {
+            let calc_mgr = self.engine.calc_mgr();
+            if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
+                return -1;
+            }
         }
 
-        let table_type: TableType = match table_type_param {
-            TABLE_AM => TableType::Amortization,
-            _ => TableType::Event,
+        let copy = match self.event_copy(index as usize) {
+            None => return -1,
+            Some(o) => o,
         };
 
-        let mut ary_column: Vec<WasmElemColumn> = Vec::new();
-        let list_column = self.engine.parse_columns(table_type);
-        let mut index: usize = 0;
-
-        loop {
-            if !list_column.get_element(index) {
-                break;
-            }
-
-            ary_column.push(WasmElemColumn::new(
-                list_column.col_name(),
-                list_column.col_name_index() as u32,
-                list_column.col_header(),
-                list_column.col_description(),
-                list_column.group(),
-                list_column.name(),
-                list_column.col_type(),
-                list_column.col_name(),
-                list_column.format() as u32,
-                list_column.decimal_digits() as u32,
-                list_column.column_width() as u32,
-                list_column.column_editable(),
-            ));
+        let sort_order = ClipboardUtility::free_sort_order(
+            &self.event_sort_orders(),
+            copy.event_date(),
+            copy.sort_order(),
+        );
 
-            index += 1;
+        if self
+            .paste_event(&copy, copy.event_date(), sort_order)
+            .is_none()
+            || !self.balance_events()
+        {
+            return -1;
         }
 
-        ary_column.into_iter().map(JsValue::from).collect()
+        self.event_index_by_date(copy.event_date(), sort_order)
+            .map_or(-1, |o| o as i32)
     }
 
-    /// Parse and return an array of WasmDescriptors.
+    /// Create the effective interest method (amortized cost) schedule
+    /// of the selected cashflow. The effective rate discounts the
+    /// amortization cash flows, net of the upfront fees, to zero and
+    /// the difference from the contractual interest amortizes the fees.
     ///
     /// # Arguments
     ///
     /// * `cf_index` - The cashflow index.
-    /// * `index` - The event index of the cashflow.
-    /// * `table_type_param` - The type of table (event or amortization).
+    /// * `fees` - The upfront fees received (negative for costs
+    ///   capitalized; empty for none).
     ///
     /// # Return
     ///
-    /// * See description.
+    /// * A json string with the "effective-rate" (annual percentage)
+    ///   and the schedule "rows" or an empty string if not successful.
 
-    pub fn parse_descriptors(&self, cf_index: i32, index: u32, table_type_param: u32) -> Array {
+    pub fn effective_interest_values(&self, cf_index: i32, fees: &str) -> String {
         if cf_index < 0 {
-            return Array::new();
+            return String::from("");
         }
 
-        let calc_mgr = self.engine.calc_mgr();
-        if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
-            return Array::new();
+        if !self
+            .engine
+            .calc_mgr()
+            .list_cashflow()
+            .get_element(cf_index as usize)
+        {
+            return String::from("");
         }
 
-        match table_type_param {
-            TABLE_AM => match calc_mgr.list_cashflow().list_amortization() {
-                None => Array::new(),
-                Some(o) => {
-                    if !o.get_element(index as usize) {
-                        return Array::new();
-                    }
+        let fees = if fees.trim().is_empty() {
+            dec!(0.0)
+        } else {
+            match self.display_number(fees) {
+                None => return String::from(""),
+                Some(o) => o,
+            }
+        };
 
-                    match o.list_descriptor() {
-                        None => Array::new(),
-                        Some(o2) => {
-                            let mut list: Vec<WasmDescriptor> = Vec::new();
-                            let orig_index = o2.index();
-                            let mut index: usize = 0;
-                            loop {
-                                if !o2.get_element(index) {
-                                    break;
-                                }
-                                list.push(WasmDescriptor::new(
-                                    o2.group(),
-                                    o2.name(),
-                                    o2.desc_type(),
-                                    o2.code(),
-                                    o2.value().as_str(),
-                                    o2.value_expr().as_str(),
-                                    o2.propagate(),
-                                    o2.list_event_index() as u32,
-                                ));
-                                index += 1;
-                            }
-                            o2.get_element(orig_index);
-                            list.into_iter().map(JsValue::from).collect()
-                        }
-                    }
-                }
-            },
-            _ => match calc_mgr.list_cashflow().list_event() {
-                None => Array::new(),
-                Some(o) => {
-                    if !o.get_element(index as usize) {
-                        return Array::new();
-                    }
+        let flows = match self.amortization_rows(true) {
+            None => return String::from(""),
+            Some(o) => ElemCashFlow::from_rows(&o),
+        };
 
-                    match o.list_descriptor() {
-                        None => Array::new(),
-                        Some(o2) => {
-                            let mut list: Vec<WasmDescriptor> = Vec::new();
-                            let orig_index = o2.index();
-                            let mut index: usize = 0;
-                            loop {
-                                if !o2.get_element(index) {
-                                    break;
-                                }
-                                list.push(WasmDescriptor::new(
-                                    o2.group(),
-                                    o2.name(),
-                                    o2.desc_type(),
-                                    o2.code(),
-                                    o2.value().as_str(),
-                                    o2.value_expr().as_str(),
-                                    o2.propagate(),
-                                    o2.list_event_index() as u32,
-                                ));
-                                index += 1;
-                            }
-                            o2.get_element(orig_index);
-                            list.into_iter().map(JsValue::from).collect()
-                        }
-                    }
-                }
-            },
+        let rate = match EffectiveInterestUtility::effective_rate(&flows, fees) {
+            None => return String::from(""),
+            Some(o) => o,
+        };
+
+        let schedule = match EffectiveInterestUtility::schedule(
+            &flows,
+            fees,
+            rate,
+            self.cashflow_decimal_digits(),
+        ) {
+            None => return String::from(""),
+            Some(o) => o,
+        };
+
+        let mut rows = String::from("");
+        for (row_index, row) in schedule.iter().enumerate() {
+            let delimiter = if row_index == 0 { "" } else { "," };
+            rows = format!(
+                "{}{}{{\"Date\":\"{}\",\"Cash\":\"{}\",\"Contractual-interest\":\"{}\",\
+                \"Interest-income\":\"{}\",\"Fee-amortization\":\"{}\",\"Carrying-amount\":\"{}\"}}",
+                rows,
+                delimiter,
+                DateUtility::format_date(row.event_date()),
+                row.cash(),
+                row.contractual_interest(),
+                row.interest_income(),
+                row.fee_amortization(),
+                row.carrying_amount()
+            );
         }
+
+        format!(
+            "{{\"effective-rate\":\"{}\",\"rows\":[{}]}}",
+            (rate * dec!(100.0)).round_dp(RATE_DECIMAL_DIGITS),
+            rows
+        )
     }
 
-    /// Parse and return an array of WasmParameters.
+    /// Return the selected cashflow's amortization rows with the
+    /// amounts converted to a reporting currency at the exchange
+    /// rate of each row's date.
     ///
     /// # Arguments
     ///
     /// * `cf_index` - The cashflow index.
-    /// * `index` - The event index of the cashflow.
-    /// * `table_type_param` - The type of table (event or amortization).
+    /// * `currency_code` - The reporting currency code (empty for the
+    ///   cashflow's cross rate code).
+    /// * `expanded` - Expand the amortization rows.
     ///
     /// # Return
     ///
-    /// * See description.
+    /// * Return a string that can be directly loaded into ag-grid or
+    ///   an empty string if an exchange rate is not available.
 
-    pub fn parse_parameters(&self, cf_index: i32, index: u32, table_type_param: u32) -> Array {
+    pub fn exchange_values(&self, cf_index: i32, currency_code: &str, expanded: bool) -> String {
         if cf_index < 0 {
-            return Array::new();
+            return String::from("");
         }
 
-        let calc_mgr = self.engine.calc_mgr();
-        if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
-            return Array::new();
+        if !self
+            .engine
+            .calc_mgr()
+            .list_cashflow()
+            .get_element(cf_index as usize)
+        {
+            return String::from("");
         }
 
-        match table_type_param {
-            TABLE_AM => match calc_mgr.list_cashflow().list_amortization() {
-                None => Array::new(),
-                Some(o) => {
-                    if !o.get_element(index as usize) {
-                        return Array::new();
-                    }
+        let to_code = self.reporting_currency_code(currency_code);
+        if to_code.is_empty() {
+            return String::from("");
+        }
 
-                    match o.list_parameter() {
-                        None => Array::new(),
-                        Some(o2) => {
-                            let mut list: Vec<WasmParameter> = Vec::new();
-                            let orig_index = o2.index();
-                            let mut index: usize = 0;
-                            loop {
-                                if !o2.get_element(index) {
-                                    break;
-                                }
-                                list.push(WasmParameter::new(
-                                    o2.name(),
-                                    o2.label(),
-                                    o2.description(),
-                                    CoreUtility::get_param_type(o2.param_type()).as_str(),
-                                    o2.param_integeri(),
-                                    o2.param_decimal().to_string().as_str(),
-                                    o2.param_string(),
-                                ));
-                                index += 1;
-                            }
-                            o2.get_element(orig_index);
-                            list.into_iter().map(JsValue::from).collect()
-                        }
-                    }
-                }
-            },
-            _ => match calc_mgr.list_cashflow().list_event() {
-                None => Array::new(),
-                Some(o) => {
-                    if !o.get_element(index as usize) {
-                        return Array::new();
-                    }
+        let from_code = self.cashflow_currency_code();
 
-                    match o.list_parameter() {
-                        None => Array::new(),
-                        Some(o2) => {
-                            let mut list: Vec<WasmParameter> = Vec::new();
-                            let orig_index = o2.index();
-                            let mut index: usize = 0;
-                            loop {
-                                if !o2.get_element(index) {
-                                    break;
-                                }
-                                list.push(WasmParameter::new(
-                                    o2.name(),
-                                    o2.label(),
-                                    o2.description(),
-                                    CoreUtility::get_param_type(o2.param_type()).as_str(),
-                                    o2.param_integeri(),
-                                    o2.param_decimal().to_string().as_str(),
-                                    o2.param_string(),
-                                ));
-                                index += 1;
-                            }
-                            o2.get_element(orig_index);
-                            list.into_iter().map(JsValue::from).collect()
-                        }
-                    }
-                }
-            },
+        let rows = match self.amortization_rows(expanded) {
+            None => return String::from(""),
+            Some(o) => o,
+        };
+
+        let exchange_rates = self.exchange_rates.borrow();
+        let mut result = String::from("");
+
+        for (row_index, row) in rows.iter().enumerate() {
+            let rate =
+                match exchange_rates.rate(from_code.as_str(), to_code.as_str(), row.event_date()) {
+                    None => return String::from(""),
+                    Some(o) => o,
+                };
+            let converted = row.scaled(rate);
+
+            let delimiter = if row_index == 0 { "" } else { "," };
+            result = format!(
+                "{}{}{{\"Type\":\"{}\",\"Date\":\"{}\",\"Value\":\"{}\",\"Decrease\":\"{}\",\
+                \"Increase\":\"{}\",\"Interest\":\"{}\",\"Balance\":\"{}\",\"Exchange-rate\":\"{}\"}}",
+                result,
+                delimiter,
+                converted.event_type(),
+                DateUtility::format_date(converted.event_date()),
+                converted.value(),
+                converted.principal_decrease(),
+                converted.principal_increase(),
+                converted.interest(),
+                converted.balance(),
+                rate
+            );
         }
+
+        format!("[{}]", result)
     }
 
-    /// Parse and return an array of WasmSummary elements.
+    /// Evaluate the conditions of the conditional events of the
+    /// selected cashflow (see set_condition) and balance the cashflow.
     ///
     /// # Arguments
     ///
@@ -1999,351 +2792,494 @@ impl Engine {
     ///
     /// # Return
     ///
-    /// * See description.
+    /// * True if successful, otherwise false.
 
-    pub fn parse_summary(&self, cf_index: i32) -> Array {
+    pub fn evaluate_conditions(&self, cf_index: i32) -> bool {
         if cf_index < 0 {
-            return Array::new();
-        }
-
-        let calc_mgr = self.engine.calc_mgr();
-        if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
-            return Array::new();
+            return false;
         }
 
-        let mut ary_summary: Vec<WasmElemSummary> = Vec::new();
-        let list_summary = self.engine.parse_summary();
-        let mut index: usize = 0;
-
-        loop {
-            if !list_summary.get_element(index) {
-                break;
+        {
+            let calc_mgr = self.engine.calc_mgr();
+            if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
+                return false;
             }
-
-            ary_summary.push(WasmElemSummary::new(
-                list_summary.name(),
-                list_summary.label(),
-                list_summary.label_expr(),
-                list_summary.result(),
-                list_summary.result_expr(),
-            ));
-
-            index += 1;
         }
 
-        ary_summary.into_iter().map(JsValue::from).collect()
+        self.balance_events()
     }
 
-    /// Remove the indicated cashflow.
+    /// Evaluate an expression within the context of the selected
+    /// cashflow. The rate conversion functions (rateconvert,
+    /// rateeffective and ratenominal) are available.
     ///
     /// # Arguments
     ///
-    /// * `cf_index` - The cashflow index.
+    /// * `cf_index` - The cashflow index or -1 for user preferences.
+    /// * `expr` - Expression to evaluate.
     ///
     /// # Return
     ///
-    /// * True if successful.
+    /// * Returns the formatted result of the expression.
 
-    pub fn remove_cashflow(&self, cf_index: i32) -> bool {
-        if cf_index < 0 {
-            return false;
-        }
-
-        {
-            let calc_mgr = self.engine.calc_mgr();
+    pub fn evaluate_expression(&self, cf_index: i32, expr: &str) -> String {
+        let calc_mgr = self.engine.calc_mgr();
 
+        let mut list_parameter: Option<&ListParameter> = None;
+        if cf_index >= 0 {
             if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
-                return false;
+                return String::from("");
+            }
+
+            match calc_mgr.list_cashflow().preferences() {
+                None => {}
+                Some(o) => {
+                    list_parameter = Option::from(o.list_parameter());
+                }
             }
         }
 
-        self.engine.calc_mgr_mut().list_cashflow_mut().remove()
+        let expanded_expr = match self.expand_expression(list_parameter, expr) {
+            Err(e) => return e,
+            Ok(o) => o,
+        };
+
+        self.symbol_value(list_parameter, expanded_expr.as_str(), true)
     }
 
-    /// Remove the indicated event for the selected cashflow.
+    /// Generate the principal change events of an extra-payment strategy
+    /// into the selected cashflow and report the interest saved and the
+    /// term shortened versus the original cashflow.
     ///
     /// # Arguments
     ///
     /// * `cf_index` - The cashflow index.
-    /// * `index` - The event index.
+    /// * `index` - The event index of the regular payment.
+    /// * `strategy_param` - The strategy (fixed, annual, roundup or biweekly).
+    /// * `amount_param` - The extra amount, lump sum or round-up amount
+    ///   (not used by biweekly).
+    /// * `start_date_param` - The date the strategy starts (empty for
+    ///   the date of the regular payment).
     ///
     /// # Return
     ///
-    /// * True if successful.
+    /// * A json string with the strategy results or an empty string
+    ///   if not successful.
 
-    pub fn remove_event(&self, cf_index: i32, index: u32) -> bool {
-        let mut result: bool = false;
+    pub fn generate_extra_payments(
+        &self,
+        cf_index: i32,
+        index: u32,
+        strategy_param: &str,
+        amount_param: &str,
+        start_date_param: &str,
+    ) -> String {
         if cf_index < 0 {
-            return result;
+            return String::from("");
         }
 
+        let strategy = match StrategyUtility::parse_type(strategy_param) {
+            None => return String::from(""),
+            Some(o) => o,
+        };
+
+        let amount = if strategy == StrategyType::Biweekly {
+            dec!(0.0)
+        } else {
+            match amount_param.parse::<Decimal>() {
+                Err(_e) => return String::from(""),
+                Ok(o) => o,
+            }
+        };
+
+        let event_date: usize;
+        let sort_order: usize;
+        let payment: Decimal;
+        let periods: usize;
+        let intervals: usize;
+        let frequency: FrequencyType;
+        let extension: ElemExtension;
+
         {
             let calc_mgr = self.engine.calc_mgr();
 
             if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
-                return result;
+                return String::from("");
             }
 
             match calc_mgr.list_cashflow().list_event() {
-                None => {
-                    return result;
-                }
+                None => return String::from(""),
                 Some(o) => {
                     if !o.get_element(index as usize) {
-                        return result;
+                        return String::from("");
                     }
+                    event_date = o.event_date();
+                    sort_order = o.sort_order();
+                    payment = o.value();
+                    periods = o.periods();
+                    intervals = o.intervals();
+                    frequency = o.frequency();
+                    extension = o.elem_extension().clone();
                 }
             }
         }
 
-        match self
-            .engine
-            .calc_mgr_mut()
-            .list_cashflow_mut()
-            .list_event_mut()
-        {
-            None => return result,
-            Some(o) => {
-                result = o.remove();
+        let start_date = if start_date_param.is_empty() {
+            event_date
+        } else {
+            CoreUtility::parse_date(self.engine.format_date_in(start_date_param).as_str())
+        };
+
+        let mut elapsed: usize = 0;
+        if start_date > event_date {
+            elapsed = CoreUtility::date_diff(event_date, start_date, frequency, intervals, false)
+                .max(0) as usize;
+        }
+        if elapsed >= periods {
+            return String::from("");
+        }
+        let remaining = periods - elapsed;
+
+        let first_date = CoreUtility::date_newi(
+            event_date,
+            event_date,
+            frequency,
+            (elapsed * intervals) as i32,
+            false,
+        );
+        let next_date =
+            CoreUtility::date_newi(event_date, event_date, frequency, intervals as i32, false);
+        let periods_in_year = DateUtility::periods_in_year(event_date, next_date).unwrap_or(12);
+
+        let original = match self.amortization_rows(true) {
+            None => return String::from(""),
+            Some(o) => ElemPayoffMetrics::new(&o),
+        };
+
+        let mut events: usize = 0;
+        match strategy {
+            StrategyType::Biweekly => {
+                let biweekly_sort_order = if elapsed == 0 {
+                    ClipboardUtility::free_sort_order(
+                        &self.event_sort_orders(),
+                        first_date,
+                        sort_order,
+                    )
+                } else {
+                    sort_order
+                };
+
+                if !self.add_event(
+                    first_date,
+                    biweekly_sort_order,
+                    self.round_currency(payment / dec!(2.0)),
+                    StrategyUtility::biweekly_periods(remaining, periods_in_year as usize),
+                    1,
+                    CoreUtility::get_frequency("2-weeks"),
+                    extension,
+                ) {
+                    return String::from("");
+                }
+                events += 1;
+
+                let orig_index = match self.event_index_by_date(event_date, sort_order) {
+                    None => return String::from(""),
+                    Some(o) => o,
+                };
+
+                if elapsed == 0 {
+                    let result = match self
+                        .engine
+                        .calc_mgr_mut()
+                        .list_cashflow_mut()
+                        .list_event_mut()
+                    {
+                        None => false,
+                        Some(o) => o.get_element(orig_index) && o.remove(),
+                    };
+                    if !result {
+                        return String::from("");
+                    }
+                } else if !self.set_event_column(
+                    orig_index,
+                    "Periods",
+                    self.engine.format_integer_out(elapsed).as_str(),
+                ) {
+                    return String::from("");
+                }
+            }
+            _ => {
+                // The extra payments have the payment event's principal
+                // type (e.g., principal increases for the loan templates).
+                if extension.extension_type() != ExtensionType::PrincipalChange {
+                    return String::from("");
+                }
+                let extra_extension = extension.clone();
+
+                let (extra, extra_periods, extra_intervals, extra_frequency) = match strategy {
+                    StrategyType::Annual => (
+                        amount,
+                        StrategyUtility::annual_periods(remaining, periods_in_year as usize),
+                        1,
+                        CoreUtility::get_frequency("1-year"),
+                    ),
+                    StrategyType::RoundUp => (
+                        StrategyUtility::round_up_extra(payment, amount),
+                        remaining,
+                        intervals,
+                        frequency,
+                    ),
+                    _ => (amount, remaining, intervals, frequency),
+                };
+
+                if extra > dec!(0.0)
+                    && self.add_event(
+                        first_date,
+                        sort_order + 1,
+                        extra,
+                        extra_periods,
+                        extra_intervals,
+                        extra_frequency,
+                        extra_extension,
+                    )
+                {
+                    events += 1;
+                }
             }
         }
 
-        match self.engine.balance_cashflow() {
-            Err(_e) => {}
-            Ok(_o) => {}
+        self.engine.evaluate_cashflow_event_type_all();
+
+        if !self.balance_events() {
+            return String::from("");
         }
 
-        result
+        let current = match self.amortization_rows(true) {
+            None => return String::from(""),
+            Some(o) => ElemPayoffMetrics::new(&o),
+        };
+
+        format!(
+            "{{\"events\":\"{}\",\"original-interest\":\"{}\",\"interest\":\"{}\",\
+            \"interest-saved\":\"{}\",\"original-payoff-date\":\"{}\",\"payoff-date\":\"{}\",\
+            \"original-payments\":\"{}\",\"payments\":\"{}\",\"payments-shortened\":\"{}\"}}",
+            events,
+            original.total_interest(),
+            current.total_interest(),
+            original.total_interest() - current.total_interest(),
+            DateUtility::format_date(original.payoff_date()),
+            DateUtility::format_date(current.payoff_date()),
+            original.payments(),
+            current.payments(),
+            original.payments() as i64 - current.payments() as i64
+        )
     }
 
-    /// Serialize and return the selected cashflow.
+    /// Get the actual payments recorded against the selected cashflow.
     ///
     /// # Arguments
     ///
     /// * `cf_index` - The cashflow index.
-    /// * `options` - Serialization options.
     ///
     /// # Return
     ///
-    /// * Returns serialized cashflow.
+    /// * A json array of the ledger of payments ("Date", "Amount" and
+    ///   "Memo") sorted by date or an empty string if not successful.
 
-    pub fn serialize(&self, cf_index: i32, options: u32) -> String {
+    pub fn get_actual_payments(&self, cf_index: i32) -> String {
         if cf_index < 0 {
             return String::from("");
         }
 
-        if !self
-            .engine
-            .calc_mgr()
-            .list_cashflow()
-            .get_element(cf_index as usize)
         {
-            return String::from("");
+            let calc_mgr = self.engine.calc_mgr();
+            if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
+                return String::from("");
+            }
         }
 
-        let json = CalcJsonSerialize::new(self.engine.calc_manager());
+        let mut result = String::from("");
+        for (payment_index, payment) in self.cashflow_actual_payments().iter().enumerate() {
+            let delimiter = if payment_index == 0 { "" } else { "," };
+            result = format!(
+                "{}{}{{\"Date\":\"{}\",\"Amount\":\"{}\",\"Memo\":\"{}\"}}",
+                result,
+                delimiter,
+                DateUtility::format_date(payment.event_date()),
+                payment.amount(),
+                JsonUtility::escape(payment.memo())
+            );
+        }
 
-        json.serialize(options as usize)
+        format!("[{}]", result)
     }
 
-    /// Set the appropriate event list value and
-    /// return it as a string.
+    /// Get the status string for the selected cashflow
+    /// as status expression.
     ///
     /// # Arguments
     ///
-    /// * `col_name_index_param` - Column name index.
-    /// * `type_param` - Column type.
-    /// * `code_param` - Column code.
-    /// * `cf_index_param` - The cashflow index.
-    /// * `index_param` - Event row index.
-    /// * `value_param` - Value to set as a string.
+    /// * `cf_index` - The cashflow index.
+    /// * `status` - Status expression to evaluate.
     ///
     /// # Return
     ///
-    /// * See description.
+    /// * Returns the cashflow status string.
 
-    pub fn set_event_value(
-        &self,
-        col_name_index_param: u32,
-        type_param: &str,
-        code_param: &str,
-        cf_index_param: i32,
-        index_param: u32,
-        value_param: &str,
-    ) -> String {
-        if cf_index_param < 0 {
+    pub fn get_cashflow_status(&self, cf_index: i32, status: &str) -> String {
+        let calc_mgr = self.engine.calc_mgr();
+
+        if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
             return String::from("");
         }
 
-        {
-            let calc_mgr = self.engine.calc_mgr();
-
-            if !calc_mgr
-                .list_cashflow()
-                .get_element(cf_index_param as usize)
-            {
-                return String::from("");
-            }
-
-            match calc_mgr.list_cashflow().list_event() {
-                None => {
-                    return String::from("");
-                }
-                Some(o) => {
-                    o.get_element(index_param as usize);
-                }
-            }
-        }
-
-        let mut event_date = String::from("");
-        let mut sort_order: usize = 0;
-
-        {
-            let calc_mgr = self.engine.calc_mgr();
-            let list_locale = calc_mgr.list_locale();
-
-            match calc_mgr.list_cashflow().list_event() {
-                None => {}
-                Some(o) => {
-                    event_date = list_locale.format_date_out(o.event_date());
-                    sort_order = o.sort_order();
-                }
+        let mut list_parameter: Option<&ListParameter> = None;
+        match calc_mgr.list_cashflow().preferences() {
+            None => {}
+            Some(o) => {
+                list_parameter = Option::from(o.list_parameter());
             }
         }
 
-        let result = self.engine.set_event_value(
-            col_name_index_param as usize,
-            type_param,
-            code_param,
-            index_param as usize,
-            value_param,
-        );
+        let expanded_status = match self.expand_expression(list_parameter, status) {
+            Err(e) => return e,
+            Ok(o) => o,
+        };
 
-        match self.engine.balance_cashflow() {
-            Err(_e) => String::from(""),
-            Ok(_o) => format!("{}|{}|{}", event_date, sort_order, result),
-        }
+        self.symbol_value(list_parameter, expanded_status.as_str(), true)
     }
 
-    /// Set the appropriate event list extension values.
+    /// Get the chart definitions for the selected cashflow.
     ///
     /// # Arguments
     ///
-    /// * `cf_index_param` - The cashflow index.
-    /// * `index_param` - Event row index.
-    /// * `ext_param` - Extension values to set.
+    /// * `cf_index` - The cashflow index.
     ///
     /// # Return
     ///
-    /// * True if successful, otherwise false.
+    /// * Returns an array of chart definition elements.
 
-    pub fn set_extension_values(
-        &self,
-        cf_index_param: i32,
-        index_param: u32,
-        ext_param: &str,
-    ) -> String {
-        if cf_index_param < 0 {
-            return String::from("");
+    pub fn get_chart_definitions(&self, cf_index: i32) -> Array {
+        let calc_mgr = self.engine.calc_mgr();
+
+        if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
+            return Array::new();
         }
 
-        {
-            let calc_mgr = self.engine.calc_mgr();
+        let mut ary_chart: Vec<WasmElemChart> = Vec::new();
+        let list_descriptor = calc_mgr.preferences().list_descriptor();
+        let mut index: usize = 0;
 
-            if !calc_mgr
-                .list_cashflow()
-                .get_element(cf_index_param as usize)
-            {
-                return String::from("");
+        loop {
+            if !list_descriptor.get_element(index) {
+                break;
             }
-        }
-
-        let ext: ElemExtension;
 
-        {
-            let json = CalcJsonDeserialize::new(self.engine.calc_manager());
-            match json.deserialize_extension_from_str(ext_param) {
-                Err(_e) => {
-                    return String::from("");
-                }
-                Ok(o) => {
-                    ext = o;
-                }
+            if list_descriptor.group() == "Chart" && list_descriptor.desc_type() == "custom" {
+                ary_chart.push(WasmElemChart::new(
+                    list_descriptor.name(),
+                    list_descriptor.value().as_str(),
+                ));
             }
-        }
 
-        if !self.engine.set_extension_values(index_param as usize, &ext) {
-            return String::from("");
+            index += 1;
         }
 
-        self.engine.evaluate_cashflow_event_type_all();
-
-        let mut result = String::from("");
+        match calc_mgr.list_cashflow().preferences() {
+            None => {}
+            Some(o) => {
+                let list_descriptor = o.list_descriptor();
+                let mut index: usize = 0;
 
-        {
-            let calc_mgr = self.engine.calc_mgr();
-            let list_cashflow = calc_mgr.list_cashflow();
-            let list_event_opt = list_cashflow.list_event();
+                loop {
+                    if !list_descriptor.get_element(index) {
+                        break;
+                    }
 
-            match list_event_opt {
-                None => {}
-                Some(o) => {
-                    let orig_index = o.index();
-                    if o.get_element(index_param as usize) {
-                        result = String::from(o.event_type());
-                        o.get_element(orig_index);
+                    if list_descriptor.group() == "Chart" && list_descriptor.desc_type() == "custom"
+                    {
+                        ary_chart.push(WasmElemChart::new(
+                            list_descriptor.name(),
+                            list_descriptor.value().as_str(),
+                        ));
                     }
+
+                    index += 1;
                 }
             }
         }
 
-        match self.engine.balance_cashflow() {
-            Err(_e) => String::from(""),
-            Ok(_o) => result,
-        }
+        ary_chart.into_iter().map(JsValue::from).collect()
     }
 
-    /// Set the appropriate event list parameter values.
+    /// Get the condition of an event.
     ///
     /// # Arguments
     ///
     /// * `cf_index` - The cashflow index.
-    /// * `index_param` - Event row index.
-    /// * `parameters` - Parameters to set.
+    /// * `index` - The event index.
     ///
     /// # Return
     ///
-    /// * True if successful, otherwise false.
+    /// * The condition descriptor value (e.g., "terminate~decBalance < 10000")
+    ///   or an empty string if the event is not conditional.
 
-    pub fn set_parameter_values(&self, cf_index: i32, index_param: u32, parameters: &str) -> bool {
+    pub fn get_condition(&self, cf_index: i32, index: u32) -> String {
         if cf_index < 0 {
-            return false;
+            return String::from("");
         }
 
         {
             let calc_mgr = self.engine.calc_mgr();
-
             if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
-                return false;
+                return String::from("");
             }
         }
 
-        let mut values: Vec<String> = Vec::new();
-        for param in parameters.split('|') {
-            values.push(String::from(param));
+        match ElemCondition::parse(
+            self.event_descriptor(index as usize, GROUP_CONDITION, NAME_CONDITION_RULE)
+                .as_str(),
+        ) {
+            None => String::from(""),
+            Some(o) => o.descriptor_value(),
         }
+    }
 
-        if !self
-            .engine
-            .set_parameter_values(index_param as usize, values)
-        {
-            return false;
+    /// Get the event by date and sort order.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    /// * `date_param` - The date to select.
+    /// * `sort_param` - The sort order to select.
+    ///
+    /// # Return
+    ///
+    /// * The event index or -1.
+
+    pub fn get_event_by_date(&self, cf_index: i32, date_param: &str, sort_param: u32) -> u32 {
+        let calc_mgr = self.engine.calc_mgr();
+
+        if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
+            return 0;
         }
 
-        true
+        let date_in = CoreUtility::parse_date(self.engine.format_date_in(date_param).as_str());
+
+        match calc_mgr.list_cashflow().list_event() {
+            None => 0,
+            Some(o) => {
+                if !o.get_element_by_date(date_in, sort_param as usize) {
+                    return 0;
+                }
+                o.index() as u32
+            }
+        }
     }
 
-    /// Set selected user or cashflow preferences.
+    /// Get selected user or cashflow preferences. The decimal
+    /// digits of a cashflow are the digits it is rounded to (see
+    /// get_decimal_digits).
     ///
     /// # Arguments
     ///
@@ -2353,212 +3289,6011 @@ impl Engine {
     ///
     /// * See description.
 
-    pub fn set_preferences(&self, cf_index: i32, prefs: &WasmElemPreferences) -> bool {
-        if cf_index >= 0 {
-            let calc_mgr = self.engine.calc_mgr();
-            calc_mgr.list_cashflow().get_element(cf_index as usize);
+    pub fn get_preferences(&self, cf_index: i32) -> WasmElemPreferences {
+        let mut decimal_digits: Option<u32> = None;
+        if cf_index >= 0
+            && self
+                .engine
+                .calc_mgr()
+                .list_cashflow()
+                .get_element(cf_index as usize)
+            && self
+                .engine
+                .calc_mgr()
+                .list_cashflow()
+                .preferences()
+                .is_some()
+        {
+            decimal_digits = Some(self.cashflow_decimal_digits());
         }
 
-        let mut calc_mgr = self.engine.calc_mgr_mut();
-        let elem_prefs: &mut ElemPreferences;
+        let calc_mgr = self.engine.calc_mgr();
+        let prefs: &ElemPreferences;
 
         if cf_index >= 0 {
-            match calc_mgr.list_cashflow_mut().preferences_mut() {
+            calc_mgr.list_cashflow().get_element(cf_index as usize);
+
+            match calc_mgr.list_cashflow().preferences() {
                 None => {
-                    elem_prefs = calc_mgr.preferences_mut();
+                    prefs = calc_mgr.preferences();
                 }
                 Some(o) => {
-                    elem_prefs = o;
+                    prefs = o;
                 }
             }
         } else {
-            elem_prefs = calc_mgr.preferences_mut();
+            prefs = calc_mgr.preferences();
         }
 
-        elem_prefs.set_cross_rate_code(prefs.cross_rate_code().as_str());
-        elem_prefs.set_default_encoding(prefs.default_encoding().as_str());
-        elem_prefs.set_fiscal_year_start(prefs.fiscal_year_start() as usize);
-        elem_prefs.set_decimal_digits(prefs.decimal_digits() as usize);
-        elem_prefs.set_target(CoreUtility::parse_decimal(prefs.target().as_str()));
-
-        true
+        WasmElemPreferences::new(
+            prefs.locale_str(),
+            prefs.group(),
+            prefs.cross_rate_code(),
+            prefs.default_encoding(),
+            prefs.fiscal_year_start() as u32,
+            decimal_digits.unwrap_or(prefs.decimal_digits() as u32),
+            prefs.target().to_string().as_str(),
+        )
     }
 
-    /// Parse and return the cashflow's event values for the table type.
+    /// Get the construction loan conversion date of the selected cashflow.
     ///
     /// # Arguments
     ///
     /// * `cf_index` - The cashflow index.
-    /// * `table_type_param` - The type of table (event or amortization).
     ///
     /// # Return
     ///
-    /// * Return a string that can be directly loaded into ag-grid.
+    /// * The conversion date or an empty string if not set
+    ///   (the date before the first payment).
 
-    pub fn table_values(&self, cf_index: i32, table_type_param: u32) -> String {
+    pub fn get_conversion_date(&self, cf_index: i32) -> String {
         if cf_index < 0 {
             return String::from("");
         }
 
-        let calc_mgr = self.engine.calc_mgr();
+        {
+            let calc_mgr = self.engine.calc_mgr();
+            if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
+                return String::from("");
+            }
+        }
 
-        if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
-            return String::from("");
+        match self.cashflow_conversion_date() {
+            0 => String::from(""),
+            o => self.engine.format_date_out(o),
         }
+    }
 
-        let table_type: TableType = match table_type_param {
-            TABLE_AM => TableType::Amortization,
-            _ => TableType::Event,
-        };
+    /// Get the number of currency decimal digits of the selected
+    /// cashflow. Unless set in the cashflow preferences, the number
+    /// is derived from the currency code of the cashflow's locale.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    ///
+    /// # Return
+    ///
+    /// * See description or -1 if not successful.
 
-        let list_column = self.engine.parse_columns(table_type);
+    pub fn get_decimal_digits(&self, cf_index: i32) -> i32 {
+        if cf_index < 0 {
+            return -1;
+        }
 
-        let json = CalcJsonSerialize::new(self.engine.calc_manager());
+        {
+            let calc_mgr = self.engine.calc_mgr();
+            if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
+                return -1;
+            }
+        }
 
-        match table_type_param {
-            TABLE_AM => {
-                let mut cresult = String::from("");
-                let mut list_am: ListAmortization = match calc_mgr
-                    .list_cashflow()
-                    .create_cashflow_output(true, false, false, false, true)
-                {
-                    Err(_e) => return cresult,
-                    Ok(o) => o,
+        self.cashflow_decimal_digits() as i32
+    }
+
+    /// Get the grace period and late-fee rules of the selected cashflow.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    ///
+    /// # Return
+    ///
+    /// * The rules descriptor value (e.g., "15~percent~5~50") or
+    ///   an empty string if not successful.
+
+    pub fn get_delinquency_rules(&self, cf_index: i32) -> String {
+        if cf_index < 0 {
+            return String::from("");
+        }
+
+        {
+            let calc_mgr = self.engine.calc_mgr();
+            if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
+                return String::from("");
+            }
+        }
+
+        self.cashflow_delinquency_rules().descriptor_value()
+    }
+
+    /// Get the depreciation method of an event.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    /// * `index` - The event index.
+    ///
+    /// # Return
+    ///
+    /// * The method descriptor value (e.g., "SL~5~1000~FM") or an
+    ///   empty string if the event is not depreciated.
+
+    pub fn get_depreciation(&self, cf_index: i32, index: u32) -> String {
+        if cf_index < 0 {
+            return String::from("");
+        }
+
+        {
+            let calc_mgr = self.engine.calc_mgr();
+            if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
+                return String::from("");
+            }
+        }
+
+        match ElemDepreciation::parse(
+            self.event_descriptor(index as usize, GROUP_DEPRECIATION, NAME_DEPRECIATION_METHOD)
+                .as_str(),
+        ) {
+            None => String::from(""),
+            Some(o) => o.descriptor_value(),
+        }
+    }
+
+    /// Get the growth terms of an event.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    /// * `index` - The event index.
+    ///
+    /// # Return
+    ///
+    /// * The terms descriptor value (e.g., "percent~7.5~12") or an
+    ///   empty string if the event payments do not grow.
+
+    pub fn get_growth(&self, cf_index: i32, index: u32) -> String {
+        if cf_index < 0 {
+            return String::from("");
+        }
+
+        {
+            let calc_mgr = self.engine.calc_mgr();
+            if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
+                return String::from("");
+            }
+        }
+
+        match ElemGrowth::parse(
+            self.event_descriptor(index as usize, GROUP_GROWTH, NAME_GROWTH_TERMS)
+                .as_str(),
+        ) {
+            None => String::from(""),
+            Some(o) => o.descriptor_value(),
+        }
+    }
+
+    /// Get the interest allocation method of the selected cashflow.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    ///
+    /// # Return
+    ///
+    /// * The method (actuarial or rule-of-78s) or an
+    ///   empty string if not successful.
+
+    pub fn get_interest_allocation(&self, cf_index: i32) -> String {
+        if cf_index < 0 {
+            return String::from("");
+        }
+
+        {
+            let calc_mgr = self.engine.calc_mgr();
+            if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
+                return String::from("");
+            }
+        }
+
+        String::from(AllocationUtility::method_mnemonic(
+            self.cashflow_allocation_method(),
+        ))
+    }
+
+    /// Get the prepayment model of the selected cashflow.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    ///
+    /// # Return
+    ///
+    /// * The model as "type~speed" (e.g., "PSA~150") or an
+    ///   empty string if prepayments are not modeled.
+
+    pub fn get_prepayment_model(&self, cf_index: i32) -> String {
+        if cf_index < 0 {
+            return String::from("");
+        }
+
+        {
+            let calc_mgr = self.engine.calc_mgr();
+            if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
+                return String::from("");
+            }
+        }
+
+        ElemPrepaymentModel::parse(
+            self.cashflow_descriptor(GROUP_PREPAYMENT, NAME_PREPAYMENT_MODEL)
+                .as_str(),
+        )
+        .descriptor_value()
+    }
+
+    /// Get the relative date rule of an event.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    /// * `index` - The event index.
+    ///
+    /// # Return
+    ///
+    /// * The rule descriptor value (e.g., "Loan~1~1-month") or an
+    ///   empty string if the event date is not relative.
+
+    pub fn get_relative_date(&self, cf_index: i32, index: u32) -> String {
+        if cf_index < 0 {
+            return String::from("");
+        }
+
+        {
+            let calc_mgr = self.engine.calc_mgr();
+            if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
+                return String::from("");
+            }
+        }
+
+        match ElemRelativeDate::parse(
+            self.event_descriptor(index as usize, GROUP_RELATIVE_DATE, NAME_RELATIVE_DATE_RULE)
+                .as_str(),
+        ) {
+            Err(_e) => String::from(""),
+            Ok(o) => o.descriptor_value(),
+        }
+    }
+
+    /// Get the rounding residue (final balance) of the selected cashflow.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    ///
+    /// # Return
+    ///
+    /// * See description or an empty string if not successful.
+
+    pub fn get_residue(&self, cf_index: i32) -> String {
+        if cf_index < 0 {
+            return String::from("");
+        }
+
+        if !self
+            .engine
+            .calc_mgr()
+            .list_cashflow()
+            .get_element(cf_index as usize)
+        {
+            return String::from("");
+        }
+
+        match self.final_balance() {
+            None => String::from(""),
+            Some(o) => o.to_string(),
+        }
+    }
+
+    /// Get the rounding residue policy of the selected cashflow.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    ///
+    /// # Return
+    ///
+    /// * The policy (carry, last, first or spread) or an
+    ///   empty string if not successful.
+
+    pub fn get_residue_policy(&self, cf_index: i32) -> String {
+        if cf_index < 0 {
+            return String::from("");
+        }
+
+        {
+            let calc_mgr = self.engine.calc_mgr();
+            if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
+                return String::from("");
+            }
+        }
+
+        String::from(ResidueUtility::policy_mnemonic(
+            self.cashflow_residue_policy(),
+        ))
+    }
+
+    /// Get the revolving line of credit terms of the selected cashflow.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    ///
+    /// # Return
+    ///
+    /// * The terms descriptor value (e.g., "50000~2032-01-01~interest-only")
+    ///   or an empty string if not set.
+
+    pub fn get_revolving_terms(&self, cf_index: i32) -> String {
+        if cf_index < 0 {
+            return String::from("");
+        }
+
+        {
+            let calc_mgr = self.engine.calc_mgr();
+            if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
+                return String::from("");
+            }
+        }
+
+        match self.cashflow_revolving_terms() {
+            None => String::from(""),
+            Some(o) => o.descriptor_value(),
+        }
+    }
+
+    /// Get the rounding mode of the selected cashflow.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    ///
+    /// # Return
+    ///
+    /// * The rounding mode (none, bias-up, bias-down, up,
+    ///   truncate or bankers) or an empty string if not successful.
+
+    pub fn get_rounding_mode(&self, cf_index: i32) -> String {
+        if cf_index < 0 {
+            return String::from("");
+        }
+
+        {
+            let calc_mgr = self.engine.calc_mgr();
+            if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
+                return String::from("");
+            }
+        }
+
+        String::from(CurrencyUtility::rounding_mode_mnemonic(
+            self.cashflow_rounding_mode(),
+        ))
+    }
+
+    /// Get a specific resource.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    /// * `key` - The resource key.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn get_resource(&self, cf_index: i32, key: &str) -> String {
+        let calc_mgr = self.engine.calc_mgr();
+
+        if cf_index >= 0 {
+            calc_mgr.list_cashflow().get_element(cf_index as usize);
+        }
+
+        let locale = calc_mgr.list_locale();
+        String::from(locale.get_resource(key))
+    }
+
+    /// Get the recurrence schedule of an event.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    /// * `index` - The event index.
+    ///
+    /// # Return
+    ///
+    /// * The recurrence rule (e.g., "DTSTART=20260115;FREQ=MONTHLY;
+    ///   BYMONTHDAY=15,-1;COUNT=24") or an empty string if the
+    ///   event has no recurrence schedule.
+
+    pub fn get_schedule(&self, cf_index: i32, index: u32) -> String {
+        if cf_index < 0 {
+            return String::from("");
+        }
+
+        {
+            let calc_mgr = self.engine.calc_mgr();
+            if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
+                return String::from("");
+            }
+        }
+
+        match ElemRecurrence::parse(
+            self.event_descriptor(index as usize, GROUP_SCHEDULE, NAME_SCHEDULE_RULE)
+                .as_str(),
+        ) {
+            None => String::from(""),
+            Some(o) => o.descriptor_value(),
+        }
+    }
+
+    /// Get the loaded template names.
+    ///
+    /// # Return
+    ///
+    /// * Returns an array of template group names.
+
+    pub fn get_template_names(&self) -> Array {
+        let calc_mgr = self.engine.calc_mgr();
+        let list_template_group = calc_mgr.list_template_group();
+        let mut ary_template_groups: Vec<String> = Vec::new();
+
+        let orig_index = list_template_group.index();
+        let mut index: usize = 0;
+        loop {
+            if !list_template_group.get_element(index) {
+                break;
+            }
+            ary_template_groups.push(String::from(list_template_group.group()));
+            index += 1;
+        }
+        list_template_group.get_element(orig_index);
+
+        ary_template_groups.into_iter().map(JsValue::from).collect()
+    }
+
+    /// Get the loaded template event names for a template group.
+    ///
+    /// # Arguments
+    ///
+    /// * `group_param` - The template group name.
+    ///
+    /// # Return
+    ///
+    /// * Returns an array of template event names.
+
+    pub fn get_template_event_names(&self, group_param: &str) -> Array {
+        let calc_mgr = self.engine.calc_mgr();
+        let list_template_group = calc_mgr.list_template_group();
+
+        let mut ary_template_events: Vec<String> = Vec::new();
+
+        if !list_template_group.get_element_by_group(group_param, true) {
+            return ary_template_events.into_iter().map(JsValue::from).collect();
+        }
+
+        let list_template_event = list_template_group.list_template_event();
+
+        let orig_index = list_template_event.index();
+        let mut index: usize = 0;
+        loop {
+            if !list_template_event.get_element(index) {
+                break;
+            }
+            ary_template_events.push(String::from(list_template_event.name()));
+            index += 1;
+        }
+        list_template_event.get_element(orig_index);
+
+        ary_template_events.into_iter().map(JsValue::from).collect()
+    }
+
+    /// Initialize the selected cashflow.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    ///
+    /// # Return
+    ///
+    /// * Returns the cashflow's name.
+
+    pub fn init_cashflow(&self, cf_index: i32) -> String {
+        if cf_index < 0 {
+            return String::from("");
+        }
+
+        if !self.engine.init_cashflow(cf_index as u32) {
+            return String::from("");
+        }
+
+        let calc_mgr = self.engine.calc_mgr();
+
+        let locale_str: &str;
+        let group: &str;
+        match calc_mgr.list_cashflow().preferences() {
+            None => {
+                locale_str = calc_mgr.preferences().locale_str();
+                group = "";
+            }
+            Some(o) => {
+                if o.locale_str().is_empty() {
+                    locale_str = calc_mgr.preferences().locale_str();
+                } else {
+                    locale_str = o.locale_str();
+                }
+                group = o.group();
+            }
+        }
+
+        format!(
+            "{}|{}|{}",
+            calc_mgr.list_cashflow().name(),
+            locale_str,
+            group
+        )
+    }
+
+    /// Initialize and return the selected cashflow's
+    /// status expression.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn init_cashflow_status(&self, cf_index: i32) -> String {
+        if cf_index < 0 {
+            return String::from("");
+        }
+
+        let calc_mgr = self.engine.calc_mgr();
+        if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
+            return String::from("");
+        }
+
+        let locale = calc_mgr.list_locale();
+        let cashflow_locale_str = locale.cashflow_locale().locale_str();
+
+        let mut status = self.engine.calc_mgr().descriptor_value(
+            amfnengine::GROUP_GENERAL,
+            amfnengine::NAME_STATUS,
+            amfnengine::TYPE_LOCALE,
+            cashflow_locale_str,
+            true,
+            false,
+        );
+        if status.is_empty() {
+            status = self.engine.calc_mgr().descriptor_value(
+                amfnengine::GROUP_GENERAL,
+                amfnengine::NAME_STATUS,
+                "",
+                "",
+                true,
+                false,
+            );
+            if status.is_empty() {
+                status = String::from(locale.get_resource(amfnengine::USER_STATUS));
+            }
+        }
+
+        status
+    }
+
+    /// Insert an event into the selected cashflow.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    /// * `extension` - The event type extension json string (as for
+    ///   set_extension_values, e.g., {"principal-change":{...}}).
+    /// * `date_param` - The event date.
+    /// * `sort_param` - The sort order (the next unused sort order
+    ///   is used if the date and sort order are taken).
+    /// * `values` - The initial values (column~value pairs separated
+    ///   by '|' in display format, e.g., "Value~1,000.00|Periods~12|
+    ///   Frequency~1-month"; the Date and Sort columns are not allowed).
+    ///
+    /// # Return
+    ///
+    /// * The index of the new event or -1 if not successful.
+
+    pub fn insert_event(
+        &self,
+        cf_index: i32,
+        extension: &str,
+        date_param: &str,
+        sort_param: u32,
+        values: &str,
+    ) -> i32 {
+        if cf_index < 0 {
+            return -1;
+        }
+
+        {
+            let calc_mgr = self.engine.calc_mgr();
+            if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
+                return -1;
+            }
+        }
+
+        let ext = {
+            let json = CalcJsonDeserialize::new(self.engine.calc_manager());
+            match json.deserialize_extension_from_str(extension) {
+                Err(_e) => return -1,
+                Ok(o) => o,
+            }
+        };
+
+        let mut column_values: Vec<(&str, &str)> = Vec::new();
+        for token in values.split('|').filter(|o| !o.trim().is_empty()) {
+            let mut tokens = token.splitn(2, '~');
+            let col_name = tokens.next().unwrap_or("").trim();
+            let value = match tokens.next() {
+                None => return -1,
+                Some(o) => o.trim(),
+            };
+            if col_name == "Date" || col_name == "Sort" {
+                return -1;
+            }
+            column_values.push((col_name, value));
+        }
+
+        let event_date = CoreUtility::parse_date(self.engine.format_date_in(date_param).as_str());
+        let sort_order = ClipboardUtility::free_sort_order(
+            &self.event_sort_orders(),
+            event_date,
+            sort_param as usize,
+        );
+
+        if !self.add_event(
+            event_date,
+            sort_order,
+            dec!(0.0),
+            1,
+            1,
+            CoreUtility::get_frequency("1-month"),
+            ext,
+        ) {
+            return -1;
+        }
+
+        for (col_name, value) in column_values.iter() {
+            let index = match self.event_index_by_date(event_date, sort_order) {
+                None => return -1,
+                Some(o) => o,
+            };
+            if !self.set_event_column(index, col_name, value) {
+                return -1;
+            }
+        }
+
+        self.engine.evaluate_cashflow_event_type_all();
+
+        if !self.balance_events() {
+            return -1;
+        }
+
+        self.event_index_by_date(event_date, sort_order)
+            .map_or(-1, |o| o as i32)
+    }
+
+    /// Create the lease accounting (ASC 842 / IFRS 16) schedule of the
+    /// selected cashflow. The payment events are the lease payments,
+    /// the cashflow's starting date is the commencement date and the
+    /// discount rate is compounded at the payment frequency.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    /// * `lease_type` - The lease type (finance or operating).
+    /// * `discount_rate` - The annual discount rate (percentage).
+    /// * `adjustments` - Initial direct costs and prepaid lease payments
+    ///   less lease incentives received (empty for none).
+    ///
+    /// # Return
+    ///
+    /// * A json string with the "lease-type", "discount-rate", initial
+    ///   "liability", initial "right-of-use-asset", "total-payments",
+    ///   "total-interest", "total-expense" and the schedule "rows"
+    ///   or an empty string if not successful.
+
+    pub fn lease_values(
+        &self,
+        cf_index: i32,
+        lease_type: &str,
+        discount_rate: &str,
+        adjustments: &str,
+    ) -> String {
+        if cf_index < 0 {
+            return String::from("");
+        }
+
+        if !self
+            .engine
+            .calc_mgr()
+            .list_cashflow()
+            .get_element(cf_index as usize)
+        {
+            return String::from("");
+        }
+
+        let lease_type = match LeaseUtility::parse_type(lease_type) {
+            None => return String::from(""),
+            Some(o) => o,
+        };
+
+        let rate = match self.display_number(discount_rate) {
+            None => return String::from(""),
+            Some(o) => o,
+        };
+
+        let adjustments = if adjustments.trim().is_empty() {
+            dec!(0.0)
+        } else {
+            match self.display_number(adjustments) {
+                None => return String::from(""),
+                Some(o) => o,
+            }
+        };
+
+        let rows = match self.amortization_rows(true) {
+            None => return String::from(""),
+            Some(o) => o,
+        };
+
+        let commencement = match rows.first() {
+            None => return String::from(""),
+            Some(o) => o.event_date(),
+        };
+
+        let (liability, asset, schedule) = match LeaseUtility::schedule(
+            &ElemLeasePayment::from_rows(&rows),
+            commencement,
+            lease_type,
+            rate,
+            adjustments,
+            self.cashflow_decimal_digits(),
+        ) {
+            None => return String::from(""),
+            Some(o) => o,
+        };
+
+        let mut lease_rows = String::from("");
+        for (row_index, row) in schedule.iter().enumerate() {
+            let delimiter = if row_index == 0 { "" } else { "," };
+            lease_rows = format!(
+                "{}{}{{\"Date\":\"{}\",\"Payment\":\"{}\",\"Interest\":\"{}\",\
+                \"Liability-reduction\":\"{}\",\"Liability\":\"{}\",\"Amortization\":\"{}\",\
+                \"Right-of-use-asset\":\"{}\",\"Expense\":\"{}\"}}",
+                lease_rows,
+                delimiter,
+                DateUtility::format_date(row.event_date()),
+                row.payment(),
+                row.interest(),
+                row.liability_reduction(),
+                row.liability(),
+                row.amortization(),
+                row.asset(),
+                row.expense()
+            );
+        }
+
+        format!(
+            "{{\"lease-type\":\"{}\",\"discount-rate\":\"{}\",\"liability\":\"{}\",\
+            \"right-of-use-asset\":\"{}\",\"total-payments\":\"{}\",\"total-interest\":\"{}\",\
+            \"total-expense\":\"{}\",\"rows\":[{}]}}",
+            LeaseUtility::type_mnemonic(lease_type),
+            rate,
+            liability,
+            asset,
+            schedule.iter().map(|o| o.payment()).sum::<Decimal>(),
+            schedule.iter().map(|o| o.interest()).sum::<Decimal>(),
+            schedule.iter().map(|o| o.expense()).sum::<Decimal>(),
+            lease_rows
+        )
+    }
+
+    /// Allocate the finance charge of the selected cashflow to its
+    /// payments using the cashflow's interest allocation method.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    ///
+    /// # Return
+    ///
+    /// * A json string with the allocation "method" and the payment
+    ///   "rows" or an empty string if not successful.
+
+    pub fn interest_allocation_values(&self, cf_index: i32) -> String {
+        if cf_index < 0 {
+            return String::from("");
+        }
+
+        if !self
+            .engine
+            .calc_mgr()
+            .list_cashflow()
+            .get_element(cf_index as usize)
+        {
+            return String::from("");
+        }
+
+        let flows = match self.amortization_rows(true) {
+            None => return String::from(""),
+            Some(o) => ElemCashFlow::from_rows(&o),
+        };
+
+        let method = self.cashflow_allocation_method();
+        let mut rows = String::from("");
+        for (row_index, row) in
+            AllocationUtility::schedule(&flows, method, self.cashflow_decimal_digits())
+                .iter()
+                .enumerate()
+        {
+            let delimiter = if row_index == 0 { "" } else { "," };
+            rows = format!(
+                "{}{}{{\"Date\":\"{}\",\"Payment\":\"{}\",\"Interest\":\"{}\",\
+                \"Principal\":\"{}\",\"Balance\":\"{}\"}}",
+                rows,
+                delimiter,
+                DateUtility::format_date(row.event_date()),
+                row.payment(),
+                row.interest(),
+                row.principal(),
+                row.balance()
+            );
+        }
+
+        format!(
+            "{{\"method\":\"{}\",\"rows\":[{}]}}",
+            AllocationUtility::method_mnemonic(method),
+            rows
+        )
+    }
+
+    /// Load dated exchange rates from a json string of the form
+    /// {"exchange-rates": [{"date": "2022-01-31", "from-code": "USD",
+    /// "to-code": "EUR", "rate": "0.89"}]}. Rates with the same date
+    /// and currency codes as an existing rate replace it. No rates
+    /// are loaded if any rate is invalid. The table is saved with
+    /// serialized cashflows (see serialize).
+    ///
+    /// # Arguments
+    ///
+    /// * `json_input` - The json input string.
+    ///
+    /// # Return
+    ///
+    /// * Returns empty string if successful or an error.
+
+    pub fn load_exchange_rates(&self, json_input: &str) -> String {
+        let value = match JSON::parse(json_input) {
+            Err(_e) => return String::from("Json error"),
+            Ok(o) => o,
+        };
+
+        let rates = match Reflect::get(&value, &JsValue::from_str("exchange-rates")) {
+            Err(_e) => return String::from("Json error"),
+            Ok(o) => o,
+        };
+        if !Array::is_array(&rates) {
+            return String::from("Json error");
+        }
+
+        let field = |elem: &JsValue, key: &str| -> String {
+            match Reflect::get(elem, &JsValue::from_str(key)) {
+                Err(_e) => String::from(""),
+                Ok(o) => match o.as_string() {
+                    Some(s) => s,
+                    None => o.as_f64().map_or(String::from(""), |f| f.to_string()),
+                },
+            }
+        };
+
+        let mut loaded = ListExchangeRate::new();
+        for elem in Array::from(&rates).iter() {
+            let rate = match field(&elem, "rate").parse::<Decimal>() {
+                Err(_e) => return String::from("Invalid exchange rate"),
+                Ok(o) => o,
+            };
+
+            if !loaded.add_exchange_rate(ElemExchangeRate::new(
+                CoreUtility::parse_date(field(&elem, "date").as_str()),
+                field(&elem, "from-code").as_str(),
+                field(&elem, "to-code").as_str(),
+                rate,
+            )) {
+                return String::from("Invalid exchange rate");
+            }
+        }
+
+        let mut exchange_rates = self.exchange_rates.borrow_mut();
+        for elem in loaded.list().iter() {
+            exchange_rates.add_exchange_rate(elem.clone());
+        }
+
+        String::from("")
+    }
+
+    /// Move an event of the selected cashflow to a new date and sort
+    /// order while preserving its values. Events with dates relative
+    /// to the event are reflowed (see set_relative_date).
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    /// * `index` - The event index.
+    /// * `date_param` - The new event date.
+    /// * `sort_param` - The new sort order.
+    ///
+    /// # Return
+    ///
+    /// * The new index of the event or -1 if not successful
+    ///   (or another event has the date and sort order). The
+    ///   event is left in place if it cannot be pasted.
+
+    pub fn move_event(&self, cf_index: i32, index: u32, date_param: &str, sort_param: u32) -> i32 {
+        if cf_index < 0 {
+            return -1;
+        }
+
+        {
+            let calc_mgr = self.engine.calc_mgr();
+            if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
+                return -1;
+            }
+        }
+
+        let copy = match self.event_copy(index as usize) {
+            None => return -1,
+            Some(o) => o,
+        };
+
+        let event_date = CoreUtility::parse_date(self.engine.format_date_in(date_param).as_str());
+        let sort_order = sort_param as usize;
+        if (event_date, sort_order) != (copy.event_date(), copy.sort_order())
+            && self.event_sort_orders().contains(&(event_date, sort_order))
+        {
+            return -1;
+        }
+
+        // The event is pasted before the original is removed so
+        // that a failed move leaves the original in place.
+        if (event_date, sort_order) != (copy.event_date(), copy.sort_order()) {
+            if self.paste_event(&copy, event_date, sort_order).is_none() {
+                return -1;
+            }
+            if !self.remove_event_by_date(copy.event_date(), copy.sort_order()) {
+                self.remove_event_by_date(event_date, sort_order);
+                return -1;
+            }
+        }
+
+        if !self.balance_events() {
+            return -1;
+        }
+
+        self.event_index_by_date(event_date, sort_order)
+            .map_or(-1, |o| o as i32)
+    }
+
+    /// Parse and return an array of WasmElemColumns.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    /// * `table_type_param` - The type of table (event or amortization).
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn parse_columns(&self, cf_index: i32, table_type_param: u32) -> Array {
+        if cf_index < 0 {
+            return Array::new();
+        }
+
+        let calc_mgr = self.engine.calc_mgr();
+        if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
+            return Array::new();
+        }
+
+        let table_type: TableType = match table_type_param {
+            TABLE_AM => TableType::Amortization,
+            _ => TableType::Event,
+        };
+
+        let mut ary_column: Vec<WasmElemColumn> = Vec::new();
+        let list_column = self.engine.parse_columns(table_type);
+        let mut index: usize = 0;
+
+        loop {
+            if !list_column.get_element(index) {
+                break;
+            }
+
+            ary_column.push(WasmElemColumn::new(
+                list_column.col_name(),
+                list_column.col_name_index() as u32,
+                list_column.col_header(),
+                list_column.col_description(),
+                list_column.group(),
+                list_column.name(),
+                list_column.col_type(),
+                list_column.col_name(),
+                list_column.format() as u32,
+                list_column.decimal_digits() as u32,
+                list_column.column_width() as u32,
+                list_column.column_editable(),
+            ));
+
+            index += 1;
+        }
+
+        if table_type_param == TABLE_AM
+            && ElemPrepaymentModel::parse(
+                self.cashflow_descriptor(GROUP_PREPAYMENT, NAME_PREPAYMENT_MODEL)
+                    .as_str(),
+            )
+            .prepayment_type()
+                != PrepaymentType::None
+        {
+            let decimal_digits = self.cashflow_decimal_digits();
+            for (col_name, col_header) in [
+                ("Prepayment", "Prepayment"),
+                ("Expected-balance", "Expected balance"),
+            ]
+            .iter()
+            {
+                ary_column.push(WasmElemColumn::new(
+                    col_name,
+                    0,
+                    col_header,
+                    col_header,
+                    GROUP_PREPAYMENT,
+                    col_name,
+                    "custom",
+                    col_name,
+                    FORMAT_CURRENCY as u32,
+                    decimal_digits,
+                    70,
+                    false,
+                ));
+            }
+        }
+
+        if table_type_param == TABLE_AM && !self.depreciation_assets().is_empty() {
+            let decimal_digits = self.cashflow_decimal_digits();
+            for (col_name, col_header) in [
+                ("Depreciation", "Depreciation"),
+                ("Accumulated-depreciation", "Accumulated depreciation"),
+                ("Book-value", "Book value"),
+            ]
+            .iter()
+            {
+                ary_column.push(WasmElemColumn::new(
+                    col_name,
+                    0,
+                    col_header,
+                    col_header,
+                    GROUP_DEPRECIATION,
+                    col_name,
+                    "custom",
+                    col_name,
+                    FORMAT_CURRENCY as u32,
+                    decimal_digits,
+                    70,
+                    false,
+                ));
+            }
+        }
+
+        if table_type_param == TABLE_AM && !self.cashflow_actual_payments().is_empty() {
+            let decimal_digits = self.cashflow_decimal_digits();
+            for (col_name, col_header, format) in [
+                ("Past-due", "Past due", FORMAT_CURRENCY),
+                ("Days-delinquent", "Days delinquent", FORMAT_INTEGER),
+                ("Late-fees", "Late fees", FORMAT_CURRENCY),
+            ]
+            .iter()
+            {
+                ary_column.push(WasmElemColumn::new(
+                    col_name,
+                    0,
+                    col_header,
+                    col_header,
+                    GROUP_DELINQUENCY,
+                    col_name,
+                    "custom",
+                    col_name,
+                    *format as u32,
+                    if *format == FORMAT_CURRENCY {
+                        decimal_digits
+                    } else {
+                        0
+                    },
+                    70,
+                    false,
+                ));
+            }
+        }
+
+        if table_type_param == TABLE_EVENT && self.event_schedules().iter().any(|o| !o.is_empty()) {
+            ary_column.push(WasmElemColumn::new(
+                COL_SCHEDULE,
+                0,
+                COL_SCHEDULE,
+                "Recurrence rule",
+                GROUP_SCHEDULE,
+                COL_SCHEDULE,
+                "custom",
+                COL_SCHEDULE,
+                FORMAT_STRING as u32,
+                0,
+                150,
+                true,
+            ));
+        }
+
+        ary_column.into_iter().map(JsValue::from).collect()
+    }
+
+    /// Parse and return an array of WasmDescriptors.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    /// * `index` - The event index of the cashflow.
+    /// * `table_type_param` - The type of table (event or amortization).
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn parse_descriptors(&self, cf_index: i32, index: u32, table_type_param: u32) -> Array {
+        if cf_index < 0 {
+            return Array::new();
+        }
+
+        let calc_mgr = self.engine.calc_mgr();
+        if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
+            return Array::new();
+        }
+
+        match table_type_param {
+            TABLE_AM => match calc_mgr.list_cashflow().list_amortization() {
+                None => Array::new(),
+                Some(o) => {
+                    if !o.get_element(index as usize) {
+                        return Array::new();
+                    }
+
+                    match o.list_descriptor() {
+                        None => Array::new(),
+                        Some(o2) => {
+                            let mut list: Vec<WasmDescriptor> = Vec::new();
+                            let orig_index = o2.index();
+                            let mut index: usize = 0;
+                            loop {
+                                if !o2.get_element(index) {
+                                    break;
+                                }
+                                list.push(WasmDescriptor::new(
+                                    o2.group(),
+                                    o2.name(),
+                                    o2.desc_type(),
+                                    o2.code(),
+                                    o2.value().as_str(),
+                                    o2.value_expr().as_str(),
+                                    o2.propagate(),
+                                    o2.list_event_index() as u32,
+                                ));
+                                index += 1;
+                            }
+                            o2.get_element(orig_index);
+                            list.into_iter().map(JsValue::from).collect()
+                        }
+                    }
+                }
+            },
+            _ => match calc_mgr.list_cashflow().list_event() {
+                None => Array::new(),
+                Some(o) => {
+                    if !o.get_element(index as usize) {
+                        return Array::new();
+                    }
+
+                    match o.list_descriptor() {
+                        None => Array::new(),
+                        Some(o2) => {
+                            let mut list: Vec<WasmDescriptor> = Vec::new();
+                            let orig_index = o2.index();
+                            let mut index: usize = 0;
+                            loop {
+                                if !o2.get_element(index) {
+                                    break;
+                                }
+                                list.push(WasmDescriptor::new(
+                                    o2.group(),
+                                    o2.name(),
+                                    o2.desc_type(),
+                                    o2.code(),
+                                    o2.value().as_str(),
+                                    o2.value_expr().as_str(),
+                                    o2.propagate(),
+                                    o2.list_event_index() as u32,
+                                ));
+                                index += 1;
+                            }
+                            o2.get_element(orig_index);
+                            list.into_iter().map(JsValue::from).collect()
+                        }
+                    }
+                }
+            },
+        }
+    }
+
+    /// Parse and return an array of WasmParameters.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    /// * `index` - The event index of the cashflow.
+    /// * `table_type_param` - The type of table (event or amortization).
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn parse_parameters(&self, cf_index: i32, index: u32, table_type_param: u32) -> Array {
+        if cf_index < 0 {
+            return Array::new();
+        }
+
+        let calc_mgr = self.engine.calc_mgr();
+        if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
+            return Array::new();
+        }
+
+        match table_type_param {
+            TABLE_AM => match calc_mgr.list_cashflow().list_amortization() {
+                None => Array::new(),
+                Some(o) => {
+                    if !o.get_element(index as usize) {
+                        return Array::new();
+                    }
+
+                    match o.list_parameter() {
+                        None => Array::new(),
+                        Some(o2) => {
+                            let mut list: Vec<WasmParameter> = Vec::new();
+                            let orig_index = o2.index();
+                            let mut index: usize = 0;
+                            loop {
+                                if !o2.get_element(index) {
+                                    break;
+                                }
+                                list.push(WasmParameter::new(
+                                    o2.name(),
+                                    o2.label(),
+                                    o2.description(),
+                                    CoreUtility::get_param_type(o2.param_type()).as_str(),
+                                    o2.param_integeri(),
+                                    o2.param_decimal().to_string().as_str(),
+                                    o2.param_string(),
+                                ));
+                                index += 1;
+                            }
+                            o2.get_element(orig_index);
+                            list.into_iter().map(JsValue::from).collect()
+                        }
+                    }
+                }
+            },
+            _ => match calc_mgr.list_cashflow().list_event() {
+                None => Array::new(),
+                Some(o) => {
+                    if !o.get_element(index as usize) {
+                        return Array::new();
+                    }
+
+                    match o.list_parameter() {
+                        None => Array::new(),
+                        Some(o2) => {
+                            let mut list: Vec<WasmParameter> = Vec::new();
+                            let orig_index = o2.index();
+                            let mut index: usize = 0;
+                            loop {
+                                if !o2.get_element(index) {
+                                    break;
+                                }
+                                list.push(WasmParameter::new(
+                                    o2.name(),
+                                    o2.label(),
+                                    o2.description(),
+                                    CoreUtility::get_param_type(o2.param_type()).as_str(),
+                                    o2.param_integeri(),
+                                    o2.param_decimal().to_string().as_str(),
+                                    o2.param_string(),
+                                ));
+                                index += 1;
+                            }
+                            o2.get_element(orig_index);
+                            list.into_iter().map(JsValue::from).collect()
+                        }
+                    }
+                }
+            },
+        }
+    }
+
+    /// Parse and return an array of WasmSummary elements.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn parse_summary(&self, cf_index: i32) -> Array {
+        if cf_index < 0 {
+            return Array::new();
+        }
+
+        if !self
+            .engine
+            .calc_mgr()
+            .list_cashflow()
+            .get_element(cf_index as usize)
+        {
+            return Array::new();
+        }
+
+        self.summary_results()
+            .into_iter()
+            .map(JsValue::from)
+            .collect()
+    }
+
+    /// Consolidate the selected cashflows into a portfolio. The
+    /// amortization rows are merged onto a common date axis and the
+    /// balance weighted average rate and maturity are calculated.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_indexes` - The cashflow indexes separated by '|' (e.g., "0|2|3").
+    /// * `as_of_date` - The date of the weighted averages (empty for
+    ///   the latest starting date of the cashflows).
+    ///
+    /// # Return
+    ///
+    /// * A json string with the consolidated "rows", "positions",
+    ///   "weighted-average-rate" and "weighted-average-maturity"
+    ///   (in months) or an empty string if not successful.
+
+    pub fn portfolio_values(&self, cf_indexes: &str, as_of_date: &str) -> String {
+        self.portfolio_json(cf_indexes, as_of_date, "")
+    }
+
+    /// Paste the events of a clipboard string (see copy_events) into
+    /// the selected cashflow. Pasted events keep their sort order
+    /// unless the date and sort order are taken (the next unused
+    /// sort order is then used). No events are pasted if any of
+    /// the events cannot be pasted.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    /// * `clipboard` - The clipboard string.
+    /// * `date_param` - The date of the first pasted event (the events
+    ///   are shifted by the same number of days) or empty to keep the
+    ///   event dates.
+    ///
+    /// # Return
+    ///
+    /// * The number of events pasted or -1 if not successful.
+
+    pub fn paste_events(&self, cf_index: i32, clipboard: &str, date_param: &str) -> i32 {
+        if cf_index < 0 {
+            return -1;
+        }
+
+        {
+            let calc_mgr = self.engine.calc_mgr();
+            if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
+                return -1;
+            }
+        }
+
+        let (text, first, last) = match Engine::parse_clipboard(clipboard) {
+            None => return -1,
+            Some(o) => o,
+        };
+
+        let scratch_index = match self.load_scratch_cashflow(text.as_str()) {
+            None => return -1,
+            Some(o) => o,
+        };
+
+        let copies: Option<Vec<ElemEventCopy>> = if self
+            .engine
+            .calc_mgr()
+            .list_cashflow()
+            .get_element(scratch_index)
+        {
+            (first..=last).map(|o| self.event_copy(o)).collect()
+        } else {
+            None
+        };
+
+        self.remove_cashflows_from(scratch_index);
+
+        let copies = match copies {
+            None => return -1,
+            Some(o) => o,
+        };
+
+        if !self
+            .engine
+            .calc_mgr()
+            .list_cashflow()
+            .get_element(cf_index as usize)
+        {
+            return -1;
+        }
+
+        let paste_date = if date_param.trim().is_empty() {
+            0
+        } else {
+            CoreUtility::parse_date(self.engine.format_date_in(date_param).as_str())
+        };
+        let first_date = copies.first().map_or(0, |o| o.event_date());
+
+        let mut pasted: Vec<(usize, usize)> = Vec::new();
+        for copy in copies.iter() {
+            let event_date =
+                ClipboardUtility::paste_date(copy.event_date(), first_date, paste_date);
+            let sort_order = ClipboardUtility::free_sort_order(
+                &self.event_sort_orders(),
+                event_date,
+                copy.sort_order(),
+            );
+            if self.paste_event(copy, event_date, sort_order).is_none() {
+                // Remove the events already pasted.
+                for (event_date, sort_order) in pasted.iter() {
+                    self.remove_event_by_date(*event_date, *sort_order);
+                }
+                return -1;
+            }
+            pasted.push((event_date, sort_order));
+        }
+
+        if !self.balance_events() {
+            return -1;
+        }
+
+        copies.len() as i32
+    }
+
+    /// Calculate the early payoff of the selected cashflow as of a date.
+    /// The unearned interest rebated depends upon the cashflow's
+    /// interest allocation method (actuarial or Rule of 78s).
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    /// * `as_of_date` - The payoff date.
+    ///
+    /// # Return
+    ///
+    /// * A json string with the "method", "payments-made",
+    ///   "payments-remaining", "finance-charge", "earned-interest",
+    ///   "rebate" and "payoff" or an empty string if not successful.
+
+    pub fn payoff_rebate(&self, cf_index: i32, as_of_date: &str) -> String {
+        if cf_index < 0 {
+            return String::from("");
+        }
+
+        if !self
+            .engine
+            .calc_mgr()
+            .list_cashflow()
+            .get_element(cf_index as usize)
+        {
+            return String::from("");
+        }
+
+        let as_of = CoreUtility::parse_date(self.engine.format_date_in(as_of_date).as_str());
+        if as_of == 0 {
+            return String::from("");
+        }
+
+        let flows = match self.amortization_rows(true) {
+            None => return String::from(""),
+            Some(o) => ElemCashFlow::from_rows(&o),
+        };
+
+        let method = self.cashflow_allocation_method();
+        let rebate =
+            AllocationUtility::rebate(&flows, method, as_of, self.cashflow_decimal_digits());
+
+        format!(
+            "{{\"method\":\"{}\",\"as-of-date\":\"{}\",\"payments-made\":\"{}\",\
+            \"payments-remaining\":\"{}\",\"finance-charge\":\"{}\",\"earned-interest\":\"{}\",\
+            \"rebate\":\"{}\",\"payoff\":\"{}\"}}",
+            AllocationUtility::method_mnemonic(method),
+            DateUtility::format_date(as_of),
+            rebate.payments_made(),
+            rebate.payments_remaining(),
+            rebate.finance_charge(),
+            rebate.earned_interest(),
+            rebate.rebate(),
+            rebate.payoff()
+        )
+    }
+
+    /// Consolidate the selected cashflows into a portfolio reported in
+    /// a currency. The amortization rows of each cashflow are converted
+    /// from the cashflow's currency at the exchange rate of each row's
+    /// date before being consolidated (see portfolio_values).
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_indexes` - The cashflow indexes separated by '|' (e.g., "0|2|3").
+    /// * `as_of_date` - The date of the weighted averages (empty for
+    ///   the latest starting date of the cashflows).
+    /// * `currency_code` - The reporting currency code (e.g., USD).
+    ///
+    /// # Return
+    ///
+    /// * See portfolio_values or an empty string if an exchange
+    ///   rate is not available.
+
+    pub fn portfolio_values_currency(
+        &self,
+        cf_indexes: &str,
+        as_of_date: &str,
+        currency_code: &str,
+    ) -> String {
+        if currency_code.trim().is_empty() {
+            return String::from("");
+        }
+
+        self.portfolio_json(cf_indexes, as_of_date, currency_code)
+    }
+
+    /// Parse and return an array of WasmSummary elements with the
+    /// currency results converted to a reporting currency. Only the
+    /// results formatted with formatcurrency are converted.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    /// * `currency_code` - The reporting currency code (empty for the
+    ///   cashflow's cross rate code).
+    /// * `date_param` - The date of the exchange rate (empty for the latest rate).
+    ///
+    /// # Return
+    ///
+    /// * See description (empty if an exchange rate is not available).
+
+    pub fn parse_summary_currency(
+        &self,
+        cf_index: i32,
+        currency_code: &str,
+        date_param: &str,
+    ) -> Array {
+        if cf_index < 0 {
+            return Array::new();
+        }
+
+        if !self
+            .engine
+            .calc_mgr()
+            .list_cashflow()
+            .get_element(cf_index as usize)
+        {
+            return Array::new();
+        }
+
+        let date = if date_param.is_empty() {
+            0
+        } else {
+            CoreUtility::parse_date(self.engine.format_date_in(date_param).as_str())
+        };
+
+        let rate = match self.exchange_rates.borrow().rate(
+            self.cashflow_currency_code().as_str(),
+            self.reporting_currency_code(currency_code).as_str(),
+            date,
+        ) {
+            None => return Array::new(),
+            Some(o) => o,
+        };
+
+        let mut ary_summary: Vec<WasmElemSummary> = Vec::new();
+        for summary in self.summary_results().iter() {
+            let mut result = summary.result();
+            if summary
+                .result_expr()
+                .to_lowercase()
+                .contains("formatcurrency")
+            {
+                if let Some(o) = self.display_number(result.as_str()) {
+                    result = self.engine.format_currency_out(o * rate);
+                }
+            }
+
+            ary_summary.push(WasmElemSummary::new(
+                summary.name().as_str(),
+                summary.label().as_str(),
+                summary.label_expr().as_str(),
+                result.as_str(),
+                summary.result_expr().as_str(),
+            ));
+        }
+
+        ary_summary.into_iter().map(JsValue::from).collect()
+    }
+
+    /// Project the expected-case schedule of the selected cashflow
+    /// under its prepayment model.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    ///
+    /// # Return
+    ///
+    /// * Return a string that can be directly loaded into ag-grid.
+
+    pub fn prepayment_values(&self, cf_index: i32) -> String {
+        if cf_index < 0 {
+            return String::from("");
+        }
+
+        {
+            let calc_mgr = self.engine.calc_mgr();
+            if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
+                return String::from("");
+            }
+        }
+
+        let model = ElemPrepaymentModel::parse(
+            self.cashflow_descriptor(GROUP_PREPAYMENT, NAME_PREPAYMENT_MODEL)
+                .as_str(),
+        );
+
+        let rows = match self.amortization_rows(true) {
+            None => return String::from(""),
+            Some(o) => o,
+        };
+
+        let mut result = String::from("");
+        for (row_index, row) in
+            PrepaymentUtility::project(&rows, &model, self.cashflow_decimal_digits())
+                .iter()
+                .enumerate()
+        {
+            let delimiter = if row_index == 0 { "" } else { "," };
+            result = format!(
+                "{}{}{{\"Date\":\"{}\",\"Scheduled-balance\":\"{}\",\"Interest\":\"{}\",\
+                \"Scheduled-principal\":\"{}\",\"Prepayment\":\"{}\",\"Balance\":\"{}\",\"Smm\":\"{}\"}}",
+                result,
+                delimiter,
+                DateUtility::format_date(row.event_date()),
+                row.scheduled_balance(),
+                row.interest(),
+                row.scheduled_principal(),
+                row.prepayment(),
+                row.balance(),
+                (row.smm() * dec!(100.0)).round_dp(RATE_DECIMAL_DIGITS)
+            );
+        }
+
+        format!("[{}]", result)
+    }
+
+    /// Reconcile the ledger of actual payments of the selected cashflow
+    /// with each scheduled payment of its amortization. Actual payments
+    /// are applied to the oldest scheduled payments first.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    /// * `as_of_date` - The as of date (empty for the date of the
+    ///   last actual payment).
+    ///
+    /// # Return
+    ///
+    /// * A json string with the "scheduled", "actual", "variance" and
+    ///   "unapplied" totals and the "rows" of each scheduled payment
+    ///   with its status (upcoming, paid, early, partial or missed)
+    ///   or an empty string if not successful.
+
+    pub fn reconciliation_values(&self, cf_index: i32, as_of_date: &str) -> String {
+        if cf_index < 0 {
+            return String::from("");
+        }
+
+        if !self
+            .engine
+            .calc_mgr()
+            .list_cashflow()
+            .get_element(cf_index as usize)
+        {
+            return String::from("");
+        }
+
+        let payments = self.cashflow_actual_payments();
+        let as_of = if as_of_date.trim().is_empty() {
+            payments.last().map_or(0, |o| o.event_date())
+        } else {
+            CoreUtility::parse_date(self.engine.format_date_in(as_of_date).as_str())
+        };
+        if as_of == 0 {
+            return String::from("");
+        }
+
+        let flows = match self.amortization_rows(true) {
+            None => return String::from(""),
+            Some(o) => ElemCashFlow::from_rows(&o),
+        };
+
+        let reconciliation = ReconciliationUtility::reconcile(
+            &flows,
+            &payments,
+            as_of,
+            self.cashflow_decimal_digits(),
+        );
+
+        let mut rows = String::from("");
+        for (row_index, row) in reconciliation.iter().enumerate() {
+            let delimiter = if row_index == 0 { "" } else { "," };
+            rows = format!(
+                "{}{}{{\"Date\":\"{}\",\"Scheduled\":\"{}\",\"Actual\":\"{}\",\
+                \"Variance\":\"{}\",\"Paid-date\":\"{}\",\"Cumulative-scheduled\":\"{}\",\
+                \"Cumulative-actual\":\"{}\",\"Cumulative-variance\":\"{}\",\"Status\":\"{}\"}}",
+                rows,
+                delimiter,
+                DateUtility::format_date(row.event_date()),
+                row.scheduled(),
+                row.actual(),
+                row.variance(),
+                if row.paid_date() == 0 {
+                    String::from("")
+                } else {
+                    DateUtility::format_date(row.paid_date())
+                },
+                row.cumulative_scheduled(),
+                row.cumulative_actual(),
+                row.cumulative_variance(),
+                ReconciliationUtility::status_mnemonic(row.status())
+            );
+        }
+
+        let scheduled: Decimal = reconciliation
+            .iter()
+            .filter(|o| o.event_date() <= as_of)
+            .map(|o| o.scheduled())
+            .sum();
+        let actual: Decimal = payments
+            .iter()
+            .filter(|o| o.event_date() <= as_of)
+            .map(|o| o.amount())
+            .sum();
+
+        format!(
+            "{{\"as-of-date\":\"{}\",\"scheduled\":\"{}\",\"actual\":\"{}\",\"variance\":\"{}\",\
+            \"unapplied\":\"{}\",\"rows\":[{}]}}",
+            DateUtility::format_date(as_of),
+            scheduled,
+            actual,
+            actual - scheduled,
+            ReconciliationUtility::unapplied(&reconciliation, &payments, as_of),
+            rows
+        )
+    }
+
+    /// Reflow the events of the selected cashflow whose dates are
+    /// relative to other events (see set_relative_date) and balance
+    /// the cashflow.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    ///
+    /// # Return
+    ///
+    /// * A readable error (e.g., the anchors form a cycle)
+    ///   or an empty string if successful.
+
+    pub fn reflow_events(&self, cf_index: i32) -> String {
+        if cf_index < 0 {
+            return self.element_error();
+        }
+
+        {
+            let calc_mgr = self.engine.calc_mgr();
+            if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
+                return self.element_error();
+            }
+        }
+
+        match self.reflow_relative_dates() {
+            Err(e) => self.relative_date_error(&e),
+            Ok(false) => self.element_error(),
+            Ok(true) => {
+                if self.balance_events() {
+                    String::from("")
+                } else {
+                    self.element_error()
+                }
+            }
+        }
+    }
+
+    /// Remove the actual payments received on a date from the
+    /// selected cashflow.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    /// * `payment_date` - The date the payments were received.
+    ///
+    /// # Return
+    ///
+    /// * True if a payment was removed, otherwise false.
+
+    pub fn remove_actual_payment(&self, cf_index: i32, payment_date: &str) -> bool {
+        if cf_index < 0 {
+            return false;
+        }
+
+        {
+            let calc_mgr = self.engine.calc_mgr();
+            if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
+                return false;
+            }
+        }
+
+        let event_date = CoreUtility::parse_date(self.engine.format_date_in(payment_date).as_str());
+        let mut payments = self.cashflow_actual_payments();
+        let count = payments.len();
+        payments.retain(|o| o.event_date() != event_date);
+        if payments.len() == count {
+            return false;
+        }
+
+        self.set_cashflow_descriptor(
+            GROUP_ACTUAL,
+            NAME_ACTUAL_PAYMENTS,
+            DailyInterestUtility::payments_value(&payments).as_str(),
+        )
+    }
+
+    /// Remove the indicated cashflow.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    ///
+    /// # Return
+    ///
+    /// * True if successful.
+
+    pub fn remove_cashflow(&self, cf_index: i32) -> bool {
+        if cf_index < 0 {
+            return false;
+        }
+
+        {
+            let calc_mgr = self.engine.calc_mgr();
+
+            if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
+                return false;
+            }
+        }
+
+        self.engine.calc_mgr_mut().list_cashflow_mut().remove()
+    }
+
+    /// Remove the indicated event for the selected cashflow.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    /// * `index` - The event index.
+    ///
+    /// # Return
+    ///
+    /// * True if successful.
+
+    pub fn remove_event(&self, cf_index: i32, index: u32) -> bool {
+        let mut result: bool = false;
+        if cf_index < 0 {
+            return result;
+        }
+
+        {
+            let calc_mgr = self.engine.calc_mgr();
+
+            if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
+                return result;
+            }
+
+            match calc_mgr.list_cashflow().list_event() {
+                None => {
+                    return result;
+                }
+                Some(o) => {
+                    if !o.get_element(index as usize) {
+                        return result;
+                    }
+                }
+            }
+        }
+
+        match self
+            .engine
+            .calc_mgr_mut()
+            .list_cashflow_mut()
+            .list_event_mut()
+        {
+            None => return result,
+            Some(o) => {
+                result = o.remove();
+            }
+        }
+
+        self.balance_events();
+
+        result
+    }
+
+    /// Create the revolving line of credit schedule of the selected
+    /// cashflow with its credit limit, draw period and minimum payment
+    /// rule. Warnings report draws that exceed the available credit,
+    /// draws after the draw period and payments less than the minimum
+    /// payment due.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    ///
+    /// # Return
+    ///
+    /// * A json string with the "terms", "peak-balance", the schedule
+    ///   "rows" and the "warnings" or an empty string if not successful
+    ///   (or the cashflow has no revolving terms).
+
+    pub fn revolving_values(&self, cf_index: i32) -> String {
+        if cf_index < 0 {
+            return String::from("");
+        }
+
+        if !self
+            .engine
+            .calc_mgr()
+            .list_cashflow()
+            .get_element(cf_index as usize)
+        {
+            return String::from("");
+        }
+
+        let terms = match self.cashflow_revolving_terms() {
+            None => return String::from(""),
+            Some(o) => o,
+        };
+
+        let am_rows = match self.amortization_rows(true) {
+            None => return String::from(""),
+            Some(o) => o,
+        };
+
+        let (schedule, warnings) =
+            RevolvingUtility::schedule(&am_rows, &terms, self.cashflow_decimal_digits());
+
+        let mut rows = String::from("");
+        for (row_index, row) in schedule.iter().enumerate() {
+            let delimiter = if row_index == 0 { "" } else { "," };
+            rows = format!(
+                "{}{}{{\"Date\":\"{}\",\"Draw\":\"{}\",\"Payment\":\"{}\",\"Interest\":\"{}\",\
+                \"Balance\":\"{}\",\"Available\":\"{}\",\"Minimum-payment\":\"{}\"}}",
+                rows,
+                delimiter,
+                DateUtility::format_date(row.event_date()),
+                row.draw(),
+                row.payment(),
+                row.interest(),
+                row.balance(),
+                row.available(),
+                row.minimum_payment()
+            );
+        }
+
+        let mut warning_values = String::from("");
+        for (warning_index, warning) in warnings.iter().enumerate() {
+            let delimiter = if warning_index == 0 { "" } else { "," };
+            warning_values = format!(
+                "{}{}{{\"Date\":\"{}\",\"Warning\":\"{}\",\"Amount\":\"{}\"}}",
+                warning_values,
+                delimiter,
+                DateUtility::format_date(warning.event_date()),
+                RevolvingUtility::warning_mnemonic(warning.warning_type()),
+                warning.amount()
+            );
+        }
+
+        format!(
+            "{{\"terms\":\"{}\",\"peak-balance\":\"{}\",\"rows\":[{}],\"warnings\":[{}]}}",
+            terms.descriptor_value(),
+            schedule
+                .iter()
+                .map(|o| o.balance())
+                .max()
+                .unwrap_or(dec!(0.0)),
+            rows,
+            warning_values
+        )
+    }
+
+    /// Calculate a sensitivity grid. One or two event inputs are
+    /// varied over their ranges and the output is calculated on a
+    /// scratch copy of the cashflow that is rebalanced for each cell
+    /// (the cashflow is unchanged).
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    /// * `output_param` - The output ("payment~index" for the value
+    ///   calculated for an event, "interest" or "balance").
+    /// * `row_input_param` - The row input ("index~column~start~end~step").
+    /// * `column_input_param` - The column input (empty for one input).
+    ///
+    /// # Return
+    ///
+    /// * A json string with the grid (see SensitivityUtility::to_json)
+    ///   or an empty string if not successful.
+
+    pub fn sensitivity_grid(
+        &self,
+        cf_index: i32,
+        output_param: &str,
+        row_input_param: &str,
+        column_input_param: &str,
+    ) -> String {
+        if cf_index < 0 {
+            return String::from("");
+        }
+
+        let output = match SensitivityUtility::parse_output(output_param) {
+            None => return String::from(""),
+            Some(o) => o,
+        };
+
+        let row_input = match ElemSensitivityInput::parse(row_input_param) {
+            None => return String::from(""),
+            Some(o) => o,
+        };
+
+        let column_input = if column_input_param.trim().is_empty() {
+            None
+        } else {
+            match ElemSensitivityInput::parse(column_input_param) {
+                None => return String::from(""),
+                Some(o) => Some(o),
+            }
+        };
+
+        let scratch = self.clone_cashflow(cf_index, SENSITIVITY_SCRATCH_NAME);
+        if scratch < 0 {
+            return String::from("");
+        }
+        if self.init_cashflow(scratch).is_empty() {
+            self.remove_cashflow(scratch);
+            return String::from("");
+        }
+
+        let mut grid: Vec<Vec<Option<Decimal>>> = Vec::new();
+        for row_value in row_input.values().iter() {
+            let mut row: Vec<Option<Decimal>> = Vec::new();
+            match column_input.as_ref() {
+                None => {
+                    row.push(self.scratch_value(scratch, output, &[(&row_input, *row_value)]));
+                }
+                Some(o) => {
+                    for column_value in o.values().iter() {
+                        row.push(self.scratch_value(
+                            scratch,
+                            output,
+                            &[(&row_input, *row_value), (o, *column_value)],
+                        ));
+                    }
+                }
+            }
+            grid.push(row);
+        }
+
+        self.remove_cashflow(scratch);
+
+        let column_values = column_input.as_ref().map_or(&[][..], |o| o.values());
+
+        SensitivityUtility::to_json(row_input.values(), column_values, &grid)
+    }
+
+    /// Serialize and return the selected cashflow. The exchange rate
+    /// table is saved with the serialized cashflow preferences (the
+    /// cashflow itself is not changed).
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    /// * `options` - Serialization options.
+    ///
+    /// # Return
+    ///
+    /// * Returns serialized cashflow.
+
+    pub fn serialize(&self, cf_index: i32, options: u32) -> String {
+        if cf_index < 0 {
+            return String::from("");
+        }
+
+        if !self
+            .engine
+            .calc_mgr()
+            .list_cashflow()
+            .get_element(cf_index as usize)
+        {
+            return String::from("");
+        }
+
+        let text = {
+            let json = CalcJsonSerialize::new(self.engine.calc_manager());
+            json.serialize(options as usize)
+        };
+
+        if options as usize & JSON_SERIALIZE_CASHFLOW_PREFERENCES == 0 {
+            return text;
+        }
+
+        let value = self.exchange_rates.borrow().rates_value();
+        Engine::set_serialized_descriptor(
+            text.as_str(),
+            GROUP_EXCHANGE,
+            NAME_EXCHANGE_RATES,
+            value.as_str(),
+        )
+        .unwrap_or_default()
+    }
+
+    /// Set the appropriate event list value and
+    /// return it as a string. A value of the custom Schedule
+    /// column sets the event's recurrence rule (see set_schedule).
+    /// Events with relative dates are reflowed (see set_relative_date)
+    /// and a date entered by hand removes the event's relative date.
+    ///
+    /// # Arguments
+    ///
+    /// * `col_name_index_param` - Column name index.
+    /// * `type_param` - Column type.
+    /// * `code_param` - Column code.
+    /// * `cf_index_param` - The cashflow index.
+    /// * `index_param` - Event row index.
+    /// * `value_param` - Value to set as a string.
+    ///
+    /// # Return
+    ///
+    /// * See description or a readable relative date error
+    ///   (see reflow_events).
+
+    pub fn set_event_value(
+        &self,
+        col_name_index_param: u32,
+        type_param: &str,
+        code_param: &str,
+        cf_index_param: i32,
+        index_param: u32,
+        value_param: &str,
+    ) -> String {
+        if cf_index_param < 0 {
+            return String::from("");
+        }
+
+        {
+            let calc_mgr = self.engine.calc_mgr();
+
+            if !calc_mgr
+                .list_cashflow()
+                .get_element(cf_index_param as usize)
+            {
+                return String::from("");
+            }
+
+            match calc_mgr.list_cashflow().list_event() {
+                None => {
+                    return String::from("");
+                }
+                Some(o) => {
+                    o.get_element(index_param as usize);
+                }
+            }
+        }
+
+        let mut event_date = String::from("");
+        let mut sort_order: usize = 0;
+
+        {
+            let calc_mgr = self.engine.calc_mgr();
+            let list_locale = calc_mgr.list_locale();
+
+            match calc_mgr.list_cashflow().list_event() {
+                None => {}
+                Some(o) => {
+                    event_date = list_locale.format_date_out(o.event_date());
+                    sort_order = o.sort_order();
+                }
+            }
+        }
+
+        if type_param == "custom" && code_param == COL_SCHEDULE {
+            return match self.schedule_event(index_param as usize, value_param) {
+                None => String::from(""),
+                Some((o1, o2)) => {
+                    format!("{}|{}|{}", self.engine.format_date_out(o1), sort_order, o2)
+                }
+            };
+        }
+
+        // A date entered by hand replaces the event's relative date
+        // (the event may move once its date is set).
+        let relative_rule =
+            if self.find_event_column("Date").map(|o| o.0) == Some(col_name_index_param as usize) {
+                self.event_descriptor(
+                    index_param as usize,
+                    GROUP_RELATIVE_DATE,
+                    NAME_RELATIVE_DATE_RULE,
+                )
+            } else {
+                String::from("")
+            };
+        if !relative_rule.is_empty()
+            && !self.set_event_descriptor(
+                index_param as usize,
+                GROUP_RELATIVE_DATE,
+                NAME_RELATIVE_DATE_RULE,
+                "",
+            )
+        {
+            return String::from("");
+        }
+
+        let result = self.engine.set_event_value(
+            col_name_index_param as usize,
+            type_param,
+            code_param,
+            index_param as usize,
+            value_param,
+        );
+
+        if result.is_empty() && !relative_rule.is_empty() {
+            self.set_event_descriptor(
+                index_param as usize,
+                GROUP_RELATIVE_DATE,
+                NAME_RELATIVE_DATE_RULE,
+                relative_rule.as_str(),
+            );
+        }
+
+        // Skip periods entered by hand on a conditional event replace
+        // the kept skip periods (see condition_mask).
+        if code_param == "Skip-periods"
+            && !result.is_empty()
+            && !self
+                .event_descriptor(index_param as usize, GROUP_CONDITION, NAME_CONDITION_RULE)
+                .is_empty()
+            && (!self.set_event_descriptor(
+                index_param as usize,
+                GROUP_CONDITION,
+                NAME_CONDITION_MASK,
+                "",
+            ) || self.condition_mask(index_param as usize).is_none())
+        {
+            return String::from("");
+        }
+
+        // Relative date errors (e.g., a renamed anchor event) leave the
+        // relative events in place (see reflow_events).
+        let reflow = self.reflow_relative_dates();
+        if !self.balance_events() {
+            return String::from("");
+        }
+
+        match reflow {
+            Err(e) => self.relative_date_error(&e),
+            Ok(_o) => format!("{}|{}|{}", event_date, sort_order, result),
+        }
+    }
+
+    /// Set the appropriate event list extension values.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index_param` - The cashflow index.
+    /// * `index_param` - Event row index.
+    /// * `ext_param` - Extension values to set.
+    ///
+    /// # Return
+    ///
+    /// * True if successful, otherwise false.
+
+    pub fn set_extension_values(
+        &self,
+        cf_index_param: i32,
+        index_param: u32,
+        ext_param: &str,
+    ) -> String {
+        if cf_index_param < 0 {
+            return String::from("");
+        }
+
+        {
+            let calc_mgr = self.engine.calc_mgr();
+
+            if !calc_mgr
+                .list_cashflow()
+                .get_element(cf_index_param as usize)
+            {
+                return String::from("");
+            }
+        }
+
+        let ext: ElemExtension;
+
+        {
+            let json = CalcJsonDeserialize::new(self.engine.calc_manager());
+            match json.deserialize_extension_from_str(ext_param) {
+                Err(_e) => {
+                    return String::from("");
+                }
+                Ok(o) => {
+                    ext = o;
+                }
+            }
+        }
+
+        if !self.engine.set_extension_values(index_param as usize, &ext) {
+            return String::from("");
+        }
+
+        // Balance rounding that differs from the cashflow's currency
+        // rounding is kept (see apply_currency_rounding).
+        if let Some((mode, decimal_digits)) = Engine::extension_rounding(ext_param) {
+            let rounded = mode
+                == CurrencyUtility::rounding_mode_mnemonic(self.cashflow_rounding_mode())
+                && decimal_digits == self.cashflow_decimal_digits().to_string();
+            if !self.set_event_descriptor(
+                index_param as usize,
+                GROUP_ROUNDING,
+                NAME_ROUNDING_OVERRIDE,
+                if rounded { "" } else { "true" },
+            ) {
+                return String::from("");
+            }
+        }
+
+        self.engine.evaluate_cashflow_event_type_all();
+
+        let mut result = String::from("");
+
+        {
+            let calc_mgr = self.engine.calc_mgr();
+            let list_cashflow = calc_mgr.list_cashflow();
+            let list_event_opt = list_cashflow.list_event();
+
+            match list_event_opt {
+                None => {}
+                Some(o) => {
+                    let orig_index = o.index();
+                    if o.get_element(index_param as usize) {
+                        result = String::from(o.event_type());
+                        o.get_element(orig_index);
+                    }
+                }
+            }
+        }
+
+        if self.balance_events() {
+            result
+        } else {
+            String::from("")
+        }
+    }
+
+    /// Set the appropriate event list parameter values.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    /// * `index_param` - Event row index.
+    /// * `parameters` - Parameters to set.
+    ///
+    /// # Return
+    ///
+    /// * True if successful, otherwise false.
+
+    pub fn set_parameter_values(&self, cf_index: i32, index_param: u32, parameters: &str) -> bool {
+        if cf_index < 0 {
+            return false;
+        }
+
+        {
+            let calc_mgr = self.engine.calc_mgr();
+
+            if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
+                return false;
+            }
+        }
+
+        let mut values: Vec<String> = Vec::new();
+        for param in parameters.split('|') {
+            values.push(String::from(param));
+        }
+
+        if !self
+            .engine
+            .set_parameter_values(index_param as usize, values)
+        {
+            return false;
+        }
+
+        true
+    }
+
+    /// Set the condition of an event, which enables, disables or
+    /// terminates the event's periods while a condition expression
+    /// holds. The condition is evaluated for each period during
+    /// balancing and may use the symbols decBalance (the balance before
+    /// the period as shown in the amortization table), intDate,
+    /// intSequence (zero based), decValue, intPayments (scheduled
+    /// payments before the period) and intOnTime (actual payments made
+    /// on time before the period) along with the summary functions.
+    /// The condition maintains the event's skip periods (merged with
+    /// the skip periods entered by hand, which are restored when the
+    /// condition is removed).
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    /// * `index` - The event index.
+    /// * `condition` - The condition descriptor value (action~expression,
+    ///   where action is enable, disable or terminate, e.g.,
+    ///   "terminate~decBalance > -10000" or "enable~intOnTime >= 36")
+    ///   or empty to remove the condition from the event.
+    ///
+    /// # Return
+    ///
+    /// * True if successful, otherwise false.
+
+    pub fn set_condition(&self, cf_index: i32, index: u32, condition: &str) -> bool {
+        if cf_index < 0 {
+            return false;
+        }
+
+        {
+            let calc_mgr = self.engine.calc_mgr();
+            if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
+                return false;
+            }
+        }
+
+        let value = if condition.trim().is_empty() {
+            String::from("")
+        } else {
+            match ElemCondition::parse(condition) {
+                None => return false,
+                Some(o) => o.descriptor_value(),
+            }
+        };
+
+        if self.event_period_dates(index as usize).is_none() {
+            return false;
+        }
+
+        if value.is_empty() {
+            let mask = match (
+                self.schedule_mask(index as usize),
+                self.condition_mask(index as usize),
+            ) {
+                (Some(o1), Some(o2)) => SkipPeriodsUtility::merge(&o1, &o2),
+                _ => return false,
+            };
+            if !self.set_event_column(index as usize, "Skip-periods", mask.as_str())
+                || !self.set_event_descriptor(
+                    index as usize,
+                    GROUP_CONDITION,
+                    NAME_CONDITION_MASK,
+                    "",
+                )
+            {
+                return false;
+            }
+        }
+
+        if !self.set_event_descriptor(
+            index as usize,
+            GROUP_CONDITION,
+            NAME_CONDITION_RULE,
+            value.as_str(),
+        ) {
+            return false;
+        }
+
+        self.balance_events()
+    }
+
+    /// Set the construction loan conversion date of the selected
+    /// cashflow. The interest accrued through the conversion date is
+    /// funded from the interest reserve. The date is kept as a
+    /// descriptor within the cashflow preferences.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    /// * `conversion_date` - The conversion date (empty for the
+    ///   date before the first payment).
+    ///
+    /// # Return
+    ///
+    /// * True if successful, otherwise false.
+
+    pub fn set_conversion_date(&self, cf_index: i32, conversion_date: &str) -> bool {
+        if cf_index < 0 {
+            return false;
+        }
+
+        {
+            let calc_mgr = self.engine.calc_mgr();
+            if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
+                return false;
+            }
+        }
+
+        let value = if conversion_date.trim().is_empty() {
+            String::from("")
+        } else {
+            match CoreUtility::parse_date(self.engine.format_date_in(conversion_date).as_str()) {
+                0 => return false,
+                o => DateUtility::format_date(o),
+            }
+        };
+
+        self.set_cashflow_descriptor(GROUP_CONSTRUCTION, NAME_CONVERSION_DATE, value.as_str())
+    }
+
+    /// Set the grace period and late-fee rules of the selected cashflow.
+    /// The rules are kept as a descriptor within the cashflow preferences.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    /// * `rules` - The rules descriptor value (grace~type~fee~cap, where
+    ///   grace is the days of the grace period, type is flat or percent
+    ///   and cap is the maximum fee for each payment, e.g., "15~flat~25"
+    ///   or "10~percent~5~50") or empty for the default.
+    ///
+    /// # Return
+    ///
+    /// * True if successful, otherwise false.
+
+    pub fn set_delinquency_rules(&self, cf_index: i32, rules: &str) -> bool {
+        if cf_index < 0 {
+            return false;
+        }
+
+        {
+            let calc_mgr = self.engine.calc_mgr();
+            if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
+                return false;
+            }
+        }
+
+        let value = if rules.trim().is_empty() {
+            String::from("")
+        } else {
+            match ElemDelinquencyRules::parse(rules) {
+                None => return false,
+                Some(o) => o.descriptor_value(),
+            }
+        };
+
+        self.set_cashflow_descriptor(GROUP_DELINQUENCY, NAME_DELINQUENCY_RULES, value.as_str())
+    }
+
+    /// Set the depreciation method of an event, which makes the event
+    /// a depreciation event whose value is the cost of the asset placed
+    /// in service on the event date.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    /// * `index` - The event index.
+    /// * `method` - The method descriptor value (method~life~salvage~
+    ///   convention~factor, e.g., "SL~5~1000", "DB~7~0~HY~150", "SYD~10"
+    ///   or "MACRS~5") or empty to remove depreciation from the event.
+    ///
+    /// # Return
+    ///
+    /// * True if successful, otherwise false.
+
+    pub fn set_depreciation(&self, cf_index: i32, index: u32, method: &str) -> bool {
+        if cf_index < 0 {
+            return false;
+        }
+
+        {
+            let calc_mgr = self.engine.calc_mgr();
+            if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
+                return false;
+            }
+        }
+
+        let value = if method.trim().is_empty() {
+            String::from("")
+        } else {
+            match ElemDepreciation::parse(method) {
+                None => return false,
+                Some(o) => o.descriptor_value(),
+            }
+        };
+
+        self.set_event_descriptor(
+            index as usize,
+            GROUP_DEPRECIATION,
+            NAME_DEPRECIATION_METHOD,
+            value.as_str(),
+        )
+    }
+
+    /// Set the growth terms of a principal change event, which expands
+    /// the event into a payment stream that steps up by a percentage
+    /// or an amount every number of periods. The event value becomes
+    /// the initial payment (see calculate_value).
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    /// * `index` - The event index.
+    /// * `terms` - The terms descriptor value (percent|amount~rate~
+    ///   step-interval, e.g., "percent~7.5~12" or "amount~50~12")
+    ///   or empty to remove the growth from the event.
+    ///
+    /// # Return
+    ///
+    /// * True if successful, otherwise false.
+
+    pub fn set_growth(&self, cf_index: i32, index: u32, terms: &str) -> bool {
+        if cf_index < 0 {
+            return false;
+        }
+
+        {
+            let calc_mgr = self.engine.calc_mgr();
+            if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
+                return false;
+            }
+            match calc_mgr.list_cashflow().list_event() {
+                None => return false,
+                Some(o) => {
+                    if !o.get_element(index as usize)
+                        || o.elem_extension().extension_type() != ExtensionType::PrincipalChange
+                    {
+                        return false;
+                    }
+                }
+            }
+        }
+
+        let growth = if terms.trim().is_empty() {
+            None
+        } else {
+            match ElemGrowth::parse(terms) {
+                None => return false,
+                Some(o) => Some(o),
+            }
+        };
+
+        let prior_expr = match ElemGrowth::parse(
+            self.event_descriptor(index as usize, GROUP_GROWTH, NAME_GROWTH_TERMS)
+                .as_str(),
+        ) {
+            None => String::from(""),
+            Some(o) => o.expression(),
+        };
+
+        let (value, expr) = match growth {
+            None => (String::from(""), String::from("")),
+            Some(o) => (o.descriptor_value(), o.expression()),
+        };
+
+        {
+            let mut calc_mgr = self.engine.calc_mgr_mut();
+            let list_event = match calc_mgr.list_cashflow_mut().list_event_mut() {
+                None => return false,
+                Some(o) => o,
+            };
+            if !list_event.get_element(index as usize) {
+                return false;
+            }
+            if (growth.is_some() || list_event.value_expr() == prior_expr)
+                && !list_event.set_value_expr(expr.as_str())
+            {
+                return false;
+            }
+        }
+
+        if !self.set_event_descriptor(
+            index as usize,
+            GROUP_GROWTH,
+            NAME_GROWTH_TERMS,
+            value.as_str(),
+        ) {
+            return false;
+        }
+
+        self.balance_events()
+    }
+
+    /// Set the interest allocation method of the selected cashflow. The
+    /// method is kept as a descriptor within the cashflow preferences.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    /// * `method` - The method (actuarial or rule-of-78s;
+    ///   empty for the default).
+    ///
+    /// # Return
+    ///
+    /// * True if successful, otherwise false.
+
+    pub fn set_interest_allocation(&self, cf_index: i32, method: &str) -> bool {
+        if cf_index < 0 {
+            return false;
+        }
+
+        {
+            let calc_mgr = self.engine.calc_mgr();
+            if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
+                return false;
+            }
+        }
+
+        let value = if method.trim().is_empty() {
+            ""
+        } else {
+            match AllocationUtility::parse_method(method) {
+                None => return false,
+                Some(o) => AllocationUtility::method_mnemonic(o),
+            }
+        };
+
+        self.set_cashflow_descriptor(GROUP_ALLOCATION, NAME_ALLOCATION_METHOD, value)
+    }
+
+    /// Set the prepayment model of the selected cashflow. The model
+    /// is kept with the cashflow preferences and is serialized with them.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    /// * `model_type` - The model type (CPR, SMM, PSA or empty for none).
+    /// * `speed` - The CPR, SMM or PSA percentage.
+    ///
+    /// # Return
+    ///
+    /// * True if successful, otherwise false.
+
+    pub fn set_prepayment_model(&self, cf_index: i32, model_type: &str, speed: &str) -> bool {
+        if cf_index < 0 {
+            return false;
+        }
+
+        {
+            let calc_mgr = self.engine.calc_mgr();
+            if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
+                return false;
+            }
+        }
+
+        let prepayment_type = ElemPrepaymentModel::parse_type(model_type);
+        let model = match speed.parse::<Decimal>() {
+            Err(_e) => {
+                if prepayment_type != PrepaymentType::None {
+                    return false;
+                }
+                ElemPrepaymentModel::new(PrepaymentType::None, dec!(0.0))
+            }
+            Ok(o) => ElemPrepaymentModel::new(prepayment_type, o),
+        };
+
+        self.set_cashflow_descriptor(
+            GROUP_PREPAYMENT,
+            NAME_PREPAYMENT_MODEL,
+            model.descriptor_value().as_str(),
+        )
+    }
+
+    /// Set the date of an event relative to the date of another
+    /// (anchor) event. Whenever the anchor event moves, the event
+    /// is reflowed during balancing. The rule is kept as an event
+    /// descriptor so it is serialized with the event.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    /// * `index` - The event index.
+    /// * `rule` - The rule (anchor-name~offset~frequency, where the
+    ///   anchor is referenced by its event name and the offset is in
+    ///   intervals of the frequency, e.g., "Loan~1~1-month" for one
+    ///   month after the event named Loan or "Loan~60~1-month" for
+    ///   a balloon at month 60) or empty to remove the rule.
+    ///
+    /// # Return
+    ///
+    /// * A readable error (e.g., the anchors form a cycle, in which
+    ///   case the rule is not set) or an empty string if successful.
+
+    pub fn set_relative_date(&self, cf_index: i32, index: u32, rule: &str) -> String {
+        if cf_index < 0 {
+            return self.element_error();
+        }
+
+        {
+            let calc_mgr = self.engine.calc_mgr();
+            if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
+                return self.element_error();
+            }
+        }
+
+        let value = if rule.trim().is_empty() {
+            String::from("")
+        } else {
+            match ElemRelativeDate::parse(rule) {
+                Err(e) => return self.relative_date_error(&e),
+                Ok(o) => o.descriptor_value(),
+            }
+        };
+
+        let (event_date, sort_order) = {
+            let calc_mgr = self.engine.calc_mgr();
+            match calc_mgr.list_cashflow().list_event() {
+                None => return self.element_error(),
+                Some(o) => {
+                    if !o.get_element(index as usize) {
+                        return self.element_error();
+                    }
+                    (o.event_date(), o.sort_order())
+                }
+            }
+        };
+
+        let orig_value =
+            self.event_descriptor(index as usize, GROUP_RELATIVE_DATE, NAME_RELATIVE_DATE_RULE);
+        if !self.set_event_descriptor(
+            index as usize,
+            GROUP_RELATIVE_DATE,
+            NAME_RELATIVE_DATE_RULE,
+            value.as_str(),
+        ) {
+            return self.element_error();
+        }
+
+        match self.reflow_relative_dates() {
+            Err(e) => {
+                self.set_event_descriptor(
+                    index as usize,
+                    GROUP_RELATIVE_DATE,
+                    NAME_RELATIVE_DATE_RULE,
+                    orig_value.as_str(),
+                );
+                self.relative_date_error(&e)
+            }
+            Ok(false) => self.element_error(),
+            Ok(true) => {
+                if self.balance_events() {
+                    String::from("")
+                } else {
+                    self.element_error()
+                }
+            }
+        }
+    }
+
+    /// Set the rounding residue policy of the selected cashflow. The
+    /// policy is kept as a descriptor within the cashflow preferences.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    /// * `policy` - The policy (carry, last, first or spread;
+    ///   empty for the default).
+    ///
+    /// # Return
+    ///
+    /// * True if successful, otherwise false.
+
+    pub fn set_residue_policy(&self, cf_index: i32, policy: &str) -> bool {
+        if cf_index < 0 {
+            return false;
+        }
+
+        {
+            let calc_mgr = self.engine.calc_mgr();
+            if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
+                return false;
+            }
+        }
+
+        let value = if policy.trim().is_empty() {
+            ""
+        } else {
+            match ResidueUtility::parse_policy(policy) {
+                None => return false,
+                Some(o) => ResidueUtility::policy_mnemonic(o),
+            }
+        };
+
+        self.set_cashflow_descriptor(GROUP_ROUNDING, NAME_RESIDUE_POLICY, value)
+    }
+
+    /// Set the revolving line of credit terms of the selected cashflow,
+    /// which makes its draws subject to a credit limit. The terms are
+    /// kept as a descriptor within the cashflow preferences.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    /// * `terms` - The terms descriptor value (limit~draw-end~rule~
+    ///   percent~floor, where draw-end is the end of the draw period
+    ///   (may be empty) and rule is interest-only or percent of the
+    ///   balance with a minimum amount, e.g., "50000~2032-01-01~interest-only"
+    ///   or "10000~~percent~2~25") or empty to remove the terms.
+    ///
+    /// # Return
+    ///
+    /// * True if successful, otherwise false.
+
+    pub fn set_revolving_terms(&self, cf_index: i32, terms: &str) -> bool {
+        if cf_index < 0 {
+            return false;
+        }
+
+        {
+            let calc_mgr = self.engine.calc_mgr();
+            if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
+                return false;
+            }
+        }
+
+        let value = if terms.trim().is_empty() {
+            String::from("")
+        } else {
+            match ElemRevolvingTerms::parse(terms) {
+                None => return false,
+                Some(o) => o.descriptor_value(),
+            }
+        };
+
+        self.set_cashflow_descriptor(GROUP_REVOLVING, NAME_REVOLVING_TERMS, value.as_str())
+    }
+
+    /// Set the rounding mode of the selected cashflow. The mode is
+    /// kept as a descriptor within the cashflow preferences. The mode
+    /// and the cashflow's decimal digits are applied to the balance
+    /// rounding of the interest change events and the cashflow is
+    /// rebalanced.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    /// * `mode` - The rounding mode (none, bias-up, bias-down, up,
+    ///   truncate or bankers; empty for the default).
+    ///
+    /// # Return
+    ///
+    /// * True if successful, otherwise false.
+
+    pub fn set_rounding_mode(&self, cf_index: i32, mode: &str) -> bool {
+        if cf_index < 0 {
+            return false;
+        }
+
+        {
+            let calc_mgr = self.engine.calc_mgr();
+            if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
+                return false;
+            }
+        }
+
+        let value = if mode.trim().is_empty() {
+            ""
+        } else {
+            match CurrencyUtility::parse_rounding_mode(mode) {
+                None => return false,
+                Some(o) => CurrencyUtility::rounding_mode_mnemonic(o),
+            }
+        };
+
+        if !self.set_cashflow_descriptor(GROUP_ROUNDING, NAME_ROUNDING_MODE, value)
+            || !self.apply_currency_rounding()
+        {
+            return false;
+        }
+
+        self.engine.evaluate_cashflow_event_type_all();
+
+        self.balance_events()
+    }
+
+    /// Set the recurrence schedule of an event. The recurrence rule
+    /// (a subset of RFC 5545 RRULE) is expanded into the event's date,
+    /// periods, frequency and skip periods (the coarsest frequency
+    /// that covers every rule date is used). The rule is kept as an
+    /// event descriptor so it is serialized with the event.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    /// * `index` - The event index.
+    /// * `rule` - The recurrence rule (e.g., "FREQ=MONTHLY;BYMONTHDAY=15,-1"
+    ///   for the 15th and last day of each month or
+    ///   "FREQ=MONTHLY;INTERVAL=3;BYDAY=2TU" for the second Tuesday
+    ///   quarterly; DTSTART defaults to the event date and COUNT to the
+    ///   event periods) or empty to remove the schedule from the event.
+    ///
+    /// # Return
+    ///
+    /// * True if successful, otherwise false.
+
+    pub fn set_schedule(&self, cf_index: i32, index: u32, rule: &str) -> bool {
+        if cf_index < 0 {
+            return false;
+        }
+
+        {
+            let calc_mgr = self.engine.calc_mgr();
+            if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
+                return false;
+            }
+        }
+
+        self.schedule_event(index as usize, rule).is_some()
+    }
+
+    /// Set selected user or cashflow preferences. Decimal digits
+    /// changed in the cashflow preferences are kept as the cashflow's
+    /// explicit decimal digits and the currency rounding is applied
+    /// to the cashflow (or every cashflow for user preferences).
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index or -1 for user preferences.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn set_preferences(&self, cf_index: i32, prefs: &WasmElemPreferences) -> bool {
+        let mut decimal_digits: Option<u32> = None;
+        if cf_index >= 0 {
+            let selected = {
+                let calc_mgr = self.engine.calc_mgr();
+                calc_mgr.list_cashflow().get_element(cf_index as usize)
+                    && calc_mgr.list_cashflow().preferences().is_some()
+            };
+            if selected {
+                decimal_digits = Some(self.cashflow_decimal_digits());
+            }
+        }
+
+        {
+            let mut calc_mgr = self.engine.calc_mgr_mut();
+            let elem_prefs: &mut ElemPreferences;
+
+            if cf_index >= 0 {
+                match calc_mgr.list_cashflow_mut().preferences_mut() {
+                    None => {
+                        elem_prefs = calc_mgr.preferences_mut();
+                    }
+                    Some(o) => {
+                        elem_prefs = o;
+                    }
+                }
+            } else {
+                elem_prefs = calc_mgr.preferences_mut();
+            }
+
+            elem_prefs.set_cross_rate_code(prefs.cross_rate_code().as_str());
+            elem_prefs.set_default_encoding(prefs.default_encoding().as_str());
+            elem_prefs.set_fiscal_year_start(prefs.fiscal_year_start() as usize);
+            elem_prefs.set_decimal_digits(prefs.decimal_digits() as usize);
+            elem_prefs.set_target(CoreUtility::parse_decimal(prefs.target().as_str()));
+        }
+
+        // Decimal digits changed in the cashflow preferences are kept
+        // as explicit (see cashflow_decimal_digits).
+        if let Some(o) = decimal_digits {
+            if o != prefs.decimal_digits()
+                && !self.set_cashflow_descriptor(
+                    GROUP_ROUNDING,
+                    NAME_DECIMAL_DIGITS,
+                    prefs.decimal_digits().to_string().as_str(),
+                )
+            {
+                return false;
+            }
+        }
+
+        if cf_index >= 0 {
+            self.apply_cashflow_rounding(cf_index as usize..cf_index as usize + 1)
+        } else {
+            self.apply_cashflow_rounding(0..self.cashflow_count())
+        }
+    }
+
+    /// Parse and return the cashflow's event values for the table type.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    /// * `table_type_param` - The type of table (event or amortization).
+    ///
+    /// # Return
+    ///
+    /// * Return a string that can be directly loaded into ag-grid.
+
+    pub fn table_values(&self, cf_index: i32, table_type_param: u32) -> String {
+        if cf_index < 0 {
+            return String::from("");
+        }
+
+        let calc_mgr = self.engine.calc_mgr();
+
+        if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
+            return String::from("");
+        }
+
+        let table_type: TableType = match table_type_param {
+            TABLE_AM => TableType::Amortization,
+            _ => TableType::Event,
+        };
+
+        let list_column = self.engine.parse_columns(table_type);
+
+        let json = CalcJsonSerialize::new(self.engine.calc_manager());
+
+        let decimal_digits = self.cashflow_decimal_digits();
+
+        match table_type_param {
+            TABLE_AM => {
+                let depreciation_assets = self.depreciation_assets();
+                let depreciation_rows = DepreciationUtility::schedule(
+                    &depreciation_assets,
+                    self.fiscal_year_start(),
+                    decimal_digits,
+                );
+
+                let actual_payments = self.cashflow_actual_payments();
+                let delinquency_rules = self.cashflow_delinquency_rules();
+                let scheduled_flows = if actual_payments.is_empty() {
+                    Vec::new()
+                } else {
+                    self.amortization_rows(true)
+                        .map_or(Vec::new(), |o| ElemCashFlow::from_rows(&o))
+                };
+
+                let prepayment_model = ElemPrepaymentModel::parse(
+                    self.cashflow_descriptor(GROUP_PREPAYMENT, NAME_PREPAYMENT_MODEL)
+                        .as_str(),
+                );
+                let prepayment_rows = if prepayment_model.prepayment_type() == PrepaymentType::None
+                {
+                    Vec::new()
+                } else {
+                    self.amortization_rows(true).map_or(Vec::new(), |o| {
+                        PrepaymentUtility::project(&o, &prepayment_model, decimal_digits)
+                    })
+                };
+
+                let mut cresult = String::from("");
+                let mut list_am: ListAmortization = match calc_mgr
+                    .list_cashflow()
+                    .create_cashflow_output(true, false, false, false, true)
+                {
+                    Err(_e) => return cresult,
+                    Ok(o) => o,
+                };
+
+                let mut row_index: usize = 0;
+                let mut prior_date: usize = 0;
+                loop {
+                    if !list_am.get_element(row_index as usize) {
+                        break;
+                    }
+                    let mut row = json.serialize_extension(
+                        list_am.elem_extension(),
+                        list_am.value(),
+                        list_am.frequency(),
+                        false,
+                        true,
+                    );
+
+                    let orig_index = list_column.index();
+                    let mut index: usize = 0;
+                    loop {
+                        if !list_column.get_element(index) {
+                            break;
+                        }
+                        let val = self.engine.am_value(list_column.column(), &list_am);
+                        row = format!("{},\"{}\":\"{}\"", row, list_column.col_name(), val);
+                        index += 1;
+                    }
+                    list_column.get_element(orig_index);
+
+                    if !depreciation_assets.is_empty() {
+                        row = format!(
+                            "{},{}",
+                            row,
+                            Engine::depreciation_columns(
+                                &depreciation_assets,
+                                &depreciation_rows,
+                                prior_date,
+                                list_am.event_date()
+                            )
+                        );
+                    }
+
+                    if !prepayment_rows.is_empty() {
+                        row = format!(
+                            "{},\"Prepayment\":\"{}\",\"Expected-balance\":\"{}\"",
+                            row,
+                            PrepaymentUtility::period(
+                                &prepayment_rows,
+                                prior_date,
+                                list_am.event_date()
+                            ),
+                            PrepaymentUtility::balance(&prepayment_rows, list_am.event_date())
+                        );
+                    }
+
+                    prior_date = list_am.event_date();
+
+                    if !actual_payments.is_empty() {
+                        row = format!(
+                            "{},{}",
+                            row,
+                            Engine::delinquency_columns(
+                                &scheduled_flows,
+                                &actual_payments,
+                                &delinquency_rules,
+                                list_am.event_date(),
+                                decimal_digits
+                            )
+                        );
+                    }
+
+                    let delimiter = if row_index == 0 { "" } else { "," };
+                    cresult = format!("{}{}{{{}}}", cresult, delimiter, row);
+
+                    row_index += 1;
+                }
+
+                cresult = format!("\"compressed\": [{}]", cresult);
+
+                let mut eresult = String::from("");
+                match calc_mgr
+                    .list_cashflow()
+                    .create_cashflow_output(false, false, false, false, true)
+                {
+                    Err(_e) => {
+                        return eresult;
+                    }
+                    Ok(o) => {
+                        list_am = o;
+                    }
+                }
+
+                row_index = 0;
+                prior_date = 0;
+                loop {
+                    if !list_am.get_element(row_index as usize) {
+                        break;
+                    }
+                    let mut row = json.serialize_extension(
+                        list_am.elem_extension(),
+                        list_am.value(),
+                        list_am.frequency(),
+                        false,
+                        true,
+                    );
+
+                    let orig_index = list_column.index();
+                    let mut index: usize = 0;
+                    loop {
+                        if !list_column.get_element(index) {
+                            break;
+                        }
+                        let val = self.engine.am_value(list_column.column(), &list_am);
+                        row = format!("{},\"{}\":\"{}\"", row, list_column.col_name(), val);
+                        index += 1;
+                    }
+                    list_column.get_element(orig_index);
+
+                    if !depreciation_assets.is_empty() {
+                        row = format!(
+                            "{},{}",
+                            row,
+                            Engine::depreciation_columns(
+                                &depreciation_assets,
+                                &depreciation_rows,
+                                prior_date,
+                                list_am.event_date()
+                            )
+                        );
+                    }
+                    prior_date = list_am.event_date();
+
+                    if !actual_payments.is_empty() {
+                        row = format!(
+                            "{},{}",
+                            row,
+                            Engine::delinquency_columns(
+                                &scheduled_flows,
+                                &actual_payments,
+                                &delinquency_rules,
+                                list_am.event_date(),
+                                decimal_digits
+                            )
+                        );
+                    }
+
+                    if let Some(o) = prepayment_rows.get(row_index) {
+                        row = format!(
+                            "{},\"Prepayment\":\"{}\",\"Expected-balance\":\"{}\"",
+                            row,
+                            o.prepayment(),
+                            o.balance()
+                        );
+                    }
+
+                    let delimiter = if row_index == 0 { "" } else { "," };
+                    eresult = format!("{}{}{{{}}}", eresult, delimiter, row);
+
+                    row_index += 1;
+                }
+
+                eresult = format!("\"expanded\": [{}]", eresult);
+
+                format!("{{{},{}}}", cresult, eresult)
+            }
+            _ => {
+                let schedules = self.event_schedules();
+                let schedules_shown = schedules.iter().any(|o| !o.is_empty());
+
+                let mut result = String::from("");
+                let mut row_index: usize = 0;
+                loop {
+                    let mut row = String::from("");
+                    let mut next_name = String::from("");
+                    match calc_mgr.list_cashflow().list_event() {
+                        None => {}
+                        Some(o) => {
+                            if !o.get_element(row_index as usize) {
+                                break;
+                            }
+
+                            row = json.serialize_extension(
+                                o.elem_extension(),
+                                dec!(0.0),
+                                o.frequency(),
+                                false,
+                                true,
+                            );
+                            next_name = String::from(o.next_name());
+                        }
+                    }
+
+                    let mut next_name_seen = false;
+                    let orig_index = list_column.index();
+                    let mut index: usize = 0;
+                    loop {
+                        if !list_column.get_element(index) {
+                            break;
+                        }
+                        let val = self.engine.event_value(list_column.column());
+                        row = format!("{},\"{}\":\"{}\"", row, list_column.col_name(), val);
+                        if !next_name_seen {
+                            next_name_seen = list_column.col_name() == "Next-name";
+                        }
+                        index += 1;
+                    }
+                    list_column.get_element(orig_index);
+
+                    if !next_name_seen {
+                        row = format!("{},\"{}\":\"{}\"", row, "Next-name", next_name);
+                    }
+
+                    if schedules_shown {
+                        row = format!(
+                            "{},\"{}\":\"{}\"",
+                            row,
+                            COL_SCHEDULE,
+                            schedules.get(row_index).map_or("", |o| o.as_str())
+                        );
+                    }
+
+                    let delimiter = if row_index == 0 { "" } else { "," };
+                    result = format!("{}{}{{{}}}", result, delimiter, row);
+
+                    row_index += 1;
+                }
+
+                format!("[{}]", result)
+            }
+        }
+    }
+
+    /// Validate a skip periods mask of an event.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The cashflow index.
+    /// * `index` - The event index.
+    /// * `mask` - The skip periods mask (e.g., the event's
+    ///   Skip-periods column value).
+    ///
+    /// # Return
+    ///
+    /// * A readable error or an empty string if the mask is valid
+    ///   (or the event is not found).
+
+    pub fn validate_skip_periods(&self, cf_index: i32, index: u32, mask: &str) -> String {
+        if cf_index < 0 {
+            return String::from("");
+        }
+
+        {
+            let calc_mgr = self.engine.calc_mgr();
+            if !calc_mgr.list_cashflow().get_element(cf_index as usize) {
+                return String::from("");
+            }
+        }
+
+        let periods = match self.event_period_dates(index as usize) {
+            None => return String::from(""),
+            Some(o) => o.len(),
+        };
+
+        match SkipPeriodsUtility::validate(mask.trim(), periods) {
+            Err(e) => self.skip_periods_error(&e),
+            Ok(_o) => String::from(""),
+        }
+    }
+}
+
+/// Wasm amfn engine private implementation.
+impl Engine {
+    /// Adjust the first payment of a payment event. The first
+    /// payment is split into its own event.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The event index.
+    /// * `event_date` - Date of the event.
+    /// * `sort_order` - Sort order of the event.
+    /// * `payment` - Value of the event.
+    /// * `periods` - Number of periods of the event.
+    /// * `intervals` - Number of intervals of frequency.
+    /// * `frequency` - Frequency of the event.
+    /// * `extension` - Extension of the event.
+    /// * `adjustment` - Amount added to the first payment.
+    ///
+    /// # Return
+    ///
+    /// * True if successful, otherwise false.
+
+    #[allow(clippy::too_many_arguments)]
+    fn adjust_first_payment(
+        &self,
+        index: usize,
+        event_date: usize,
+        sort_order: usize,
+        payment: Decimal,
+        periods: usize,
+        intervals: usize,
+        frequency: FrequencyType,
+        extension: ElemExtension,
+        adjustment: Decimal,
+    ) -> bool {
+        if periods < 2 {
+            return self.set_event_column_decimal(index, "Value", payment + adjustment)
+                && self.balance_events();
+        }
+
+        let next_date =
+            CoreUtility::date_newi(event_date, event_date, frequency, intervals as i32, false);
+
+        self.set_event_column_decimal(index, "Periods", Decimal::from(periods - 1))
+            && self.set_event_column(
+                index,
+                "Date",
+                self.engine.format_date_out(next_date).as_str(),
+            )
+            && self.add_event(
+                event_date,
+                sort_order,
+                self.round_currency(payment + adjustment),
+                1,
+                intervals,
+                frequency,
+                extension,
+            )
+            && self.balance_events()
+    }
+
+    /// Adjust the last payment of a payment event. The last
+    /// payment is split into its own event.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The event index.
+    /// * `event_date` - Date of the event.
+    /// * `sort_order` - Sort order of the event.
+    /// * `payment` - Value of the event.
+    /// * `periods` - Number of periods of the event.
+    /// * `intervals` - Number of intervals of frequency.
+    /// * `frequency` - Frequency of the event.
+    /// * `extension` - Extension of the event.
+    /// * `adjustment` - Amount added to the last payment.
+    ///
+    /// # Return
+    ///
+    /// * True if successful, otherwise false.
+
+    #[allow(clippy::too_many_arguments)]
+    fn adjust_last_payment(
+        &self,
+        index: usize,
+        event_date: usize,
+        sort_order: usize,
+        payment: Decimal,
+        periods: usize,
+        intervals: usize,
+        frequency: FrequencyType,
+        extension: ElemExtension,
+        adjustment: Decimal,
+    ) -> bool {
+        if periods < 2 {
+            return self.set_event_column_decimal(index, "Value", payment + adjustment)
+                && self.balance_events();
+        }
+
+        let last_date = CoreUtility::date_newi(
+            event_date,
+            event_date,
+            frequency,
+            ((periods - 1) * intervals) as i32,
+            false,
+        );
+
+        self.set_event_column_decimal(index, "Periods", Decimal::from(periods - 1))
+            && self.add_event(
+                last_date,
+                sort_order,
+                self.round_currency(payment + adjustment),
+                1,
+                intervals,
+                frequency,
+                extension,
+            )
+            && self.balance_events()
+    }
+
+    /// Add an event to the selected cashflow's event list.
+    ///
+    /// # Arguments
+    ///
+    /// * `event_date` - Date of the event.
+    /// * `sort_order` - Sort order of the event.
+    /// * `value` - Value of the event.
+    /// * `periods` - Number of periods of the event.
+    /// * `intervals` - Number of intervals of frequency.
+    /// * `frequency` - Frequency of the event.
+    /// * `extension` - Extension of the event.
+    ///
+    /// # Return
+    ///
+    /// * True if successful, otherwise false.
+
+    #[allow(clippy::too_many_arguments)]
+    fn add_event(
+        &self,
+        event_date: usize,
+        sort_order: usize,
+        value: Decimal,
+        periods: usize,
+        intervals: usize,
+        frequency: FrequencyType,
+        extension: ElemExtension,
+    ) -> bool {
+        match self
+            .engine
+            .calc_mgr_mut()
+            .list_cashflow_mut()
+            .list_event_mut()
+        {
+            None => false,
+            Some(o) => o.add_event(
+                event_date, "", sort_order, value, "", false, periods, "", 0, None, intervals,
+                frequency, extension, None, None, "", "",
+            ),
+        }
+    }
+
+    /// Evaluate the conditions of the conditional events of the
+    /// selected cashflow and balance the cashflow. The skip periods
+    /// of the conditional events (merged with the skip periods of
+    /// their recurrence schedules and the skip periods entered by
+    /// hand) are updated and the cashflow is rebalanced until the
+    /// conditions settle.
+    ///
+    /// # Return
+    ///
+    /// * True if successful, otherwise false (including
+    ///   conditions that do not settle).
+
+    fn apply_conditions(&self) -> bool {
+        let count = {
+            let calc_mgr = self.engine.calc_mgr();
+            calc_mgr
+                .list_cashflow()
+                .list_event()
+                .map_or(0, |o| o.count())
+        };
+
+        let conditions: Vec<(usize, ElemCondition)> = (0..count)
+            .filter_map(|o| {
+                ElemCondition::parse(
+                    self.event_descriptor(o, GROUP_CONDITION, NAME_CONDITION_RULE)
+                        .as_str(),
+                )
+                .map(|o2| (o, o2))
+            })
+            .collect();
+
+        if conditions.is_empty() {
+            return true;
+        }
+
+        let mut base_masks: Vec<String> = Vec::new();
+        for (index, _condition) in conditions.iter() {
+            match (self.schedule_mask(*index), self.condition_mask(*index)) {
+                (Some(o1), Some(o2)) => base_masks.push(SkipPeriodsUtility::merge(&o1, &o2)),
+                _ => return false,
+            }
+        }
+        let mut masks = base_masks.clone();
+
+        for _ in 0..MAX_CONDITION_PASSES {
+            for (((index, _condition), mask), base_mask) in
+                conditions.iter().zip(masks.iter()).zip(base_masks.iter())
+            {
+                if !self.set_event_column(
+                    *index,
+                    "Skip-periods",
+                    SkipPeriodsUtility::merge(base_mask, mask).as_str(),
+                ) {
+                    return false;
+                }
+            }
+
+            if self.engine.balance_cashflow().is_err() {
+                return false;
+            }
+
+            let new_masks = match self.condition_masks(&conditions) {
+                None => return false,
+                Some(o) => o,
+            };
+            if new_masks == masks {
+                return true;
+            }
+            masks = new_masks;
+        }
+
+        false
+    }
+
+    /// Apply the decimal digits and rounding mode of the selected
+    /// cashflow to the balance rounding (round-balance and
+    /// round-decimal-digits) of its interest change events. Events
+    /// whose balance rounding was set by hand (see
+    /// set_extension_values) are left unchanged.
+    ///
+    /// # Return
+    ///
+    /// * True if successful, otherwise false.
+
+    fn apply_currency_rounding(&self) -> bool {
+        let decimal_digits = self.cashflow_decimal_digits();
+        let mode = CurrencyUtility::rounding_mode_mnemonic(self.cashflow_rounding_mode());
+
+        let mut extensions: Vec<(usize, String)> = Vec::new();
+        {
+            let calc_mgr = self.engine.calc_mgr();
+            let json = CalcJsonSerialize::new(self.engine.calc_manager());
+            if let Some(o) = calc_mgr.list_cashflow().list_event() {
+                let orig_index = o.index();
+                let mut index: usize = 0;
+                while o.get_element(index) {
+                    if o.elem_extension().extension_type() == ExtensionType::InterestChange {
+                        extensions.push((
+                            index,
+                            json.serialize_extension(
+                                o.elem_extension(),
+                                dec!(0.0),
+                                o.frequency(),
+                                false,
+                                true,
+                            ),
+                        ));
+                    }
+                    index += 1;
+                }
+                o.get_element(orig_index);
+            }
+        }
+
+        for (index, text) in extensions.iter() {
+            if !self
+                .event_descriptor(*index, GROUP_ROUNDING, NAME_ROUNDING_OVERRIDE)
+                .is_empty()
+            {
+                continue;
+            }
+
+            let extension = match JSON::parse(format!("{{{}}}", text).as_str())
+                .and_then(|o| Reflect::get(&o, &JsValue::from_str("extension")))
+            {
+                Err(_e) => return false,
+                Ok(o) => o,
+            };
+            let interest_change =
+                match Reflect::get(&extension, &JsValue::from_str("interest-change")) {
+                    Err(_e) => return false,
+                    Ok(o) => o,
+                };
+
+            if Reflect::set(
+                &interest_change,
+                &JsValue::from_str("round-balance"),
+                &JsValue::from_str(mode),
+            )
+            .is_err()
+                || Reflect::set(
+                    &interest_change,
+                    &JsValue::from_str("round-decimal-digits"),
+                    &JsValue::from_str(decimal_digits.to_string().as_str()),
+                )
+                .is_err()
+            {
+                return false;
+            }
+
+            let ext_param = match JSON::stringify(&extension) {
+                Err(_e) => return false,
+                Ok(o) => String::from(o),
+            };
+
+            let ext = {
+                let json = CalcJsonDeserialize::new(self.engine.calc_manager());
+                match json.deserialize_extension_from_str(ext_param.as_str()) {
+                    Err(_e) => return false,
+                    Ok(o) => o,
+                }
+            };
+
+            if !self.engine.set_extension_values(*index, &ext) {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Apply the currency rounding (see apply_currency_rounding) to
+    /// a range of cashflows and rebalance the cashflows with events.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_indexes` - The cashflow indexes.
+    ///
+    /// # Return
+    ///
+    /// * True if successful, otherwise false.
+
+    fn apply_cashflow_rounding(&self, cf_indexes: Range<usize>) -> bool {
+        for cf_index in cf_indexes {
+            if !self.engine.calc_mgr().list_cashflow().get_element(cf_index)
+                || !self.apply_currency_rounding()
+            {
+                return false;
+            }
+
+            let events = self
+                .engine
+                .calc_mgr()
+                .list_cashflow()
+                .list_event()
+                .map_or(0, |o| o.count());
+            if events == 0 {
+                continue;
+            }
+
+            self.engine.evaluate_cashflow_event_type_all();
+
+            if !self.balance_events() {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Return the amortization rows of the selected cashflow.
+    ///
+    /// # Arguments
+    ///
+    /// * `expanded` - Return the expanded (rather than compressed) rows.
+    ///
+    /// # Return
+    ///
+    /// * See description or None if the output cannot be created.
+
+    fn amortization_rows(&self, expanded: bool) -> Option<Vec<ElemAmRow>> {
+        let calc_mgr = self.engine.calc_mgr();
+
+        match calc_mgr
+            .list_cashflow()
+            .create_cashflow_output(!expanded, false, false, false, true)
+        {
+            Err(_e) => None,
+            Ok(o) => Some(ElemAmRow::from_list_all(&o)),
+        }
+    }
+
+    /// Balance the selected cashflow. Every change to the events
+    /// of a cashflow is balanced here so that the events with
+    /// relative dates are reflowed (see reflow_relative_dates) and
+    /// the conditions of its conditional events are evaluated (see
+    /// apply_conditions). Relative date errors (e.g., a removed
+    /// anchor event) leave the relative events in place.
+    ///
+    /// # Return
+    ///
+    /// * True if successful, otherwise false.
+
+    fn balance_events(&self) -> bool {
+        !matches!(self.reflow_relative_dates(), Ok(false))
+            && self.apply_conditions()
+            && self.engine.balance_cashflow().is_ok()
+    }
+
+    /// Calculate the initial payment of an event with growth terms
+    /// that leaves the target final balance (see cashflow_target).
+    /// Trial payments are balanced until the estimate (see
+    /// ElemGrowth::initial_payment) converges. The original payment
+    /// is restored if the estimate does not converge.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The event index.
+    ///
+    /// # Return
+    ///
+    /// * See description or None if not successful.
+
+    fn calculate_growth_value(&self, index: usize) -> Option<Decimal> {
+        let orig_value = {
+            let calc_mgr = self.engine.calc_mgr();
+            let list_event = calc_mgr.list_cashflow().list_event()?;
+            if !list_event.get_element(index) {
+                return None;
+            }
+            list_event.value()
+        };
+        let target = self.cashflow_target();
+
+        if !self.balance_events() {
+            return None;
+        }
+        let mut value = orig_value;
+        let mut balance = self.final_balance()? - target;
+        let mut trial_value =
+            self.round_currency(value + (value.abs() / dec!(100.0)).max(dec!(1.0)));
+
+        if balance.is_zero() {
+            return Some(value);
+        }
+
+        for _ in 0..MAX_GROWTH_TRIALS {
+            if !self.set_event_column_decimal(index, "Value", trial_value) {
+                return None;
+            }
+            if !self.balance_events() {
+                return None;
+            }
+            let trial_balance = self.final_balance()? - target;
+            if trial_balance.is_zero() {
+                return Some(trial_value);
+            }
+
+            let next_value =
+                match ElemGrowth::initial_payment(value, balance, trial_value, trial_balance) {
+                    None => break,
+                    Some(o) => self.round_currency(o),
+                };
+            if next_value == trial_value {
+                return Some(trial_value);
+            }
+
+            value = trial_value;
+            balance = trial_balance;
+            trial_value = next_value;
+        }
+
+        if self.set_event_column_decimal(index, "Value", orig_value) {
+            self.balance_events();
+        }
+
+        None
+    }
+
+    /// Return the skip periods entered by hand on a conditional
+    /// event. The event's skip periods are kept as a descriptor
+    /// when its condition is first applied (see apply_conditions).
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The event index.
+    ///
+    /// # Return
+    ///
+    /// * See description or None if the event is not found.
+
+    fn condition_mask(&self, index: usize) -> Option<String> {
+        let mask = self.event_descriptor(index, GROUP_CONDITION, NAME_CONDITION_MASK);
+        if !mask.is_empty() {
+            return Some(mask);
+        }
+
+        let mask = {
+            let calc_mgr = self.engine.calc_mgr();
+            let list_event = calc_mgr.list_cashflow().list_event()?;
+            if !list_event.get_element(index) {
+                return None;
+            }
+            self.event_column_value("Skip-periods").unwrap_or_default()
+        };
+        let mask = if mask.is_empty() {
+            String::from("0")
+        } else {
+            mask
+        };
+
+        if !self.set_event_descriptor(index, GROUP_CONDITION, NAME_CONDITION_MASK, mask.as_str()) {
+            return None;
+        }
+
+        Some(mask)
+    }
+
+    /// Evaluate the conditions of conditional events for each of
+    /// their periods and create their skip periods masks.
+    ///
+    /// # Arguments
+    ///
+    /// * `conditions` - The event indexes and conditions.
+    ///
+    /// # Return
+    ///
+    /// * The mask of each event or None if not successful.
+
+    fn condition_masks(&self, conditions: &[(usize, ElemCondition)]) -> Option<Vec<String>> {
+        let rows = self.amortization_rows(true)?;
+        let flows = ElemCashFlow::from_rows(&rows);
+        let records = DelinquencyUtility::records(
+            &flows,
+            &self.cashflow_actual_payments(),
+            &self.cashflow_delinquency_rules(),
+            rows.last().map_or(0, |o| o.event_date()),
+            self.cashflow_decimal_digits(),
+        );
+
+        let mut result: Vec<String> = Vec::new();
+        for (index, condition) in conditions.iter() {
+            let (sort_order, value) = {
+                let calc_mgr = self.engine.calc_mgr();
+                let list_event = calc_mgr.list_cashflow().list_event()?;
+                if !list_event.get_element(*index) {
+                    return None;
+                }
+                (list_event.sort_order(), list_event.value())
+            };
+
+            let dates = self.event_period_dates(*index)?;
+            let results: Vec<bool> = dates
+                .iter()
+                .enumerate()
+                .map(|(sequence, event_date)| {
+                    let expr = ConditionUtility::substitute(
+                        condition.expression(),
+                        &[
+                            (
+                                SYM_BALANCE,
+                                ConditionUtility::balance_before(&rows, *event_date, sort_order)
+                                    .to_string(),
+                            ),
+                            (SYM_DATE, event_date.to_string()),
+                            (SYM_SEQUENCE, sequence.to_string()),
+                            (SYM_VALUE, value.to_string()),
+                            (
+                                SYM_PAYMENTS,
+                                ConditionUtility::payments_before(&flows, *event_date).to_string(),
+                            ),
+                            (
+                                SYM_ON_TIME,
+                                ConditionUtility::on_time_before(&records, *event_date).to_string(),
+                            ),
+                        ],
+                    );
+                    match self.expand_expression(None, expr.as_str()) {
+                        Err(_) => false,
+                        Ok(o) => ConditionUtility::holds(
+                            self.symbol_value(None, o.as_str(), false).as_str(),
+                        ),
+                    }
+                })
+                .collect();
+
+            result.push(condition.mask(&results));
+        }
+
+        Some(result)
+    }
+
+    /// Return the decimal digits of the selected cashflow. Decimal
+    /// digits set explicitly in the cashflow preferences (see
+    /// set_preferences) are used, otherwise the decimal digits of the
+    /// currency of the cashflow's locale (or the user preferences).
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    fn cashflow_decimal_digits(&self) -> u32 {
+        if let Ok(o) = self
+            .cashflow_descriptor(GROUP_ROUNDING, NAME_DECIMAL_DIGITS)
+            .parse::<u32>()
+        {
+            return o;
+        }
+
+        if let Some(o) = CurrencyUtility::decimal_digits(self.cashflow_currency_code().as_str()) {
+            return o;
+        }
+
+        let user_decimal_digits = self.engine.calc_mgr().preferences().decimal_digits();
+        if user_decimal_digits > 0 {
+            return user_decimal_digits as u32;
+        }
+
+        amfnengine::DEFAULT_DECIMAL_DIGITS as u32
+    }
+
+    /// Return the actual payments recorded against the selected
+    /// cashflow sorted by date.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    fn cashflow_actual_payments(&self) -> Vec<ElemActualPayment> {
+        DailyInterestUtility::parse_payments(
+            self.cashflow_descriptor(GROUP_ACTUAL, NAME_ACTUAL_PAYMENTS)
+                .as_str(),
+        )
+    }
+
+    /// Return the grace period and late-fee rules of the selected
+    /// cashflow (no grace period and no late fees if not set).
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    fn cashflow_delinquency_rules(&self) -> ElemDelinquencyRules {
+        ElemDelinquencyRules::parse(
+            self.cashflow_descriptor(GROUP_DELINQUENCY, NAME_DELINQUENCY_RULES)
+                .as_str(),
+        )
+        .unwrap_or_default()
+    }
+
+    /// Return the revolving line of credit terms of the selected cashflow.
+    ///
+    /// # Return
+    ///
+    /// * See description or None if not set.
+
+    fn cashflow_revolving_terms(&self) -> Option<ElemRevolvingTerms> {
+        ElemRevolvingTerms::parse(
+            self.cashflow_descriptor(GROUP_REVOLVING, NAME_REVOLVING_TERMS)
+                .as_str(),
+        )
+    }
+
+    /// Return the construction loan conversion date of the selected cashflow.
+    ///
+    /// # Return
+    ///
+    /// * See description or zero if not set.
+
+    fn cashflow_conversion_date(&self) -> usize {
+        self.cashflow_descriptor(GROUP_CONSTRUCTION, NAME_CONVERSION_DATE)
+            .replace('-', "")
+            .parse::<usize>()
+            .unwrap_or(0)
+    }
+
+    /// Return the interest allocation method of the selected
+    /// cashflow (actuarial if not set).
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    fn cashflow_allocation_method(&self) -> AllocationMethod {
+        AllocationUtility::parse_method(
+            self.cashflow_descriptor(GROUP_ALLOCATION, NAME_ALLOCATION_METHOD)
+                .as_str(),
+        )
+        .unwrap_or(AllocationMethod::Actuarial)
+    }
+
+    /// Return the rounding residue policy of the selected cashflow
+    /// (adjust the last payment if not set).
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    fn cashflow_residue_policy(&self) -> ResiduePolicy {
+        ResidueUtility::parse_policy(
+            self.cashflow_descriptor(GROUP_ROUNDING, NAME_RESIDUE_POLICY)
+                .as_str(),
+        )
+        .unwrap_or(ResiduePolicy::AdjustLast)
+    }
+
+    /// Return the rounding mode of the selected cashflow
+    /// (bankers rounding if not set).
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    fn cashflow_rounding_mode(&self) -> RoundingMode {
+        CurrencyUtility::parse_rounding_mode(
+            self.cashflow_descriptor(GROUP_ROUNDING, NAME_ROUNDING_MODE)
+                .as_str(),
+        )
+        .unwrap_or(RoundingMode::Bankers)
+    }
+
+    /// Return the target final balance of the selected cashflow
+    /// (or the user preferences if the cashflow has no preferences).
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    fn cashflow_target(&self) -> Decimal {
+        let calc_mgr = self.engine.calc_mgr();
+
+        match calc_mgr.list_cashflow().preferences() {
+            None => calc_mgr.preferences().target(),
+            Some(o) => o.target(),
+        }
+    }
+
+    /// Return the number of cashflows in the cashflow list.
+    /// The selected cashflow is not preserved.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    fn cashflow_count(&self) -> usize {
+        let calc_mgr = self.engine.calc_mgr();
+        let mut count: usize = 0;
+
+        while calc_mgr.list_cashflow().get_element(count) {
+            count += 1;
+        }
+
+        count
+    }
+
+    /// Parse a displayed currency or decimal value.
+    ///
+    /// # Arguments
+    ///
+    /// * `display_val` - The displayed value.
+    ///
+    /// # Return
+    ///
+    /// * See description or None if the value is not numeric.
+
+    fn display_number(&self, display_val: &str) -> Option<Decimal> {
+        if display_val.trim().is_empty() {
+            return None;
+        }
+
+        match self
+            .engine
+            .format_currency_in(display_val)
+            .parse::<Decimal>()
+        {
+            Ok(o) => Some(o),
+            Err(_e) => self
+                .engine
+                .format_decimal_in(display_val)
+                .parse::<Decimal>()
+                .ok(),
+        }
+    }
+
+    /// Return the delinquency column values of an amortization row.
+    ///
+    /// # Arguments
+    ///
+    /// * `flows` - The scheduled cash flows.
+    /// * `payments` - The actual payments.
+    /// * `rules` - The grace period and late-fee rules.
+    /// * `date` - Date of the amortization row.
+    /// * `decimal_digits` - Number of decimal digits for rounding.
+    ///
+    /// # Return
+    ///
+    /// * The json name/value pairs of the columns.
+
+    fn delinquency_columns(
+        flows: &[ElemCashFlow],
+        payments: &[ElemActualPayment],
+        rules: &ElemDelinquencyRules,
+        date: usize,
+        decimal_digits: u32,
+    ) -> String {
+        let summary = DelinquencyUtility::summary(&DelinquencyUtility::records(
+            flows,
+            payments,
+            rules,
+            date,
+            decimal_digits,
+        ));
+
+        format!(
+            "\"Past-due\":\"{}\",\"Days-delinquent\":\"{}\",\"Late-fees\":\"{}\"",
+            summary.past_due(),
+            summary.days_delinquent(),
+            summary.late_fees()
+        )
+    }
+
+    /// Return the depreciation column values of an amortization row.
+    ///
+    /// # Arguments
+    ///
+    /// * `assets` - The depreciable assets.
+    /// * `rows` - The depreciation schedule.
+    /// * `prior_date` - Date of the prior amortization row (zero if none).
+    /// * `date` - Date of the amortization row.
+    ///
+    /// # Return
+    ///
+    /// * The json name/value pairs of the columns.
+
+    fn depreciation_columns(
+        assets: &[ElemDepreciationAsset],
+        rows: &[ElemDepreciationRow],
+        prior_date: usize,
+        date: usize,
+    ) -> String {
+        let accumulated = DepreciationUtility::accumulated(rows, date);
+
+        format!(
+            "\"Depreciation\":\"{}\",\"Accumulated-depreciation\":\"{}\",\"Book-value\":\"{}\"",
+            DepreciationUtility::period(rows, prior_date, date),
+            accumulated,
+            DepreciationUtility::cost(assets, date) - accumulated
+        )
+    }
+
+    /// Return the depreciable assets of the selected cashflow (the
+    /// events with a depreciation method descriptor). The cost of
+    /// each asset is the absolute value of the event.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    fn depreciation_assets(&self) -> Vec<ElemDepreciationAsset> {
+        let calc_mgr = self.engine.calc_mgr();
+        let mut assets: Vec<ElemDepreciationAsset> = Vec::new();
+
+        let list_event = match calc_mgr.list_cashflow().list_event() {
+            None => return assets,
+            Some(o) => o,
+        };
+
+        let orig_index = list_event.index();
+        let mut index: usize = 0;
+        loop {
+            if !list_event.get_element(index) {
+                break;
+            }
+
+            if let Some(o) = list_event.list_descriptor() {
+                let orig_desc_index = o.index();
+                let mut desc_index: usize = 0;
+                loop {
+                    if !o.get_element(desc_index) {
+                        break;
+                    }
+                    if o.group() == GROUP_DEPRECIATION
+                        && o.name() == NAME_DEPRECIATION_METHOD
+                        && o.desc_type() == "custom"
+                    {
+                        if let Some(o2) = ElemDepreciation::parse(o.value().as_str()) {
+                            assets.push(ElemDepreciationAsset::new(
+                                list_event.event_date(),
+                                list_event.value().abs(),
+                                o2,
+                            ));
+                        }
+                        break;
+                    }
+                    desc_index += 1;
+                }
+                o.get_element(orig_desc_index);
+            }
+
+            index += 1;
+        }
+        list_event.get_element(orig_index);
+
+        assets
+    }
+
+    /// Return the currency code of the selected cashflow's locale
+    /// (or the user preferences if not set by the cashflow). The
+    /// cashflow locale is selected while reading the currency code.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    fn cashflow_currency_code(&self) -> String {
+        let calc_mgr = self.engine.calc_mgr();
+        let locale = calc_mgr.list_locale();
+
+        let locale_str = match calc_mgr.list_cashflow().preferences() {
+            Some(o) if !o.locale_str().is_empty() => o.locale_str(),
+            _ => calc_mgr.preferences().locale_str(),
+        };
+
+        let orig_locale_str = String::from(locale.cashflow_locale().locale_str());
+        locale.select_cashflow_locale(locale_str);
+        let result = String::from(locale.cashflow_locale().currency_code());
+        locale.select_cashflow_locale(orig_locale_str.as_str());
+
+        result
+    }
+
+    /// Convert amortization rows from the selected cashflow's
+    /// currency at the exchange rate of each row's date.
+    ///
+    /// # Arguments
+    ///
+    /// * `rows` - The amortization rows.
+    /// * `currency_code` - The currency code converted to.
+    ///
+    /// # Return
+    ///
+    /// * See description or None if an exchange rate is not available.
+
+    fn convert_rows(&self, rows: &[ElemAmRow], currency_code: &str) -> Option<Vec<ElemAmRow>> {
+        let from_code = self.cashflow_currency_code();
+        let exchange_rates = self.exchange_rates.borrow();
+        let mut result: Vec<ElemAmRow> = Vec::new();
+
+        for row in rows.iter() {
+            let rate = exchange_rates.rate(from_code.as_str(), currency_code, row.event_date())?;
+            result.push(row.scaled(rate));
+        }
+
+        Some(result)
+    }
+
+    /// Create a principal change extension.
+    ///
+    /// # Arguments
+    ///
+    /// * `increase` - Principal increase (otherwise decrease).
+    /// * `eom` - Adjust successive dates to end of month.
+    ///
+    /// # Return
+    ///
+    /// * See description or None if not successful.
+
+    fn principal_extension(&self, increase: bool, eom: bool) -> Option<ElemExtension> {
+        let ext = format!(
+            "{{\"principal-change\":{{\"principal-type\":\"{}\",\"eom\":{},\
+            \"principal-first\":false,\"statistics\":false,\"auxiliary\":false,\"passive\":false}}}}",
+            if increase { "increase" } else { "decrease" },
+            eom
+        );
+
+        let json = CalcJsonDeserialize::new(self.engine.calc_manager());
+        match json.deserialize_extension_from_str(ext.as_str()) {
+            Err(_e) => None,
+            Ok(o) => Some(o),
+        }
+    }
+
+    /// Create an actuarial interest change extension with a periodic
+    /// day count basis.
+    ///
+    /// # Return
+    ///
+    /// * See description or None if not successful.
+
+    fn interest_extension(&self) -> Option<ElemExtension> {
+        let ext = "{\"interest-change\":{\"interest-method\":\"actuarial\",\
+            \"day-count-basis\":\"periodic\",\"days-in-year\":360}}";
+
+        let json = CalcJsonDeserialize::new(self.engine.calc_manager());
+        match json.deserialize_extension_from_str(ext) {
+            Err(_e) => None,
+            Ok(o) => Some(o),
+        }
+    }
+
+    /// Consolidate the selected cashflows into a portfolio
+    /// (see portfolio_values).
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_indexes` - The cashflow indexes separated by '|'.
+    /// * `as_of_date` - The date of the weighted averages.
+    /// * `currency_code` - The reporting currency code (empty to
+    ///   leave the amounts in each cashflow's currency).
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    fn portfolio_json(&self, cf_indexes: &str, as_of_date: &str, currency_code: &str) -> String {
+        let mut series: Vec<Vec<ElemAmRow>> = Vec::new();
+
+        for token in cf_indexes.split('|') {
+            let cf_index = match token.trim().parse::<usize>() {
+                Err(_e) => return String::from(""),
+                Ok(o) => o,
+            };
+
+            if !self.engine.calc_mgr().list_cashflow().get_element(cf_index) {
+                return String::from("");
+            }
+
+            let rows = match self.amortization_rows(true) {
+                None => return String::from(""),
+                Some(o) => o,
+            };
+
+            if currency_code.is_empty() {
+                series.push(rows);
+            } else {
+                match self.convert_rows(&rows, currency_code) {
+                    None => return String::from(""),
+                    Some(o) => series.push(o),
+                }
+            }
+        }
+
+        let as_of = if as_of_date.is_empty() {
+            series
+                .iter()
+                .filter_map(|o| o.first().map(|row| row.event_date()))
+                .max()
+                .unwrap_or(0)
+        } else {
+            CoreUtility::parse_date(self.engine.format_date_in(as_of_date).as_str())
+        };
+
+        let positions: Vec<ElemPortfolioPosition> = series
+            .iter()
+            .map(|o| ElemPortfolioPosition::new(o, as_of))
+            .collect();
+
+        let mut rows = String::from("");
+        for (row_index, row) in PortfolioUtility::consolidate(&series).iter().enumerate() {
+            let delimiter = if row_index == 0 { "" } else { "," };
+            rows = format!(
+                "{}{}{{\"Date\":\"{}\",\"Balance\":\"{}\",\"Interest\":\"{}\",\
+                \"Principal-decrease\":\"{}\",\"Principal-increase\":\"{}\"}}",
+                rows,
+                delimiter,
+                DateUtility::format_date(row.event_date()),
+                row.balance(),
+                row.interest(),
+                row.principal_decrease(),
+                row.principal_increase()
+            );
+        }
+
+        let mut position_values = String::from("");
+        for (position_index, position) in positions.iter().enumerate() {
+            let delimiter = if position_index == 0 { "" } else { "," };
+            position_values = format!(
+                "{}{}{{\"Balance\":\"{}\",\"Rate\":\"{}\",\"Maturity\":\"{}\"}}",
+                position_values,
+                delimiter,
+                position.balance(),
+                position.rate(),
+                position.maturity()
+            );
+        }
+
+        format!(
+            "{{\"as-of-date\":\"{}\",\"rows\":[{}],\"positions\":[{}],\
+            \"weighted-average-rate\":\"{}\",\"weighted-average-maturity\":\"{}\"}}",
+            DateUtility::format_date(as_of),
+            rows,
+            position_values,
+            PortfolioUtility::weighted_average(&positions, &|o| o.rate())
+                .round_dp(RATE_DECIMAL_DIGITS),
+            PortfolioUtility::weighted_average(&positions, &|o| o.maturity()).round_dp(2)
+        )
+    }
+
+    /// Return the reporting currency code, defaulting to the
+    /// selected cashflow's cross rate code.
+    ///
+    /// # Arguments
+    ///
+    /// * `currency_code` - The requested currency code (may be empty).
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    fn reporting_currency_code(&self, currency_code: &str) -> String {
+        if !currency_code.trim().is_empty() {
+            return currency_code.trim().to_uppercase();
+        }
+
+        match self.engine.calc_mgr().list_cashflow().preferences() {
+            None => String::from(""),
+            Some(o) => o.cross_rate_code().trim().to_uppercase(),
+        }
+    }
+
+    /// Round a currency value with the selected cashflow's
+    /// decimal digits and rounding mode.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value to round.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    fn round_currency(&self, value: Decimal) -> Decimal {
+        CurrencyUtility::round(
+            value,
+            self.cashflow_decimal_digits(),
+            self.cashflow_rounding_mode(),
+        )
+    }
+
+    /// Reflow the events of the selected cashflow whose dates are
+    /// relative to other events. Anchor events are reflowed before
+    /// the events relative to them.
+    ///
+    /// # Return
+    ///
+    /// * True if successful, false if an event date cannot be set
+    ///   or the relative date error (no event is moved).
+
+    fn reflow_relative_dates(&self) -> Result<bool, RelativeDateError> {
+        let mut events: Vec<(usize, usize, Option<ElemRelativeDate>)> = Vec::new();
+        let mut names: Vec<String> = Vec::new();
+        let count = {
+            let calc_mgr = self.engine.calc_mgr();
+            calc_mgr
+                .list_cashflow()
+                .list_event()
+                .map_or(0, |o| o.count())
+        };
+
+        for index in 0..count {
+            let rule = ElemRelativeDate::parse(
+                self.event_descriptor(index, GROUP_RELATIVE_DATE, NAME_RELATIVE_DATE_RULE)
+                    .as_str(),
+            )
+            .ok();
+
+            let calc_mgr = self.engine.calc_mgr();
+            let list_event = match calc_mgr.list_cashflow().list_event() {
+                None => return Ok(false),
+                Some(o) => o,
+            };
+            let orig_index = list_event.index();
+            if !list_event.get_element(index) {
+                list_event.get_element(orig_index);
+                return Ok(false);
+            }
+            events.push((list_event.event_date(), list_event.sort_order(), rule));
+            names.push(String::from(list_event.event_name()));
+            list_event.get_element(orig_index);
+        }
+
+        let anchors: Vec<Option<String>> = events
+            .iter()
+            .map(|o| o.2.as_ref().map(|o2| String::from(o2.anchor())))
+            .collect();
+        let order = RelativeDateUtility::order(&names, &anchors)?;
+
+        let mut dates: Vec<usize> = events.iter().map(|o| o.0).collect();
+        for (index, anchor) in order.iter() {
+            let rule = match &events[*index].2 {
+                None => continue,
+                Some(o) => o,
+            };
+            let anchor_date = dates[*anchor];
+            let eom = DateUtility::to_naive(anchor_date).map_or(false, |o| {
+                o.day() == DateUtility::days_in_month(o.year(), o.month())
+            });
+            dates[*index] = CoreUtility::date_newi(
+                anchor_date,
+                anchor_date,
+                CoreUtility::get_frequency(rule.frequency()),
+                rule.offset(),
+                eom,
+            );
+        }
+
+        for ((event_date, sort_order, _rule), new_date) in events.iter().zip(dates.iter()) {
+            if *new_date == *event_date {
+                continue;
+            }
+
+            let index = {
+                let calc_mgr = self.engine.calc_mgr();
+                match calc_mgr.list_cashflow().list_event() {
+                    None => return Ok(false),
+                    Some(o) => {
+                        if !o.get_element_by_date(*event_date, *sort_order) {
+                            return Ok(false);
+                        }
+                        o.index()
+                    }
+                }
+            };
+
+            if !self.set_event_column(
+                index,
+                "Date",
+                self.engine.format_date_out(*new_date).as_str(),
+            ) {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Return the readable text of a relative date error.
+    ///
+    /// # Arguments
+    ///
+    /// * `error` - The relative date error.
+    ///
+    /// # Return
+    ///
+    /// * The error's locale resource text followed by its detail.
+
+    fn relative_date_error(&self, error: &RelativeDateError) -> String {
+        let calc_mgr = self.engine.calc_mgr();
+        let text = String::from(
+            calc_mgr
+                .list_locale()
+                .get_resource(RelativeDateUtility::error_key(error)),
+        );
+
+        let detail = RelativeDateUtility::error_detail(error);
+        if detail.is_empty() {
+            text
+        } else {
+            format!("{}: {}", text, detail)
+        }
+    }
+
+    /// Parse a clipboard string (see copy_events).
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The clipboard string.
+    ///
+    /// # Return
+    ///
+    /// * The serialized cashflow json string and the indexes of the
+    ///   first and last copied events or None if not a clipboard string.
+
+    fn parse_clipboard(text: &str) -> Option<(String, usize, usize)> {
+        let value = JSON::parse(text).ok()?;
+        if Reflect::get(&value, &JsValue::from_str("format"))
+            .ok()?
+            .as_string()?
+            != CLIPBOARD_FORMAT
+        {
+            return None;
+        }
+
+        let first = Reflect::get(&value, &JsValue::from_str("first"))
+            .ok()?
+            .as_f64()?;
+        let last = Reflect::get(&value, &JsValue::from_str("last"))
+            .ok()?
+            .as_f64()?;
+        if first < 0.0 || first > last {
+            return None;
+        }
+
+        let cashflow = Reflect::get(&value, &JsValue::from_str("cashflow")).ok()?;
+        let cashflow = JSON::stringify(&cashflow).ok().map(String::from)?;
+
+        Some((cashflow, first as usize, last as usize))
+    }
+
+    /// Add a copy of an event to the selected cashflow. The event
+    /// is removed again if its skip periods cannot be set.
+    ///
+    /// # Arguments
+    ///
+    /// * `copy` - The event copy.
+    /// * `event_date` - The date of the new event.
+    /// * `sort_order` - The sort order of the new event.
+    ///
+    /// # Return
+    ///
+    /// * The index of the new event or None if not successful.
+
+    fn paste_event(
+        &self,
+        copy: &ElemEventCopy,
+        event_date: usize,
+        sort_order: usize,
+    ) -> Option<usize> {
+        {
+            let mut calc_mgr = self.engine.calc_mgr_mut();
+            let list_event = calc_mgr.list_cashflow_mut().list_event_mut()?;
+            if !copy.add_to(list_event, event_date, sort_order) {
+                return None;
+            }
+        }
+
+        let index = self.event_index_by_date(event_date, sort_order)?;
+        if !copy.skip_periods().is_empty()
+            && !self.set_event_column(index, "Skip-periods", copy.skip_periods())
+        {
+            self.remove_event_by_date(event_date, sort_order);
+            return None;
+        }
+
+        Some(index)
+    }
+
+    /// Remove an event of the selected cashflow by its date
+    /// and sort order.
+    ///
+    /// # Arguments
+    ///
+    /// * `event_date` - The event date.
+    /// * `sort_order` - The event sort order.
+    ///
+    /// # Return
+    ///
+    /// * True if successful, otherwise false.
+
+    fn remove_event_by_date(&self, event_date: usize, sort_order: usize) -> bool {
+        let index = match self.event_index_by_date(event_date, sort_order) {
+            None => return false,
+            Some(o) => o,
+        };
+
+        match self
+            .engine
+            .calc_mgr_mut()
+            .list_cashflow_mut()
+            .list_event_mut()
+        {
+            None => false,
+            Some(o) => o.get_element(index) && o.remove(),
+        }
+    }
+
+    /// Return the balance rounding of an interest change extension.
+    ///
+    /// # Arguments
+    ///
+    /// * `ext_param` - The extension json string.
+    ///
+    /// # Return
+    ///
+    /// * The round-balance and round-decimal-digits values or None
+    ///   if the extension is not an interest change.
+
+    fn extension_rounding(ext_param: &str) -> Option<(String, String)> {
+        let value = JSON::parse(ext_param).ok()?;
+        let interest_change = Reflect::get(&value, &JsValue::from_str("interest-change")).ok()?;
+        if !interest_change.is_object() {
+            return None;
+        }
+
+        let field = |name: &str| {
+            Reflect::get(&interest_change, &JsValue::from_str(name))
+                .ok()
+                .and_then(|o| {
+                    o.as_string()
+                        .or_else(|| o.as_f64().map(|o2| o2.to_string()))
+                })
+                .unwrap_or_default()
+        };
+
+        Some((field("round-balance"), field("round-decimal-digits")))
+    }
+
+    /// Load a serialized cashflow json string into a scratch
+    /// cashflow under a name not used by any cashflow. Unlike
+    /// deserialize, the exchange rates of the cashflow are not
+    /// merged and the currency rounding is not applied. Any
+    /// cashflows loaded are removed if not successful.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The serialized json string of one cashflow.
+    ///
+    /// # Return
+    ///
+    /// * The index of the scratch cashflow or None if not successful.
+
+    fn load_scratch_cashflow(&self, text: &str) -> Option<usize> {
+        let count = self.cashflow_count();
+
+        let mut names: Vec<String> = Vec::new();
+        {
+            let calc_mgr = self.engine.calc_mgr();
+            for cf_index in 0..count {
+                if calc_mgr.list_cashflow().get_element(cf_index) {
+                    names.push(String::from(calc_mgr.list_cashflow().name()));
+                }
+            }
+        }
+
+        let name = ClipboardUtility::scratch_name(&names);
+        let text = Engine::rename_serialized_cashflow(text, name.as_str())?;
+
+        let loaded = {
+            let json = CalcJsonDeserialize::new(self.engine.calc_manager());
+            json.deserialize(text).is_ok()
+        };
+
+        if !loaded || self.cashflow_count() != count + 1 {
+            self.remove_cashflows_from(count);
+            return None;
+        }
+
+        Some(count)
+    }
+
+    /// Remove the cashflows from an index through the last cashflow.
+    ///
+    /// # Arguments
+    ///
+    /// * `cf_index` - The index of the first cashflow removed.
+
+    fn remove_cashflows_from(&self, cf_index: usize) {
+        let mut count = self.cashflow_count();
+
+        while count > cf_index {
+            if !self.remove_cashflow((count - 1) as i32) {
+                break;
+            }
+            count -= 1;
+        }
+    }
+
+    /// Set a descriptor of the cashflow preferences within a serialized
+    /// cashflow json string. An empty value removes the descriptor.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The serialized json string.
+    /// * `group` - The descriptor group.
+    /// * `name` - The descriptor name.
+    /// * `value` - The descriptor value.
+    ///
+    /// # Return
+    ///
+    /// * The updated json string or None if not successful.
+
+    fn set_serialized_descriptor(
+        text: &str,
+        group: &str,
+        name: &str,
+        value: &str,
+    ) -> Option<String> {
+        let json = JSON::parse(text).ok()?;
+        let cashflows = Reflect::get(&json, &JsValue::from_str("cashflows")).ok()?;
+        if !Array::is_array(&cashflows) {
+            return None;
+        }
+
+        for cashflow in Array::from(&cashflows).iter() {
+            let mut preferences =
+                Reflect::get(&cashflow, &JsValue::from_str("preferences")).ok()?;
+            if !preferences.is_object() {
+                preferences = Object::new().into();
+                Reflect::set(&cashflow, &JsValue::from_str("preferences"), &preferences).ok()?;
+            }
+
+            let descriptors =
+                Reflect::get(&preferences, &JsValue::from_str("descriptor-list")).ok()?;
+            let descriptors: Array = if Array::is_array(&descriptors) {
+                Array::from(&descriptors)
+                    .iter()
+                    .filter(|o| {
+                        Reflect::get(o, &JsValue::from_str("group"))
+                            .ok()
+                            .and_then(|p| p.as_string())
+                            .map_or(true, |p| p != group)
+                            || Reflect::get(o, &JsValue::from_str("name"))
+                                .ok()
+                                .and_then(|p| p.as_string())
+                                .map_or(true, |p| p != name)
+                    })
+                    .collect()
+            } else {
+                Array::new()
+            };
+
+            if !value.is_empty() {
+                let descriptor = Object::new();
+                for (key, val) in [
+                    ("propagate", JsValue::from_bool(false)),
+                    ("group", JsValue::from_str(group)),
+                    ("name", JsValue::from_str(name)),
+                    ("descriptor-type", JsValue::from_str("custom")),
+                    ("descriptor-code", JsValue::from_str("")),
+                    ("value", JsValue::from_str(value)),
+                ]
+                .iter()
+                {
+                    Reflect::set(&descriptor, &JsValue::from_str(key), val).ok()?;
+                }
+                descriptors.push(&descriptor);
+            }
+
+            Reflect::set(
+                &preferences,
+                &JsValue::from_str("descriptor-list"),
+                &descriptors,
+            )
+            .ok()?;
+        }
+
+        JSON::stringify(&json).ok().map(String::from)
+    }
+
+    /// Rename the cashflows within a serialized cashflow json string.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The serialized json string.
+    /// * `name` - The new cashflow name.
+    ///
+    /// # Return
+    ///
+    /// * The renamed json string or None if not successful.
+
+    fn rename_serialized_cashflow(text: &str, name: &str) -> Option<String> {
+        let value = JSON::parse(text).ok()?;
+        let cashflows = Reflect::get(&value, &JsValue::from_str("cashflows")).ok()?;
+        if !Array::is_array(&cashflows) {
+            return None;
+        }
+
+        for cashflow in Array::from(&cashflows).iter() {
+            Reflect::set(
+                &cashflow,
+                &JsValue::from_str("name"),
+                &JsValue::from_str(name),
+            )
+            .ok()?;
+        }
+
+        JSON::stringify(&value).ok().map(String::from)
+    }
+
+    /// Return the final balance of the selected cashflow.
+    ///
+    /// # Return
+    ///
+    /// * See description or None if not successful.
+
+    fn final_balance(&self) -> Option<Decimal> {
+        let rows = self.amortization_rows(true)?;
+
+        Some(rows.last().map_or(dec!(0.0), |o| o.balance()))
+    }
+
+    /// Find an event column of the selected cashflow by name.
+    ///
+    /// # Arguments
+    ///
+    /// * `col_name` - The column name (e.g., "Periods").
+    ///
+    /// # Return
+    ///
+    /// * The column name index, column type and format or None if not found.
+
+    fn find_event_column(&self, col_name: &str) -> Option<(usize, String, usize)> {
+        let list_column = self.engine.parse_columns(TableType::Event);
+        let mut col_index: usize = 0;
+
+        loop {
+            if !list_column.get_element(col_index) {
+                break;
+            }
+            if list_column.col_name() == col_name {
+                return Some((
+                    list_column.col_name_index(),
+                    String::from(list_column.col_type()),
+                    list_column.format(),
+                ));
+            }
+            col_index += 1;
+        }
+
+        None
+    }
+
+    /// Set an event value through the named event column
+    /// of the selected cashflow.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The event index.
+    /// * `col_name` - The column name (e.g., "Periods").
+    /// * `value` - The value to set (in display format).
+    ///
+    /// # Return
+    ///
+    /// * True if successful, otherwise false.
+
+    fn set_event_column(&self, index: usize, col_name: &str, value: &str) -> bool {
+        let (col_name_index, col_type, _format) = match self.find_event_column(col_name) {
+            None => return false,
+            Some(o) => o,
+        };
+
+        {
+            let calc_mgr = self.engine.calc_mgr();
+            match calc_mgr.list_cashflow().list_event() {
+                None => return false,
+                Some(o) => {
+                    if !o.get_element(index) {
+                        return false;
+                    }
+                }
+            }
+        }
+
+        !self
+            .engine
+            .set_event_value(col_name_index, col_type.as_str(), col_name, index, value)
+            .is_empty()
+    }
+
+    /// Set a numeric event value through the named event column
+    /// of the selected cashflow. The value is formatted according
+    /// to the column's format.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The event index.
+    /// * `col_name` - The column name (e.g., "Value").
+    /// * `value` - The value to set.
+    ///
+    /// # Return
+    ///
+    /// * True if successful, otherwise false.
+
+    fn set_event_column_decimal(&self, index: usize, col_name: &str, value: Decimal) -> bool {
+        let format = match self.find_event_column(col_name) {
+            None => return false,
+            Some(o) => o.2,
+        };
+
+        let display_val = match format {
+            FORMAT_INTEGER => match value.to_usize() {
+                None => return false,
+                Some(o) => self.engine.format_integer_out(o),
+            },
+            FORMAT_DECIMAL => self.engine.format_decimal_out(value),
+            FORMAT_CURRENCY => self.engine.format_currency_out(value),
+            _ => return false,
+        };
+
+        self.set_event_column(index, col_name, display_val.as_str())
+    }
+
+    /// Set a numeric event value through the named event column of
+    /// the selected cashflow's event at a date and sort order.
+    ///
+    /// # Arguments
+    ///
+    /// * `event_date` - Date of the event.
+    /// * `sort_order` - Sort order of the event.
+    /// * `col_name` - The column name (e.g., "Value").
+    /// * `value` - The value to set.
+    ///
+    /// # Return
+    ///
+    /// * True if successful, otherwise false.
+
+    fn set_event_value_at(
+        &self,
+        event_date: usize,
+        sort_order: usize,
+        col_name: &str,
+        value: Decimal,
+    ) -> bool {
+        match self.event_index_by_date(event_date, sort_order) {
+            None => false,
+            Some(o) => self.set_event_column_decimal(o, col_name, value),
+        }
+    }
+
+    /// Expand a recurrence rule into an event. The event's periods,
+    /// frequency, skip periods and date are set from the rule dates
+    /// and the rule is kept as an event descriptor.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The event index.
+    /// * `rule` - The recurrence rule or empty to remove the schedule.
+    ///
+    /// # Return
+    ///
+    /// * The (new) event date and the rule's descriptor value
+    ///   or None if not successful.
+
+    fn schedule_event(&self, index: usize, rule: &str) -> Option<(usize, String)> {
+        let (event_date, periods, intervals) = {
+            let calc_mgr = self.engine.calc_mgr();
+            let list_event = calc_mgr.list_cashflow().list_event()?;
+
+            let orig_index = list_event.index();
+            if !list_event.get_element(index) {
+                list_event.get_element(orig_index);
+                return None;
+            }
+            let result = (
+                list_event.event_date(),
+                list_event.periods(),
+                list_event.intervals(),
+            );
+            list_event.get_element(orig_index);
+
+            result
+        };
+
+        if rule.trim().is_empty() {
+            if !self.set_event_descriptor(index, GROUP_SCHEDULE, NAME_SCHEDULE_RULE, "")
+                || !self.set_event_column(index, "Skip-periods", "0".repeat(periods).as_str())
+                || !self.balance_events()
+            {
+                return None;
+            }
+            return Some((event_date, String::from("")));
+        }
+
+        let mut recurrence = ElemRecurrence::parse(rule)?;
+        if recurrence.start_date() == 0 {
+            recurrence.set_start_date(event_date);
+        }
+        let dates = recurrence.dates(periods);
+        if recurrence.count() == 0 && recurrence.until() == 0 {
+            recurrence.set_count(dates.len());
+        }
+
+        let first = *dates.first()?;
+        let last = *dates.last()?;
+        let eom = DateUtility::to_naive(first).map_or(false, |o| {
+            o.day() == DateUtility::days_in_month(o.year(), o.month())
+        });
+
+        let (frequency, mask) = SCHEDULE_FREQUENCIES.iter().find_map(|o| {
+            let frequency = CoreUtility::get_frequency(o);
+            let grid: Vec<usize> = (0..MAX_SCHEDULE_PERIODS)
+                .map(|o2| CoreUtility::date_newi(first, first, frequency, o2 as i32, eom))
+                .take_while(|o2| *o2 <= last)
+                .collect();
+            RecurrenceUtility::mask(&dates, &grid).map(|o2| (*o, o2))
+        })?;
+
+        if !self.set_event_descriptor(
+            index,
+            GROUP_SCHEDULE,
+            NAME_SCHEDULE_RULE,
+            recurrence.descriptor_value().as_str(),
+        ) || !self.set_event_column(
+            index,
+            "Periods",
+            self.engine.format_integer_out(mask.len()).as_str(),
+        ) || (intervals != 1
+            && !self.set_event_column(
+                index,
+                "Intervals",
+                self.engine.format_integer_out(1).as_str(),
+            ))
+            || !self.set_event_column(index, "Frequency", frequency)
+            || !self.set_event_column(index, "Skip-periods", mask.as_str())
+            || !self.set_event_column(index, "Date", self.engine.format_date_out(first).as_str())
+            || !self.balance_events()
+        {
+            return None;
+        }
+
+        Some((first, recurrence.descriptor_value()))
+    }
+
+    /// Return the skip periods mask of an event's recurrence
+    /// schedule (see RecurrenceUtility::mask).
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The event index.
+    ///
+    /// # Return
+    ///
+    /// * See description (no periods are skipped if the event has
+    ///   no recurrence schedule) or None if the event is not found.
+
+    fn schedule_mask(&self, index: usize) -> Option<String> {
+        let grid = self.event_period_dates(index)?;
+        let recurrence = match ElemRecurrence::parse(
+            self.event_descriptor(index, GROUP_SCHEDULE, NAME_SCHEDULE_RULE)
+                .as_str(),
+        ) {
+            None => return Some("0".repeat(grid.len())),
+            Some(o) => o,
+        };
+
+        let last = grid.last().copied().unwrap_or(0);
+        let dates: Vec<usize> = recurrence
+            .dates(grid.len())
+            .into_iter()
+            .filter(|o| *o <= last)
+            .collect();
+
+        Some(RecurrenceUtility::mask(&dates, &grid).unwrap_or_else(|| "0".repeat(grid.len())))
+    }
+
+    /// Return the readable text of a skip periods error.
+    ///
+    /// # Arguments
+    ///
+    /// * `error` - The skip periods error.
+    ///
+    /// # Return
+    ///
+    /// * The error's locale resource text followed by its detail.
+
+    fn skip_periods_error(&self, error: &SkipPeriodsError) -> String {
+        let calc_mgr = self.engine.calc_mgr();
+        let text = String::from(
+            calc_mgr
+                .list_locale()
+                .get_resource(SkipPeriodsUtility::error_key(error)),
+        );
+
+        let detail = SkipPeriodsUtility::error_detail(error);
+        if detail.is_empty() {
+            text
+        } else {
+            format!("{}: {}", text, detail)
+        }
+    }
+
+    /// Set the inputs and calculate an output on a scratch cashflow.
+    ///
+    /// # Arguments
+    ///
+    /// * `scratch` - The scratch cashflow index.
+    /// * `output` - The output to calculate.
+    /// * `inputs` - The inputs and their values.
+    ///
+    /// # Return
+    ///
+    /// * See description or None if not successful.
+
+    fn scratch_value(
+        &self,
+        scratch: i32,
+        output: SensitivityOutput,
+        inputs: &[(&ElemSensitivityInput, Decimal)],
+    ) -> Option<Decimal> {
+        if !self
+            .engine
+            .calc_mgr()
+            .list_cashflow()
+            .get_element(scratch as usize)
+        {
+            return None;
+        }
+
+        for (input, value) in inputs.iter() {
+            if !self.set_event_column_decimal(input.index(), input.col_name(), *value) {
+                return None;
+            }
+        }
+
+        if !self.balance_events() {
+            return None;
+        }
+
+        match output {
+            SensitivityOutput::Payment(index) => self
+                .calculate_value(scratch, index as u32)
+                .parse::<Decimal>()
+                .ok(),
+            SensitivityOutput::Interest => {
+                let rows = self.amortization_rows(true)?;
+                Some(ElemPayoffMetrics::new(&rows).total_interest())
+            }
+            SensitivityOutput::Balance => {
+                let rows = self.amortization_rows(true)?;
+                Some(rows.last().map_or(dec!(0.0), |o| o.balance()))
+            }
+        }
+    }
+
+    /// Return the summary results of the selected cashflow. Results
+    /// using the rate conversion functions are re-evaluated.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    fn summary_results(&self) -> Vec<WasmElemSummary> {
+        let calc_mgr = self.engine.calc_mgr();
+
+        let mut list_parameter: Option<&ListParameter> = None;
+        match calc_mgr.list_cashflow().preferences() {
+            None => {}
+            Some(o) => {
+                list_parameter = Option::from(o.list_parameter());
+            }
+        }
+
+        let mut ary_summary: Vec<WasmElemSummary> = Vec::new();
+        let list_summary = self.engine.parse_summary();
+        let mut index: usize = 0;
+
+        loop {
+            if !list_summary.get_element(index) {
+                break;
+            }
+
+            let mut result = String::from(list_summary.result());
+            if RateUtility::has_functions(list_summary.result_expr())
+                || DepreciationUtility::has_functions(list_summary.result_expr())
+                || DelinquencyUtility::has_functions(list_summary.result_expr())
+                || ConstructionUtility::has_functions(list_summary.result_expr())
+            {
+                result = match self.expand_expression(list_parameter, list_summary.result_expr()) {
+                    Err(e) => e,
+                    Ok(o) => self.symbol_value(list_parameter, o.as_str(), true),
                 };
+            }
+
+            ary_summary.push(WasmElemSummary::new(
+                list_summary.name(),
+                list_summary.label(),
+                list_summary.label_expr(),
+                result.as_str(),
+                list_summary.result_expr(),
+            ));
+
+            index += 1;
+        }
+
+        ary_summary
+    }
+
+    /// Return the readable text of the element not selected error.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    fn element_error(&self) -> String {
+        let calc_mgr = self.engine.calc_mgr();
+
+        String::from(calc_mgr.list_locale().get_resource("Error_Element"))
+    }
+
+    /// Return the value of the named event column for
+    /// the selected event of the selected cashflow.
+    ///
+    /// # Arguments
+    ///
+    /// * `col_name` - The column name (e.g., "Skip-periods").
+    ///
+    /// # Return
+    ///
+    /// * See description or None if the column is not found.
+
+    fn event_column_value(&self, col_name: &str) -> Option<String> {
+        let list_column = self.engine.parse_columns(TableType::Event);
+        let mut col_index: usize = 0;
+
+        loop {
+            if !list_column.get_element(col_index) {
+                break;
+            }
+            if list_column.col_name() == col_name {
+                return Some(self.engine.event_value(list_column.column()));
+            }
+            col_index += 1;
+        }
+
+        None
+    }
+
+    /// Return a copy of an event of the selected cashflow.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The event index.
+    ///
+    /// # Return
+    ///
+    /// * See description or None if the event is not found.
+
+    fn event_copy(&self, index: usize) -> Option<ElemEventCopy> {
+        let calc_mgr = self.engine.calc_mgr();
+        let list_event = calc_mgr.list_cashflow().list_event()?;
+
+        if !list_event.get_element(index) {
+            return None;
+        }
+        let skip_periods = self.event_column_value("Skip-periods").unwrap_or_default();
+
+        Some(ElemEventCopy::from_list(list_event, skip_periods.as_str()))
+    }
+
+    /// Return the value of a custom descriptor of an event.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The event index.
+    /// * `group` - The descriptor group.
+    /// * `name` - The descriptor name.
+    ///
+    /// # Return
+    ///
+    /// * See description or an empty string if not found.
+
+    fn event_descriptor(&self, index: usize, group: &str, name: &str) -> String {
+        let calc_mgr = self.engine.calc_mgr();
+
+        let list_event = match calc_mgr.list_cashflow().list_event() {
+            None => return String::from(""),
+            Some(o) => o,
+        };
+
+        let orig_index = list_event.index();
+        let mut value = String::from("");
+        if list_event.get_element(index) {
+            if let Some(o) = list_event.list_descriptor() {
+                let orig_desc_index = o.index();
+                let mut desc_index: usize = 0;
+                loop {
+                    if !o.get_element(desc_index) {
+                        break;
+                    }
+                    if o.group() == group && o.name() == name && o.desc_type() == "custom" {
+                        value = o.value();
+                        break;
+                    }
+                    desc_index += 1;
+                }
+                o.get_element(orig_desc_index);
+            }
+        }
+        list_event.get_element(orig_index);
+
+        value
+    }
+
+    /// Return the dates of the periods of an event.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The event index.
+    ///
+    /// # Return
+    ///
+    /// * See description or None if the event is not found.
+
+    fn event_period_dates(&self, index: usize) -> Option<Vec<usize>> {
+        let calc_mgr = self.engine.calc_mgr();
+        let list_event = calc_mgr.list_cashflow().list_event()?;
+
+        let orig_index = list_event.index();
+        if !list_event.get_element(index) {
+            list_event.get_element(orig_index);
+            return None;
+        }
+        let event_date = list_event.event_date();
+        let periods = list_event.periods();
+        let intervals = list_event.intervals().max(1);
+        let frequency = list_event.frequency();
+        list_event.get_element(orig_index);
+
+        let eom = DateUtility::to_naive(event_date).map_or(false, |o| {
+            o.day() == DateUtility::days_in_month(o.year(), o.month())
+        });
+
+        Some(
+            (0..periods)
+                .map(|o| {
+                    CoreUtility::date_newi(
+                        event_date,
+                        event_date,
+                        frequency,
+                        (o * intervals) as i32,
+                        eom,
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    /// Return the recurrence rules of the events
+    /// of the selected cashflow.
+    ///
+    /// # Return
+    ///
+    /// * The recurrence rule of each event (empty if
+    ///   the event has no recurrence schedule).
+
+    fn event_schedules(&self) -> Vec<String> {
+        let count = {
+            let calc_mgr = self.engine.calc_mgr();
+            calc_mgr
+                .list_cashflow()
+                .list_event()
+                .map_or(0, |o| o.count())
+        };
+
+        (0..count)
+            .map(|o| {
+                ElemRecurrence::parse(
+                    self.event_descriptor(o, GROUP_SCHEDULE, NAME_SCHEDULE_RULE)
+                        .as_str(),
+                )
+                .map_or(String::from(""), |o2| o2.descriptor_value())
+            })
+            .collect()
+    }
+
+    /// Return the index of an event of the selected
+    /// cashflow by date and sort order.
+    ///
+    /// # Arguments
+    ///
+    /// * `event_date` - Date of the event.
+    /// * `sort_order` - Sort order of the event.
+    ///
+    /// # Return
+    ///
+    /// * See description or None if the event is not found.
 
-                let mut row_index: usize = 0;
-                loop {
-                    if !list_am.get_element(row_index as usize) {
-                        break;
-                    }
-                    let mut row = json.serialize_extension(
-                        list_am.elem_extension(),
-                        list_am.value(),
-                        list_am.frequency(),
-                        false,
-                        true,
-                    );
+    fn event_index_by_date(&self, event_date: usize, sort_order: usize) -> Option<usize> {
+        let calc_mgr = self.engine.calc_mgr();
+        let list_event = calc_mgr.list_cashflow().list_event()?;
 
-                    let orig_index = list_column.index();
-                    let mut index: usize = 0;
-                    loop {
-                        if !list_column.get_element(index) {
-                            break;
-                        }
-                        let val = self.engine.am_value(list_column.column(), &list_am);
-                        row = format!("{},\"{}\":\"{}\"", row, list_column.col_name(), val);
-                        index += 1;
-                    }
-                    list_column.get_element(orig_index);
+        if !list_event.get_element_by_date(event_date, sort_order) {
+            return None;
+        }
 
-                    let delimiter = if row_index == 0 { "" } else { "," };
-                    cresult = format!("{}{}{{{}}}", cresult, delimiter, row);
+        Some(list_event.index())
+    }
 
-                    row_index += 1;
-                }
+    /// Return the dates and sort orders of the events
+    /// of the selected cashflow.
+    ///
+    /// # Return
+    ///
+    /// * See description.
 
-                cresult = format!("\"compressed\": [{}]", cresult);
+    fn event_sort_orders(&self) -> Vec<(usize, usize)> {
+        let calc_mgr = self.engine.calc_mgr();
+        let mut result: Vec<(usize, usize)> = Vec::new();
+
+        if let Some(o) = calc_mgr.list_cashflow().list_event() {
+            let orig_index = o.index();
+            let mut index: usize = 0;
+            while o.get_element(index) {
+                result.push((o.event_date(), o.sort_order()));
+                index += 1;
+            }
+            o.get_element(orig_index);
+        }
 
-                let mut eresult = String::from("");
-                match calc_mgr
-                    .list_cashflow()
-                    .create_cashflow_output(false, false, false, false, true)
-                {
-                    Err(_e) => {
-                        return eresult;
+        result
+    }
+
+    /// Return the start of the selected cashflow's fiscal year
+    /// (or the user preferences if not set by the cashflow).
+    ///
+    /// # Return
+    ///
+    /// * The start of the fiscal year in MMDD format.
+
+    fn fiscal_year_start(&self) -> usize {
+        let calc_mgr = self.engine.calc_mgr();
+
+        if let Some(o) = calc_mgr.list_cashflow().preferences() {
+            if o.fiscal_year_start() > 0 {
+                return o.fiscal_year_start() as usize;
+            }
+        }
+
+        calc_mgr.preferences().fiscal_year_start() as usize
+    }
+
+    /// Return the annual interest rate (percentage) of the first
+    /// interest change event of the selected cashflow.
+    ///
+    /// # Return
+    ///
+    /// * See description or None if there is no interest change event.
+
+    fn interest_change_rate(&self) -> Option<Decimal> {
+        let calc_mgr = self.engine.calc_mgr();
+        let list_event = calc_mgr.list_cashflow().list_event()?;
+        let orig_index = list_event.index();
+        let mut result: Option<Decimal> = None;
+
+        let mut index: usize = 0;
+        while list_event.get_element(index) {
+            if list_event.elem_extension().extension_type() == ExtensionType::InterestChange {
+                result = Some(list_event.value());
+                break;
+            }
+            index += 1;
+        }
+        list_event.get_element(orig_index);
+
+        result
+    }
+
+    /// Return the value of a custom descriptor from the
+    /// selected cashflow's preferences.
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - The descriptor group.
+    /// * `name` - The descriptor name.
+    ///
+    /// # Return
+    ///
+    /// * See description or an empty string if not found.
+
+    fn cashflow_descriptor(&self, group: &str, name: &str) -> String {
+        let calc_mgr = self.engine.calc_mgr();
+
+        match calc_mgr.list_cashflow().preferences() {
+            None => String::from(""),
+            Some(o) => {
+                let list_descriptor = o.list_descriptor();
+                let mut value = String::from("");
+                let orig_index = list_descriptor.index();
+                let mut index: usize = 0;
+                loop {
+                    if !list_descriptor.get_element(index) {
+                        break;
                     }
-                    Ok(o) => {
-                        list_am = o;
+                    if list_descriptor.group() == group
+                        && list_descriptor.name() == name
+                        && list_descriptor.desc_type() == "custom"
+                    {
+                        value = list_descriptor.value();
+                        break;
                     }
+                    index += 1;
                 }
+                list_descriptor.get_element(orig_index);
+                value
+            }
+        }
+    }
 
-                row_index = 0;
+    /// Set (or remove) a custom descriptor within the selected
+    /// cashflow's preferences.
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - The descriptor group.
+    /// * `name` - The descriptor name.
+    /// * `value` - The descriptor value (empty to remove the descriptor).
+    ///
+    /// # Return
+    ///
+    /// * True if successful, otherwise false.
+
+    fn set_cashflow_descriptor(&self, group: &str, name: &str, value: &str) -> bool {
+        let mut calc_mgr = self.engine.calc_mgr_mut();
+
+        match calc_mgr.list_cashflow_mut().preferences_mut() {
+            None => false,
+            Some(o) => {
+                let list_descriptor = o.list_descriptor_mut();
+                let mut index: usize = 0;
                 loop {
-                    if !list_am.get_element(row_index as usize) {
+                    if !list_descriptor.get_element(index) {
                         break;
                     }
-                    let mut row = json.serialize_extension(
-                        list_am.elem_extension(),
-                        list_am.value(),
-                        list_am.frequency(),
-                        false,
-                        true,
-                    );
-
-                    let orig_index = list_column.index();
-                    let mut index: usize = 0;
-                    loop {
-                        if !list_column.get_element(index) {
-                            break;
-                        }
-                        let val = self.engine.am_value(list_column.column(), &list_am);
-                        row = format!("{},\"{}\":\"{}\"", row, list_column.col_name(), val);
-                        index += 1;
+                    if list_descriptor.group() == group
+                        && list_descriptor.name() == name
+                        && list_descriptor.desc_type() == "custom"
+                    {
+                        list_descriptor.remove();
+                        break;
                     }
-                    list_column.get_element(orig_index);
+                    index += 1;
+                }
 
-                    let delimiter = if row_index == 0 { "" } else { "," };
-                    eresult = format!("{}{}{{{}}}", eresult, delimiter, row);
+                if value.is_empty() {
+                    return true;
+                }
 
-                    row_index += 1;
+                list_descriptor.add_descriptor(group, name, "custom", "", value, "", false, true)
+            }
+        }
+    }
+
+    /// Set (or remove) a custom descriptor of an event.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The event index.
+    /// * `group` - The descriptor group.
+    /// * `name` - The descriptor name.
+    /// * `value` - The descriptor value (empty to remove the descriptor).
+    ///
+    /// # Return
+    ///
+    /// * True if successful, otherwise false.
+
+    fn set_event_descriptor(&self, index: usize, group: &str, name: &str, value: &str) -> bool {
+        let mut calc_mgr = self.engine.calc_mgr_mut();
+
+        let list_event = match calc_mgr.list_cashflow_mut().list_event_mut() {
+            None => return false,
+            Some(o) => o,
+        };
+
+        if !list_event.get_element(index) {
+            return false;
+        }
+
+        match list_event.list_descriptor_mut() {
+            None => false,
+            Some(o) => {
+                let mut desc_index: usize = 0;
+                loop {
+                    if !o.get_element(desc_index) {
+                        break;
+                    }
+                    if o.group() == group && o.name() == name && o.desc_type() == "custom" {
+                        o.remove();
+                        break;
+                    }
+                    desc_index += 1;
                 }
 
-                eresult = format!("\"expanded\": [{}]", eresult);
+                if value.is_empty() {
+                    return true;
+                }
 
-                format!("{{{},{}}}", cresult, eresult)
+                o.add_descriptor(group, name, "custom", "", value, "", false, true)
             }
-            _ => {
-                let mut result = String::from("");
-                let mut row_index: usize = 0;
-                loop {
-                    let mut row = String::from("");
-                    let mut next_name = String::from("");
-                    match calc_mgr.list_cashflow().list_event() {
-                        None => {}
-                        Some(o) => {
-                            if !o.get_element(row_index as usize) {
-                                break;
-                            }
+        }
+    }
 
-                            row = json.serialize_extension(
-                                o.elem_extension(),
-                                dec!(0.0),
-                                o.frequency(),
-                                false,
-                                true,
-                            );
-                            next_name = String::from(o.next_name());
-                        }
-                    }
+    /// Expand the wasm expression functions found within an
+    /// expression into their literal results.
+    ///
+    /// # Arguments
+    ///
+    /// * `list_parameter` - Optional parameter list for the expression.
+    /// * `expr` - Expression to expand.
+    ///
+    /// # Return
+    ///
+    /// * See description or the readable text of the error if a
+    ///   function has an invalid argument.
 
-                    let mut next_name_seen = false;
-                    let orig_index = list_column.index();
-                    let mut index: usize = 0;
-                    loop {
-                        if !list_column.get_element(index) {
-                            break;
-                        }
-                        let val = self.engine.event_value(list_column.column());
-                        row = format!("{},\"{}\":\"{}\"", row, list_column.col_name(), val);
-                        if !next_name_seen {
-                            next_name_seen = list_column.col_name() == "Next-name";
-                        }
-                        index += 1;
-                    }
-                    list_column.get_element(orig_index);
+    fn expand_expression(
+        &self,
+        list_parameter: Option<&ListParameter>,
+        expr: &str,
+    ) -> Result<String, String> {
+        let mut expanded_expr = String::from(expr);
+
+        if DepreciationUtility::has_functions(expr) {
+            let assets = self.depreciation_assets();
+            let fiscal_year_start = self.fiscal_year_start();
+            let rows = DepreciationUtility::schedule(
+                &assets,
+                fiscal_year_start,
+                self.cashflow_decimal_digits(),
+            );
+            expanded_expr = DepreciationUtility::expand_expression(
+                expr,
+                &|o| self.symbol_value(list_parameter, o, false),
+                &|name, date| {
+                    DepreciationUtility::value(&assets, &rows, fiscal_year_start, name, date)
+                },
+            );
+        }
 
-                    if !next_name_seen {
-                        row = format!("{},\"{}\":\"{}\"", row, "Next-name", next_name);
-                    }
+        if DelinquencyUtility::has_functions(expanded_expr.as_str()) {
+            let flows = self
+                .amortization_rows(true)
+                .map_or(Vec::new(), |o| ElemCashFlow::from_rows(&o));
+            let payments = self.cashflow_actual_payments();
+            let rules = self.cashflow_delinquency_rules();
+            let decimal_digits = self.cashflow_decimal_digits();
+            expanded_expr = DelinquencyUtility::expand_expression(
+                expanded_expr.as_str(),
+                &|o| self.symbol_value(list_parameter, o, false),
+                &|name, date| {
+                    DelinquencyUtility::value(&flows, &payments, &rules, name, date, decimal_digits)
+                },
+            );
+        }
 
-                    let delimiter = if row_index == 0 { "" } else { "," };
-                    result = format!("{}{}{{{}}}", result, delimiter, row);
+        if ConstructionUtility::has_functions(expanded_expr.as_str()) {
+            let rows = self.amortization_rows(true).unwrap_or_default();
+            let (_schedule, summary) =
+                ConstructionUtility::schedule(&rows, self.cashflow_conversion_date());
+            expanded_expr = ConstructionUtility::expand_expression(
+                expanded_expr.as_str(),
+                &|o| self.symbol_value(list_parameter, o, false),
+                &summary,
+            );
+        }
 
-                    row_index += 1;
-                }
+        match RateUtility::expand_expression(expanded_expr.as_str(), &|o| {
+            self.symbol_value(list_parameter, o, false)
+        }) {
+            None => {
+                let calc_mgr = self.engine.calc_mgr();
+                Err(String::from(
+                    calc_mgr.list_locale().get_resource("Error_Function"),
+                ))
+            }
+            Some(o) => Ok(o),
+        }
+    }
 
-                format!("[{}]", result)
+    /// Evaluate an expression and return the result as a string.
+    ///
+    /// # Arguments
+    ///
+    /// * `list_parameter` - Optional parameter list for the expression.
+    /// * `expr` - Expression to evaluate.
+    /// * `format_out` - Format numeric results for display.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    fn symbol_value(
+        &self,
+        list_parameter: Option<&ListParameter>,
+        expr: &str,
+        format_out: bool,
+    ) -> String {
+        let result_symbol = self.engine.evaluate_expression(list_parameter, expr, true);
+
+        match result_symbol.sym_type() {
+            amfnengine::TokenType::Integer => {
+                if format_out {
+                    self.engine.format_integer_out(result_symbol.sym_integer())
+                } else {
+                    result_symbol.sym_integer().to_string()
+                }
+            }
+            amfnengine::TokenType::Decimal => {
+                if format_out {
+                    self.engine.format_decimal_out(result_symbol.sym_decimal())
+                } else {
+                    result_symbol.sym_decimal().to_string()
+                }
             }
+            _ => String::from(result_symbol.sym_string()),
         }
     }
 }
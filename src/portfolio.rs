@@ -0,0 +1,280 @@
+//! Portfolio consolidation.
+// Copyright (c) 2021 ShiftLeft Software
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use rust_decimal::prelude::*;
+
+use amfnengine::*;
+
+use crate::am_row::*;
+use crate::date_utility::*;
+use crate::rate_utility::*;
+
+/// Consolidated portfolio row.
+pub struct ElemPortfolioRow {
+    /// Date of the row (YYYYMMDD).
+    event_date: usize,
+    /// Consolidated balance at the end of the date.
+    balance: Decimal,
+    /// Consolidated interest.
+    interest: Decimal,
+    /// Consolidated principal decrease.
+    principal_decrease: Decimal,
+    /// Consolidated principal increase.
+    principal_increase: Decimal,
+}
+
+/// Consolidated portfolio row implementation.
+impl ElemPortfolioRow {
+    /// Get the date of the row.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn event_date(&self) -> usize {
+        self.event_date
+    }
+
+    /// Get the consolidated balance.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn balance(&self) -> Decimal {
+        self.balance
+    }
+
+    /// Get the consolidated interest.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn interest(&self) -> Decimal {
+        self.interest
+    }
+
+    /// Get the consolidated principal decrease.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn principal_decrease(&self) -> Decimal {
+        self.principal_decrease
+    }
+
+    /// Get the consolidated principal increase.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn principal_increase(&self) -> Decimal {
+        self.principal_increase
+    }
+}
+
+/// Portfolio position of a single cashflow as of a date.
+pub struct ElemPortfolioPosition {
+    /// Balance as of the date.
+    balance: Decimal,
+    /// Implied annual interest rate (percentage).
+    rate: Decimal,
+    /// Remaining term in months.
+    maturity: Decimal,
+}
+
+/// Portfolio position implementation.
+impl ElemPortfolioPosition {
+    /// Calculate the position of a cashflow as of a date.
+    /// The rate is implied from the interest of the most recent
+    /// period ending on or before the date.
+    ///
+    /// # Arguments
+    ///
+    /// * `rows` - The expanded amortization rows of the cashflow.
+    /// * `as_of` - The date of the position (YYYYMMDD).
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn new(rows: &[ElemAmRow], as_of: usize) -> ElemPortfolioPosition {
+        let mut balance = dec!(0.0);
+        let mut rate = dec!(0.0);
+        let mut maturity_date: usize = 0;
+        let mut prior: Option<&ElemAmRow> = None;
+        let mut last_row: Option<&ElemAmRow> = None;
+
+        for row in rows.iter() {
+            if let Some(o) = last_row {
+                if o.event_date() != row.event_date() {
+                    prior = Some(o);
+                }
+                if !o.balance().is_zero() || !row.balance().is_zero() {
+                    maturity_date = row.event_date();
+                }
+            }
+            if row.event_date() <= as_of {
+                balance = row.balance();
+            }
+
+            if let Some(o) = prior {
+                if !row.interest().is_zero()
+                    && !o.balance().is_zero()
+                    && (row.event_date() <= as_of || rate.is_zero())
+                {
+                    if let Some(ppy) =
+                        DateUtility::periods_in_year(o.event_date(), row.event_date())
+                    {
+                        rate = (row.interest().abs() / o.balance().abs()
+                            * Decimal::from(ppy)
+                            * dec!(100.0))
+                        .round_dp(RATE_DECIMAL_DIGITS);
+                    }
+                }
+            }
+
+            last_row = Some(row);
+        }
+
+        // Months are measured as the average number of days within a month
+        let maturity = if maturity_date > as_of {
+            (Decimal::from(DateUtility::days_between(as_of, maturity_date)) / dec!(30.4375))
+                .round_dp(2)
+        } else {
+            dec!(0.0)
+        };
+
+        ElemPortfolioPosition {
+            balance,
+            rate,
+            maturity,
+        }
+    }
+
+    /// Get the balance.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn balance(&self) -> Decimal {
+        self.balance
+    }
+
+    /// Get the implied annual interest rate.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn rate(&self) -> Decimal {
+        self.rate
+    }
+
+    /// Get the remaining term in months.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn maturity(&self) -> Decimal {
+        self.maturity
+    }
+}
+
+/// Portfolio consolidation utility.
+pub struct PortfolioUtility {}
+
+/// Portfolio consolidation utility implementation.
+impl PortfolioUtility {
+    /// Consolidate the amortization rows of several cashflows onto a
+    /// common date axis (the union of all their dates). The interest
+    /// and principal of each date are summed and the balance of each
+    /// cashflow is carried forward to the dates it has no rows for.
+    ///
+    /// # Arguments
+    ///
+    /// * `series` - The expanded amortization rows of each cashflow.
+    ///
+    /// # Return
+    ///
+    /// * One consolidated row for each date.
+
+    pub fn consolidate(series: &[Vec<ElemAmRow>]) -> Vec<ElemPortfolioRow> {
+        let mut dates: Vec<usize> = series
+            .iter()
+            .flat_map(|o| o.iter().map(|row| row.event_date()))
+            .collect();
+        dates.sort_unstable();
+        dates.dedup();
+
+        let mut positions: Vec<usize> = vec![0; series.len()];
+        let mut balances: Vec<Decimal> = vec![dec!(0.0); series.len()];
+        let mut result: Vec<ElemPortfolioRow> = Vec::new();
+
+        for date in dates.iter() {
+            let mut row = ElemPortfolioRow {
+                event_date: *date,
+                balance: dec!(0.0),
+                interest: dec!(0.0),
+                principal_decrease: dec!(0.0),
+                principal_increase: dec!(0.0),
+            };
+
+            for (cf_index, rows) in series.iter().enumerate() {
+                while positions[cf_index] < rows.len()
+                    && rows[positions[cf_index]].event_date() == *date
+                {
+                    let am_row = &rows[positions[cf_index]];
+                    row.interest += am_row.interest();
+                    row.principal_decrease += am_row.principal_decrease();
+                    row.principal_increase += am_row.principal_increase();
+                    balances[cf_index] = am_row.balance();
+                    positions[cf_index] += 1;
+                }
+                row.balance += balances[cf_index];
+            }
+
+            result.push(row);
+        }
+
+        result
+    }
+
+    /// Calculate a balance weighted average. The positions are
+    /// weighted by the absolute value of their balances (loan
+    /// balances are negative).
+    ///
+    /// # Arguments
+    ///
+    /// * `positions` - The cashflow positions.
+    /// * `value` - Returns the value to average from a position.
+    ///
+    /// # Return
+    ///
+    /// * See description (zero if there is no balance).
+
+    pub fn weighted_average(
+        positions: &[ElemPortfolioPosition],
+        value: &dyn Fn(&ElemPortfolioPosition) -> Decimal,
+    ) -> Decimal {
+        let total: Decimal = positions.iter().map(|o| o.balance().abs()).sum();
+        if total.is_zero() {
+            return dec!(0.0);
+        }
+
+        let weighted: Decimal = positions.iter().map(|o| o.balance().abs() * value(o)).sum();
+
+        weighted / total
+    }
+}
@@ -0,0 +1,392 @@
+//! Prepayment models (CPR, SMM and PSA).
+// Copyright (c) 2021 ShiftLeft Software
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use rust_decimal::prelude::*;
+
+use amfnengine::*;
+
+use crate::am_row::*;
+use crate::date_utility::*;
+use crate::decimal_utility::*;
+
+/// Cashflow preferences descriptor group for the prepayment model.
+pub const GROUP_PREPAYMENT: &str = "Prepayment";
+/// Cashflow preferences descriptor name for the prepayment model.
+pub const NAME_PREPAYMENT_MODEL: &str = "Model";
+
+/// Number of months until the PSA benchmark reaches its plateau.
+const PSA_RAMP_MONTHS: u32 = 30;
+
+/// Prepayment model type.
+#[derive(Clone, Copy, PartialEq)]
+pub enum PrepaymentType {
+    /// No prepayments.
+    None,
+    /// Constant prepayment rate (annual percentage).
+    Cpr,
+    /// Single monthly mortality (percentage per payment period).
+    Smm,
+    /// Public Securities Association benchmark (percentage of PSA).
+    Psa,
+}
+
+/// Prepayment model element.
+#[derive(Clone, Copy)]
+pub struct ElemPrepaymentModel {
+    /// Type of prepayment model.
+    prepayment_type: PrepaymentType,
+    /// Prepayment speed (CPR, SMM or PSA percentage).
+    speed: Decimal,
+}
+
+/// Prepayment model implementation.
+impl ElemPrepaymentModel {
+    /// Create and return a prepayment model.
+    ///
+    /// # Arguments
+    ///
+    /// * `prepayment_type_param` - Type of prepayment model.
+    /// * `speed_param` - Prepayment speed.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn new(prepayment_type_param: PrepaymentType, speed_param: Decimal) -> ElemPrepaymentModel {
+        ElemPrepaymentModel {
+            prepayment_type: prepayment_type_param,
+            speed: speed_param,
+        }
+    }
+
+    /// Parse a prepayment model from its descriptor value
+    /// (e.g., "CPR~6.0", "SMM~0.5" or "PSA~150").
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The descriptor value.
+    ///
+    /// # Return
+    ///
+    /// * See description (no prepayments if the value is invalid).
+
+    pub fn parse(value: &str) -> ElemPrepaymentModel {
+        let mut tokens = value.split('~');
+        let prepayment_type = ElemPrepaymentModel::parse_type(tokens.next().unwrap_or(""));
+        let speed = tokens
+            .next()
+            .and_then(|o| o.trim().parse::<Decimal>().ok())
+            .unwrap_or_else(|| dec!(0.0));
+
+        if prepayment_type == PrepaymentType::None || speed <= dec!(0.0) {
+            return ElemPrepaymentModel::new(PrepaymentType::None, dec!(0.0));
+        }
+
+        ElemPrepaymentModel::new(prepayment_type, speed)
+    }
+
+    /// Parse a prepayment model type mnemonic.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The mnemonic (CPR, SMM or PSA).
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn parse_type(value: &str) -> PrepaymentType {
+        match value.trim().to_uppercase().as_str() {
+            "CPR" => PrepaymentType::Cpr,
+            "SMM" => PrepaymentType::Smm,
+            "PSA" => PrepaymentType::Psa,
+            _ => PrepaymentType::None,
+        }
+    }
+
+    /// Get the type of prepayment model.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn prepayment_type(&self) -> PrepaymentType {
+        self.prepayment_type
+    }
+
+    /// Get the prepayment speed.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn speed(&self) -> Decimal {
+        self.speed
+    }
+
+    /// Return the descriptor value for the model.
+    ///
+    /// # Return
+    ///
+    /// * See description (empty if there are no prepayments).
+
+    pub fn descriptor_value(&self) -> String {
+        let mnemonic = match self.prepayment_type {
+            PrepaymentType::Cpr => "CPR",
+            PrepaymentType::Smm => "SMM",
+            PrepaymentType::Psa => "PSA",
+            PrepaymentType::None => return String::from(""),
+        };
+
+        format!("{}~{}", mnemonic, self.speed)
+    }
+
+    /// Calculate the single period mortality for a payment.
+    ///
+    /// # Arguments
+    ///
+    /// * `age` - The number of payments made (including this one).
+    /// * `periods_in_year` - The number of payments per year.
+    ///
+    /// # Return
+    ///
+    /// * The fraction (not percentage) of the balance prepaid.
+
+    pub fn smm(&self, age: u32, periods_in_year: u32) -> Decimal {
+        let periods = periods_in_year.max(1);
+
+        let cpr = match self.prepayment_type {
+            PrepaymentType::None => return dec!(0.0),
+            PrepaymentType::Smm => return (self.speed / dec!(100.0)).min(dec!(1.0)),
+            PrepaymentType::Cpr => self.speed,
+            PrepaymentType::Psa => {
+                let months = (age * 12 / periods).clamp(1, PSA_RAMP_MONTHS);
+                Decimal::from(months) * dec!(0.2) * self.speed / dec!(100.0)
+            }
+        };
+
+        let survival = dec!(1.0) - (cpr / dec!(100.0)).min(dec!(1.0));
+        if survival.is_zero() {
+            return dec!(1.0);
+        }
+
+        match DecimalUtility::powd(survival, dec!(1.0) / Decimal::from(periods)) {
+            None => dec!(0.0),
+            Some(o) => dec!(1.0) - o,
+        }
+    }
+}
+
+/// Expected-case prepayment row.
+pub struct ElemPrepaymentRow {
+    /// Date of the row (YYYYMMDD).
+    event_date: usize,
+    /// Scheduled balance (without prepayments).
+    scheduled_balance: Decimal,
+    /// Expected interest.
+    interest: Decimal,
+    /// Expected scheduled principal payment.
+    scheduled_principal: Decimal,
+    /// Expected prepayment.
+    prepayment: Decimal,
+    /// Expected balance.
+    balance: Decimal,
+    /// Single period mortality applied to the row.
+    smm: Decimal,
+}
+
+/// Expected-case prepayment row implementation.
+impl ElemPrepaymentRow {
+    /// Get the date of the row.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn event_date(&self) -> usize {
+        self.event_date
+    }
+
+    /// Get the scheduled balance.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn scheduled_balance(&self) -> Decimal {
+        self.scheduled_balance
+    }
+
+    /// Get the expected interest.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn interest(&self) -> Decimal {
+        self.interest
+    }
+
+    /// Get the expected scheduled principal decrease.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn scheduled_principal(&self) -> Decimal {
+        self.scheduled_principal
+    }
+
+    /// Get the expected prepayment.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn prepayment(&self) -> Decimal {
+        self.prepayment
+    }
+
+    /// Get the expected balance.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn balance(&self) -> Decimal {
+        self.balance
+    }
+
+    /// Get the single period mortality.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn smm(&self) -> Decimal {
+        self.smm
+    }
+}
+
+/// Prepayment projection utility.
+pub struct PrepaymentUtility {}
+
+/// Prepayment projection utility implementation.
+impl PrepaymentUtility {
+    /// Project the expected-case schedule for a prepayment model.
+    /// The scheduled amortization describes a pool of identical loans;
+    /// after each payment the surviving fraction of the pool is reduced
+    /// by the single period mortality, so the expected balance, interest
+    /// and scheduled principal are the scheduled amounts multiplied by
+    /// the survival factor and the prepayment is the prepaid fraction of
+    /// the balance remaining after the scheduled principal payment.
+    /// The payments are the principal changes in the direction of the
+    /// payments (see ElemAmRow::payment_direction).
+    ///
+    /// # Arguments
+    ///
+    /// * `rows` - The expanded amortization rows.
+    /// * `model` - The prepayment model.
+    /// * `decimal_digits` - Number of decimal digits for rounding.
+    ///
+    /// # Return
+    ///
+    /// * One expected-case row for each amortization row.
+
+    pub fn project(
+        rows: &[ElemAmRow],
+        model: &ElemPrepaymentModel,
+        decimal_digits: u32,
+    ) -> Vec<ElemPrepaymentRow> {
+        let mut result: Vec<ElemPrepaymentRow> = Vec::new();
+        let direction = ElemAmRow::payment_direction(rows);
+        let mut survival = dec!(1.0);
+        let mut prior_balance = dec!(0.0);
+        let mut prior_payment_date: usize = 0;
+        let mut periods_in_year: u32 = 12;
+        let mut age: u32 = 0;
+
+        for row in rows.iter() {
+            let mut smm = dec!(0.0);
+            let mut prepayment = dec!(0.0);
+            let interest = row.interest() * survival;
+            let payment = row.payment(direction).max(dec!(0.0));
+            let scheduled_principal = payment * survival;
+
+            if payment > dec!(0.0) && !prior_balance.is_zero() {
+                if prior_payment_date > 0 {
+                    if let Some(o) =
+                        DateUtility::periods_in_year(prior_payment_date, row.event_date())
+                    {
+                        periods_in_year = o;
+                    }
+                }
+                prior_payment_date = row.event_date();
+                age += 1;
+
+                smm = model.smm(age, periods_in_year);
+                prepayment = row.balance().abs() * survival * smm;
+                survival *= dec!(1.0) - smm;
+            }
+
+            result.push(ElemPrepaymentRow {
+                event_date: row.event_date(),
+                scheduled_balance: row.balance(),
+                interest: interest.round_dp(decimal_digits),
+                scheduled_principal: scheduled_principal.round_dp(decimal_digits),
+                prepayment: prepayment.round_dp(decimal_digits),
+                balance: (row.balance() * survival).round_dp(decimal_digits),
+                smm,
+            });
+
+            prior_balance = row.balance();
+        }
+
+        result
+    }
+
+    /// Return the expected prepayments of the rows dated after
+    /// one date and on or before another.
+    ///
+    /// # Arguments
+    ///
+    /// * `rows` - The expected-case rows.
+    /// * `from_date` - The starting date (exclusive).
+    /// * `to_date` - The ending date (inclusive).
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn period(rows: &[ElemPrepaymentRow], from_date: usize, to_date: usize) -> Decimal {
+        rows.iter()
+            .filter(|o| o.event_date() > from_date && o.event_date() <= to_date)
+            .map(|o| o.prepayment())
+            .sum()
+    }
+
+    /// Return the expected balance as of a date.
+    ///
+    /// # Arguments
+    ///
+    /// * `rows` - The expected-case rows.
+    /// * `date` - The date.
+    ///
+    /// # Return
+    ///
+    /// * The expected balance of the last row dated on
+    ///   or before the date (zero if there is none).
+
+    pub fn balance(rows: &[ElemPrepaymentRow], date: usize) -> Decimal {
+        rows.iter()
+            .filter(|o| o.event_date() <= date)
+            .last()
+            .map_or(dec!(0.0), |o| o.balance())
+    }
+}
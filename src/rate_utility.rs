@@ -0,0 +1,327 @@
+//! Interest rate conversion utilities.
+// Copyright (c) 2021 ShiftLeft Software
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use rust_decimal::prelude::*;
+
+use amfnengine::*;
+
+use crate::decimal_utility::*;
+use crate::expression::*;
+
+/// Number of decimal digits kept for converted rates.
+pub const RATE_DECIMAL_DIGITS: u32 = 10;
+
+/// Expression function converting a rate between two frequencies.
+pub const FUNC_RATE_CONVERT: &str = "rateconvert";
+/// Expression function converting a nominal rate to an effective annual rate.
+pub const FUNC_RATE_EFFECTIVE: &str = "rateeffective";
+/// Expression function converting an effective annual rate to a nominal rate.
+pub const FUNC_RATE_NOMINAL: &str = "ratenominal";
+
+/// Interest rate conversion utility.
+pub struct RateUtility {}
+
+/// Interest rate conversion utility implementation.
+impl RateUtility {
+    /// Return the number of compounding periods per year for
+    /// a frequency.
+    ///
+    /// # Arguments
+    ///
+    /// * `frequency` - The frequency name (e.g., "1-month").
+    ///
+    /// # Return
+    ///
+    /// * See description, zero for continuous compounding
+    ///   or None if the frequency is not recognized.
+
+    pub fn periods_in_year(frequency: &str) -> Option<Decimal> {
+        match frequency.trim().to_lowercase().as_str() {
+            "1-year" => Some(dec!(1.0)),
+            "6-months" => Some(dec!(2.0)),
+            "4-months" => Some(dec!(3.0)),
+            "3-months" => Some(dec!(4.0)),
+            "2-months" => Some(dec!(6.0)),
+            "1-month" => Some(dec!(12.0)),
+            "half-month" => Some(dec!(24.0)),
+            "4-weeks" => Some(dec!(13.0)),
+            "2-weeks" => Some(dec!(26.0)),
+            "1-week" => Some(dec!(52.0)),
+            "1-day" => Some(dec!(365.0)),
+            "continuous" => Some(dec!(0.0)),
+            _ => None,
+        }
+    }
+
+    /// Convert a nominal rate compounded at a frequency to an
+    /// effective annual rate. Rates are expressed as percentages.
+    ///
+    /// # Arguments
+    ///
+    /// * `rate` - The nominal rate.
+    /// * `frequency` - The compounding frequency of the nominal rate.
+    ///
+    /// # Return
+    ///
+    /// * See description or None if the conversion is undefined.
+
+    pub fn effective_rate(rate: Decimal, frequency: &str) -> Option<Decimal> {
+        let periods = RateUtility::periods_in_year(frequency)?;
+        let nominal = rate / dec!(100.0);
+
+        let effective = if periods.is_zero() {
+            DecimalUtility::exp(nominal)? - dec!(1.0)
+        } else {
+            let periodic = dec!(1.0) + nominal / periods;
+            DecimalUtility::powd(periodic, periods)? - dec!(1.0)
+        };
+
+        Some((effective * dec!(100.0)).round_dp(RATE_DECIMAL_DIGITS))
+    }
+
+    /// Convert an effective annual rate to a nominal rate
+    /// compounded at a frequency. Rates are expressed as percentages.
+    ///
+    /// # Arguments
+    ///
+    /// * `rate` - The effective annual rate.
+    /// * `frequency` - The compounding frequency of the nominal rate.
+    ///
+    /// # Return
+    ///
+    /// * See description or None if the conversion is undefined.
+
+    pub fn nominal_rate(rate: Decimal, frequency: &str) -> Option<Decimal> {
+        let periods = RateUtility::periods_in_year(frequency)?;
+        let growth = dec!(1.0) + rate / dec!(100.0);
+
+        let nominal = if periods.is_zero() {
+            DecimalUtility::ln(growth)?
+        } else {
+            let periodic = DecimalUtility::powd(growth, dec!(1.0) / periods)?;
+            (periodic - dec!(1.0)) * periods
+        };
+
+        Some((nominal * dec!(100.0)).round_dp(RATE_DECIMAL_DIGITS))
+    }
+
+    /// Convert a nominal rate from one compounding frequency to another.
+    /// Rates are expressed as percentages.
+    ///
+    /// # Arguments
+    ///
+    /// * `rate` - The nominal rate.
+    /// * `from_frequency` - The compounding frequency of the given rate.
+    /// * `to_frequency` - The compounding frequency of the result.
+    ///
+    /// # Return
+    ///
+    /// * See description or None if the conversion is undefined.
+
+    pub fn convert_rate(
+        rate: Decimal,
+        from_frequency: &str,
+        to_frequency: &str,
+    ) -> Option<Decimal> {
+        let effective = RateUtility::effective_rate(rate, from_frequency)?;
+
+        RateUtility::nominal_rate(effective, to_frequency)
+    }
+
+    /// Expand the rate conversion functions found within an expression
+    /// into their literal results. Function arguments are evaluated
+    /// with the given closure, so they may themselves be expressions.
+    ///
+    /// # Arguments
+    ///
+    /// * `expr` - The expression to expand.
+    /// * `eval` - Evaluates an argument expression and returns its value.
+    ///
+    /// # Return
+    ///
+    /// * The expanded expression or None if a function has an
+    ///   invalid argument (e.g., an unrecognized frequency).
+
+    pub fn expand_expression(expr: &str, eval: &dyn Fn(&str) -> String) -> Option<String> {
+        let mut result = String::from("");
+        let mut rest = expr;
+
+        loop {
+            let found = RateUtility::find_function(rest);
+            let (start, name) = match found {
+                None => {
+                    result.push_str(rest);
+                    break;
+                }
+                Some(o) => o,
+            };
+
+            let args_start = start + name.len() + 1;
+            let args_end = match ExpressionUtility::find_closing_paren(&rest[args_start..]) {
+                None => {
+                    result.push_str(rest);
+                    break;
+                }
+                Some(o) => args_start + o,
+            };
+
+            let args: Vec<String> = ExpressionUtility::split_arguments(&rest[args_start..args_end])
+                .iter()
+                .map(|o| RateUtility::expand_expression(o, eval).map(|p| eval(p.as_str())))
+                .collect::<Option<Vec<String>>>()?;
+
+            let value = RateUtility::evaluate_function(name, &args)?;
+            result.push_str(&rest[..start]);
+            result.push_str(value.to_string().as_str());
+
+            rest = &rest[args_end + 1..];
+        }
+
+        Some(result)
+    }
+
+    /// Determine whether an expression contains any rate conversion functions.
+    ///
+    /// # Arguments
+    ///
+    /// * `expr` - The expression to search.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn has_functions(expr: &str) -> bool {
+        RateUtility::find_function(expr).is_some()
+    }
+
+    /// Evaluate a rate conversion function.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The function name.
+    /// * `args` - The evaluated arguments.
+    ///
+    /// # Return
+    ///
+    /// * See description or None if the arguments are invalid.
+
+    fn evaluate_function(name: &str, args: &[String]) -> Option<Decimal> {
+        let rate = args.first()?.trim().parse::<Decimal>().ok()?;
+        let frequency = ExpressionUtility::unquote(args.get(1)?);
+
+        match name {
+            FUNC_RATE_CONVERT => {
+                RateUtility::convert_rate(rate, frequency, ExpressionUtility::unquote(args.get(2)?))
+            }
+            FUNC_RATE_EFFECTIVE => RateUtility::effective_rate(rate, frequency),
+            _ => RateUtility::nominal_rate(rate, frequency),
+        }
+    }
+
+    /// Find the first rate conversion function call in an expression.
+    ///
+    /// # Arguments
+    ///
+    /// * `expr` - The expression to search.
+    ///
+    /// # Return
+    ///
+    /// * The byte offset and name of the function or None.
+
+    fn find_function(expr: &str) -> Option<(usize, &'static str)> {
+        let lower = expr.to_ascii_lowercase();
+        let mut found: Option<(usize, &'static str)> = None;
+
+        for name in [FUNC_RATE_CONVERT, FUNC_RATE_EFFECTIVE, FUNC_RATE_NOMINAL].iter() {
+            let mut offset: usize = 0;
+            while let Some(o) = lower[offset..].find(format!("{}(", name).as_str()) {
+                let start = offset + o;
+                let preceded = lower[..start]
+                    .chars()
+                    .last()
+                    .map_or(false, |c| c.is_alphanumeric() || c == '_');
+                if !preceded {
+                    if found.map_or(true, |f| start < f.0) {
+                        found = Some((start, name));
+                    }
+                    break;
+                }
+                offset = start + name.len();
+            }
+        }
+
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn effective_rate_monthly() {
+        assert_eq!(
+            RateUtility::effective_rate(dec!(12.0), "1-month").map(|o| o.round_dp(4)),
+            Some(dec!(12.6825))
+        );
+        assert_eq!(RateUtility::effective_rate(dec!(12.0), "1-fortnight"), None);
+    }
+
+    #[test]
+    fn nominal_rate_round_trips() {
+        let effective = RateUtility::effective_rate(dec!(6.0), "3-months").unwrap();
+        assert_eq!(
+            RateUtility::nominal_rate(effective, "3-months").map(|o| o.round_dp(6)),
+            Some(dec!(6.000000))
+        );
+    }
+
+    #[test]
+    fn convert_rate_continuous() {
+        assert_eq!(
+            RateUtility::effective_rate(dec!(5.0), "continuous").map(|o| o.round_dp(4)),
+            Some(dec!(5.1271))
+        );
+        assert_eq!(
+            RateUtility::convert_rate(dec!(5.0), "continuous", "continuous").map(|o| o.round_dp(6)),
+            Some(dec!(5.000000))
+        );
+    }
+
+    #[test]
+    fn expand_expression_replaces_functions() {
+        let eval = |o: &str| String::from(o.trim());
+        let expanded =
+            RateUtility::expand_expression("1 + rateeffective(12, \"1-month\") * 2", &eval)
+                .unwrap();
+        assert!(expanded.starts_with("1 + 12.6825"));
+        assert!(expanded.ends_with(" * 2"));
+        assert_eq!(
+            RateUtility::expand_expression("x + 1", &eval),
+            Some(String::from("x + 1"))
+        );
+    }
+
+    #[test]
+    fn expand_expression_fails_on_invalid_arguments() {
+        let eval = |o: &str| String::from(o.trim());
+        assert_eq!(
+            RateUtility::expand_expression("rateeffective(12, \"1-fortnight\")", &eval),
+            None
+        );
+        assert_eq!(
+            RateUtility::expand_expression("ratenominal(abc, \"1-month\")", &eval),
+            None
+        );
+        assert_eq!(
+            RateUtility::expand_expression("rateconvert(12, \"1-month\")", &eval),
+            None
+        );
+    }
+}
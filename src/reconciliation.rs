@@ -0,0 +1,269 @@
+//! Reconciliation of actual payments against the projected schedule.
+// Copyright (c) 2021 ShiftLeft Software
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use rust_decimal::prelude::*;
+
+use amfnengine::*;
+
+use crate::daily_interest::*;
+use crate::delinquency::*;
+use crate::effective_interest::*;
+
+/// Reconciliation status of a scheduled payment.
+#[derive(Clone, Copy, PartialEq)]
+pub enum ReconcileStatus {
+    /// Not yet due and not paid in full.
+    Upcoming,
+    /// Paid in full.
+    Paid,
+    /// Paid in full on or before the prior scheduled payment date.
+    Early,
+    /// Past due and paid in part.
+    Partial,
+    /// Past due and not paid.
+    Missed,
+}
+
+/// Reconciliation row (one for each scheduled payment).
+pub struct ElemReconciliationRow {
+    /// Date of the scheduled payment (YYYYMMDD).
+    event_date: usize,
+    /// Scheduled payment amount.
+    scheduled: Decimal,
+    /// Actual amount applied to the scheduled payment.
+    actual: Decimal,
+    /// Date the scheduled payment was paid in full (zero if not).
+    paid_date: usize,
+    /// Cumulative scheduled payments through the date.
+    cumulative_scheduled: Decimal,
+    /// Cumulative actual payments received through the date.
+    cumulative_actual: Decimal,
+    /// Reconciliation status.
+    status: ReconcileStatus,
+}
+
+/// Reconciliation row implementation.
+impl ElemReconciliationRow {
+    /// Get the date of the scheduled payment.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn event_date(&self) -> usize {
+        self.event_date
+    }
+
+    /// Get the scheduled payment amount.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn scheduled(&self) -> Decimal {
+        self.scheduled
+    }
+
+    /// Get the actual amount applied.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn actual(&self) -> Decimal {
+        self.actual
+    }
+
+    /// Get the variance of the actual amount applied from the scheduled amount.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn variance(&self) -> Decimal {
+        self.actual - self.scheduled
+    }
+
+    /// Get the date the scheduled payment was paid in full.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn paid_date(&self) -> usize {
+        self.paid_date
+    }
+
+    /// Get the cumulative scheduled payments.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn cumulative_scheduled(&self) -> Decimal {
+        self.cumulative_scheduled
+    }
+
+    /// Get the cumulative actual payments received.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn cumulative_actual(&self) -> Decimal {
+        self.cumulative_actual
+    }
+
+    /// Get the cumulative variance of the actual payments
+    /// received from the scheduled payments.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn cumulative_variance(&self) -> Decimal {
+        self.cumulative_actual - self.cumulative_scheduled
+    }
+
+    /// Get the reconciliation status.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn status(&self) -> ReconcileStatus {
+        self.status
+    }
+}
+
+/// Reconciliation utility.
+pub struct ReconciliationUtility {}
+
+/// Reconciliation utility implementation.
+impl ReconciliationUtility {
+    /// Return the mnemonic of a reconciliation status.
+    ///
+    /// # Arguments
+    ///
+    /// * `status` - The reconciliation status.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn status_mnemonic(status: ReconcileStatus) -> &'static str {
+        match status {
+            ReconcileStatus::Upcoming => "upcoming",
+            ReconcileStatus::Paid => "paid",
+            ReconcileStatus::Early => "early",
+            ReconcileStatus::Partial => "partial",
+            ReconcileStatus::Missed => "missed",
+        }
+    }
+
+    /// Reconcile the actual payments received on or before a date
+    /// with each scheduled payment. The actual payments are applied
+    /// to the oldest scheduled payments first (see
+    /// DelinquencyUtility::records) and the cumulative amounts compare
+    /// everything scheduled and received through each scheduled date.
+    ///
+    /// # Arguments
+    ///
+    /// * `flows` - The scheduled cash flows (see ElemCashFlow::from_rows).
+    /// * `payments` - The actual payments sorted by date.
+    /// * `as_of` - The as of date (YYYYMMDD).
+    /// * `decimal_digits` - Number of decimal digits for rounding.
+    ///
+    /// # Return
+    ///
+    /// * One row for each scheduled payment.
+
+    pub fn reconcile(
+        flows: &[ElemCashFlow],
+        payments: &[ElemActualPayment],
+        as_of: usize,
+        decimal_digits: u32,
+    ) -> Vec<ElemReconciliationRow> {
+        let records = DelinquencyUtility::records(
+            flows,
+            payments,
+            &ElemDelinquencyRules::new(),
+            as_of,
+            decimal_digits,
+        );
+
+        let mut cumulative_scheduled = dec!(0.0);
+        let mut prior_date: usize = flows.first().map_or(0, |o| o.event_date());
+        let mut result: Vec<ElemReconciliationRow> = Vec::new();
+
+        for record in records.iter() {
+            cumulative_scheduled += record.amount_due();
+            let cumulative_actual: Decimal = payments
+                .iter()
+                .filter(|o| o.event_date() <= record.due_date().min(as_of))
+                .map(|o| o.amount())
+                .sum();
+
+            let status = if record.paid_date() > 0 {
+                if record.paid_date() <= prior_date {
+                    ReconcileStatus::Early
+                } else {
+                    ReconcileStatus::Paid
+                }
+            } else if record.due_date() >= as_of {
+                ReconcileStatus::Upcoming
+            } else if record.amount_paid() > dec!(0.0) {
+                ReconcileStatus::Partial
+            } else {
+                ReconcileStatus::Missed
+            };
+
+            result.push(ElemReconciliationRow {
+                event_date: record.due_date(),
+                scheduled: record.amount_due(),
+                actual: record.amount_paid(),
+                paid_date: record.paid_date(),
+                cumulative_scheduled,
+                cumulative_actual,
+                status,
+            });
+
+            prior_date = record.due_date();
+        }
+
+        result
+    }
+
+    /// Return the actual payments received on or before a date that
+    /// exceed all of the scheduled payments.
+    ///
+    /// # Arguments
+    ///
+    /// * `rows` - The reconciliation rows (see reconcile).
+    /// * `payments` - The actual payments sorted by date.
+    /// * `as_of` - The as of date (YYYYMMDD).
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn unapplied(
+        rows: &[ElemReconciliationRow],
+        payments: &[ElemActualPayment],
+        as_of: usize,
+    ) -> Decimal {
+        let received: Decimal = payments
+            .iter()
+            .filter(|o| o.event_date() <= as_of && o.amount() > dec!(0.0))
+            .map(|o| o.amount())
+            .sum();
+
+        received - rows.iter().map(|o| o.actual()).sum::<Decimal>()
+    }
+}
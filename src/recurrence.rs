@@ -0,0 +1,764 @@
+//! Recurrence rule (RFC 5545 RRULE subset) event schedules.
+// Copyright (c) 2021 ShiftLeft Software
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use chrono::{Datelike, Duration, NaiveDate};
+
+use crate::date_utility::*;
+
+/// Event descriptor group for a recurrence schedule.
+pub const GROUP_SCHEDULE: &str = "Schedule";
+/// Event descriptor name for the recurrence rule.
+pub const NAME_SCHEDULE_RULE: &str = "RRule";
+/// Event column (and set_event_value code) for the recurrence rule.
+pub const COL_SCHEDULE: &str = "Schedule";
+
+/// Maximum number of dates expanded from a recurrence rule.
+pub const MAX_RECURRENCE_DATES: usize = 5000;
+/// Maximum number of rule periods searched for the dates.
+const MAX_RECURRENCE_PERIODS: usize = 50000;
+/// Maximum number of event periods of a recurrence schedule.
+pub const MAX_SCHEDULE_PERIODS: usize = 20000;
+
+/// Event frequencies (coarsest first) tried when expanding
+/// a recurrence rule into an event.
+pub const SCHEDULE_FREQUENCIES: [&str; 11] = [
+    "1-year",
+    "6-months",
+    "4-months",
+    "3-months",
+    "2-months",
+    "1-month",
+    "half-month",
+    "4-weeks",
+    "2-weeks",
+    "1-week",
+    "1-day",
+];
+
+/// Weekday abbreviations (ISO order).
+const WEEKDAYS: [&str; 7] = ["MO", "TU", "WE", "TH", "FR", "SA", "SU"];
+
+/// Recurrence rule frequency.
+#[derive(Clone, Copy, PartialEq)]
+pub enum RecurrenceFreq {
+    /// Every day.
+    Daily,
+    /// Every week (weeks start on Monday).
+    Weekly,
+    /// Every month.
+    Monthly,
+    /// Every year.
+    Yearly,
+}
+
+/// Recurrence rule (FREQ, INTERVAL, COUNT, UNTIL, BYMONTH, BYMONTHDAY,
+/// BYDAY and BYSETPOS of RFC 5545 with the start date as DTSTART).
+#[derive(Clone)]
+pub struct ElemRecurrence {
+    /// Start date (YYYYMMDD; zero for the event date).
+    start_date: usize,
+    /// Frequency.
+    freq: RecurrenceFreq,
+    /// Number of frequency units between periods.
+    interval: usize,
+    /// Number of dates (zero for none).
+    count: usize,
+    /// Last date (YYYYMMDD; zero for none).
+    until: usize,
+    /// Months of the year (1 to 12).
+    by_month: Vec<u32>,
+    /// Days of the month (negative from the end of the month).
+    by_month_day: Vec<i32>,
+    /// Ordinal (zero for every, negative from the end) and
+    /// weekday (1 for Monday to 7 for Sunday).
+    by_day: Vec<(i32, u32)>,
+    /// Positions within the dates of each period (negative from the end).
+    by_set_pos: Vec<i32>,
+}
+
+/// Recurrence rule implementation.
+impl ElemRecurrence {
+    /// Parse a recurrence rule (e.g., "FREQ=MONTHLY;BYMONTHDAY=15,-1;COUNT=24"
+    /// for the 15th and last day of each month or
+    /// "FREQ=MONTHLY;INTERVAL=3;BYDAY=2TU;UNTIL=20301231" for the second
+    /// Tuesday quarterly). An optional "RRULE:" prefix is ignored and
+    /// DTSTART=YYYYMMDD sets the start date.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The recurrence rule.
+    ///
+    /// # Return
+    ///
+    /// * See description or None if the rule is invalid.
+
+    pub fn parse(value: &str) -> Option<ElemRecurrence> {
+        let value = value.trim();
+        let value = if value.to_uppercase().starts_with("RRULE:") {
+            &value[6..]
+        } else {
+            value
+        };
+
+        let mut result = ElemRecurrence {
+            start_date: 0,
+            freq: RecurrenceFreq::Monthly,
+            interval: 1,
+            count: 0,
+            until: 0,
+            by_month: Vec::new(),
+            by_month_day: Vec::new(),
+            by_day: Vec::new(),
+            by_set_pos: Vec::new(),
+        };
+        let mut freq_seen = false;
+
+        for part in value.split(';').map(|o| o.trim()).filter(|o| !o.is_empty()) {
+            let mut tokens = part.splitn(2, '=');
+            let name = tokens.next()?.trim().to_uppercase();
+            let item = tokens.next()?.trim().to_uppercase();
+            let list = item.split(',').map(|o| o.trim());
+
+            match name.as_str() {
+                "DTSTART" => {
+                    result.start_date = ElemRecurrence::parse_date(item.as_str())?;
+                }
+                "FREQ" => {
+                    result.freq = match item.as_str() {
+                        "DAILY" => RecurrenceFreq::Daily,
+                        "WEEKLY" => RecurrenceFreq::Weekly,
+                        "MONTHLY" => RecurrenceFreq::Monthly,
+                        "YEARLY" => RecurrenceFreq::Yearly,
+                        _ => return None,
+                    };
+                    freq_seen = true;
+                }
+                "INTERVAL" => {
+                    result.interval = item.parse::<usize>().ok().filter(|o| *o > 0)?;
+                }
+                "COUNT" => {
+                    result.count = item.parse::<usize>().ok().filter(|o| *o > 0)?;
+                }
+                "UNTIL" => {
+                    result.until = ElemRecurrence::parse_date(item.as_str())?;
+                }
+                "BYMONTH" => {
+                    for o in list {
+                        result
+                            .by_month
+                            .push(o.parse::<u32>().ok().filter(|o| (1..=12).contains(o))?);
+                    }
+                }
+                "BYMONTHDAY" => {
+                    for o in list {
+                        result.by_month_day.push(
+                            o.parse::<i32>()
+                                .ok()
+                                .filter(|o| *o != 0 && (-31..=31).contains(o))?,
+                        );
+                    }
+                }
+                "BYDAY" => {
+                    for o in list {
+                        let (ordinal, weekday) = o.split_at(o.char_indices().rev().nth(1)?.0);
+                        let weekday = WEEKDAYS.iter().position(|o2| *o2 == weekday)? as u32 + 1;
+                        let ordinal = if ordinal.is_empty() {
+                            0
+                        } else {
+                            ordinal
+                                .trim_start_matches('+')
+                                .parse::<i32>()
+                                .ok()
+                                .filter(|o| *o != 0 && (-53..=53).contains(o))?
+                        };
+                        result.by_day.push((ordinal, weekday));
+                    }
+                }
+                "BYSETPOS" => {
+                    for o in list {
+                        result
+                            .by_set_pos
+                            .push(o.parse::<i32>().ok().filter(|o| *o != 0)?);
+                    }
+                }
+                "WKST" if item == "MO" => {}
+                _ => return None,
+            }
+        }
+
+        if !freq_seen || (result.count > 0 && result.until > 0) {
+            return None;
+        }
+
+        Some(result)
+    }
+
+    /// Parse a YYYYMMDD (or YYYY-MM-DD) date.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The date.
+    ///
+    /// # Return
+    ///
+    /// * See description or None if the date is invalid.
+
+    fn parse_date(value: &str) -> Option<usize> {
+        let digits: String = value
+            .chars()
+            .take_while(|o| *o != 'T')
+            .filter(|o| *o != '-')
+            .collect();
+        let date = digits.parse::<usize>().ok()?;
+
+        DateUtility::to_naive(date).map(|_o| date)
+    }
+
+    /// Return the descriptor value of the recurrence rule.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn descriptor_value(&self) -> String {
+        let join = |list: Vec<String>| list.join(",");
+        let mut parts: Vec<String> = Vec::new();
+
+        if self.start_date > 0 {
+            parts.push(format!("DTSTART={}", self.start_date));
+        }
+        parts.push(format!(
+            "FREQ={}",
+            match self.freq {
+                RecurrenceFreq::Daily => "DAILY",
+                RecurrenceFreq::Weekly => "WEEKLY",
+                RecurrenceFreq::Monthly => "MONTHLY",
+                RecurrenceFreq::Yearly => "YEARLY",
+            }
+        ));
+        if self.interval > 1 {
+            parts.push(format!("INTERVAL={}", self.interval));
+        }
+        if !self.by_month.is_empty() {
+            parts.push(format!(
+                "BYMONTH={}",
+                join(self.by_month.iter().map(|o| o.to_string()).collect())
+            ));
+        }
+        if !self.by_month_day.is_empty() {
+            parts.push(format!(
+                "BYMONTHDAY={}",
+                join(self.by_month_day.iter().map(|o| o.to_string()).collect())
+            ));
+        }
+        if !self.by_day.is_empty() {
+            parts.push(format!(
+                "BYDAY={}",
+                join(
+                    self.by_day
+                        .iter()
+                        .map(|(ordinal, weekday)| {
+                            let ordinal = if *ordinal == 0 {
+                                String::from("")
+                            } else {
+                                ordinal.to_string()
+                            };
+                            format!("{}{}", ordinal, WEEKDAYS[*weekday as usize - 1])
+                        })
+                        .collect()
+                )
+            ));
+        }
+        if !self.by_set_pos.is_empty() {
+            parts.push(format!(
+                "BYSETPOS={}",
+                join(self.by_set_pos.iter().map(|o| o.to_string()).collect())
+            ));
+        }
+        if self.count > 0 {
+            parts.push(format!("COUNT={}", self.count));
+        }
+        if self.until > 0 {
+            parts.push(format!("UNTIL={}", self.until));
+        }
+
+        parts.join(";")
+    }
+
+    /// Get the start date.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn start_date(&self) -> usize {
+        self.start_date
+    }
+
+    /// Set the start date.
+    ///
+    /// # Arguments
+    ///
+    /// * `start_date` - The start date (YYYYMMDD).
+
+    pub fn set_start_date(&mut self, start_date: usize) {
+        self.start_date = start_date;
+    }
+
+    /// Get the frequency.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn freq(&self) -> RecurrenceFreq {
+        self.freq
+    }
+
+    /// Get the number of dates (zero for none).
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Set the number of dates.
+    ///
+    /// # Arguments
+    ///
+    /// * `count` - The number of dates.
+
+    pub fn set_count(&mut self, count: usize) {
+        self.count = count;
+    }
+
+    /// Get the last date (zero for none).
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn until(&self) -> usize {
+        self.until
+    }
+
+    /// Expand the recurrence rule into dates.
+    ///
+    /// # Arguments
+    ///
+    /// * `default_count` - The number of dates if the rule
+    ///   has neither a COUNT nor an UNTIL.
+    ///
+    /// # Return
+    ///
+    /// * The dates (YYYYMMDD) in ascending order.
+
+    pub fn dates(&self, default_count: usize) -> Vec<usize> {
+        let mut result: Vec<usize> = Vec::new();
+        let start = match DateUtility::to_naive(self.start_date) {
+            None => return result,
+            Some(o) => o,
+        };
+
+        let count = if self.count > 0 {
+            self.count
+        } else if self.until > 0 {
+            MAX_RECURRENCE_DATES
+        } else {
+            default_count.min(MAX_RECURRENCE_DATES)
+        };
+
+        for period in 0..MAX_RECURRENCE_PERIODS {
+            let candidates = match self.period_dates(start, period) {
+                None => break,
+                Some(o) => o,
+            };
+
+            for date in candidates.iter().filter(|o| **o >= start) {
+                let date = DateUtility::from_naive(*date);
+                if (self.until > 0 && date > self.until) || result.len() >= count {
+                    return result;
+                }
+                result.push(date);
+            }
+        }
+
+        result
+    }
+
+    /// Return the candidate dates of a period of the rule
+    /// (after BYSETPOS is applied).
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The start date.
+    /// * `period` - The zero based period.
+    ///
+    /// # Return
+    ///
+    /// * See description or None if the period is out of range.
+
+    fn period_dates(&self, start: NaiveDate, period: usize) -> Option<Vec<NaiveDate>> {
+        let offset = (period * self.interval) as i64;
+
+        let mut dates: Vec<NaiveDate> = match self.freq {
+            RecurrenceFreq::Daily => {
+                let date = start.checked_add_signed(Duration::days(offset))?;
+                if (self.by_month_day.is_empty()
+                    || ElemRecurrence::matches_month_day(date, &self.by_month_day))
+                    && (self.by_day.is_empty()
+                        || self
+                            .by_day
+                            .iter()
+                            .any(|o| o.1 == date.weekday().number_from_monday()))
+                {
+                    vec![date]
+                } else {
+                    Vec::new()
+                }
+            }
+            RecurrenceFreq::Weekly => {
+                let week = start
+                    .checked_sub_signed(Duration::days(
+                        start.weekday().num_days_from_monday() as i64
+                    ))?
+                    .checked_add_signed(Duration::weeks(offset))?;
+                (0..7)
+                    .filter_map(|o| week.checked_add_signed(Duration::days(o)))
+                    .filter(|o| {
+                        if self.by_day.is_empty() {
+                            o.weekday() == start.weekday()
+                        } else {
+                            self.by_day
+                                .iter()
+                                .any(|o2| o2.1 == o.weekday().number_from_monday())
+                        }
+                    })
+                    .collect()
+            }
+            RecurrenceFreq::Monthly => {
+                let month = start.year() as i64 * 12 + start.month0() as i64 + offset;
+                let first = NaiveDate::from_ymd_opt(
+                    month.div_euclid(12) as i32,
+                    month.rem_euclid(12) as u32 + 1,
+                    1,
+                )?;
+                self.month_dates(first, start)
+            }
+            RecurrenceFreq::Yearly => {
+                let year = start.year() + offset as i32;
+                NaiveDate::from_ymd_opt(year, 1, 1)?;
+                if self.by_month.is_empty()
+                    && !self.by_day.is_empty()
+                    && self.by_month_day.is_empty()
+                {
+                    ElemRecurrence::weekday_dates(
+                        NaiveDate::from_ymd_opt(year, 1, 1)?,
+                        NaiveDate::from_ymd_opt(year, 12, 31)?,
+                        &self.by_day,
+                    )
+                } else {
+                    let months: Vec<u32> = if !self.by_month.is_empty() {
+                        self.by_month.clone()
+                    } else if !self.by_month_day.is_empty() {
+                        (1..=12).collect()
+                    } else {
+                        vec![start.month()]
+                    };
+                    let mut dates: Vec<NaiveDate> = Vec::new();
+                    for month in months {
+                        dates.extend(
+                            self.month_dates(NaiveDate::from_ymd_opt(year, month, 1)?, start),
+                        );
+                    }
+                    dates
+                }
+            }
+        };
+
+        if !self.by_month.is_empty() {
+            dates.retain(|o| self.by_month.contains(&o.month()));
+        }
+        dates.sort();
+        dates.dedup();
+
+        if !self.by_set_pos.is_empty() {
+            let len = dates.len() as i32;
+            let mut positions: Vec<NaiveDate> = self
+                .by_set_pos
+                .iter()
+                .filter_map(|o| {
+                    let index = if *o > 0 { *o - 1 } else { len + *o };
+                    if index >= 0 && index < len {
+                        Some(dates[index as usize])
+                    } else {
+                        None
+                    }
+                })
+                .collect();
+            positions.sort();
+            positions.dedup();
+            dates = positions;
+        }
+
+        Some(dates)
+    }
+
+    /// Return the candidate dates of a month.
+    ///
+    /// # Arguments
+    ///
+    /// * `first` - The first day of the month.
+    /// * `start` - The start date (whose day is used if there
+    ///   are no day rules).
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    fn month_dates(&self, first: NaiveDate, start: NaiveDate) -> Vec<NaiveDate> {
+        let last_day = DateUtility::days_in_month(first.year(), first.month());
+        let last = first.with_day(last_day).unwrap_or(first);
+
+        if self.by_day.is_empty() {
+            if self.by_month_day.is_empty() {
+                return first.with_day(start.day()).into_iter().collect();
+            }
+            return (1..=last_day)
+                .filter_map(|o| first.with_day(o))
+                .filter(|o| ElemRecurrence::matches_month_day(*o, &self.by_month_day))
+                .collect();
+        }
+
+        ElemRecurrence::weekday_dates(first, last, &self.by_day)
+            .into_iter()
+            .filter(|o| {
+                self.by_month_day.is_empty()
+                    || ElemRecurrence::matches_month_day(*o, &self.by_month_day)
+            })
+            .collect()
+    }
+
+    /// Return the dates within a range that match weekday rules.
+    ///
+    /// # Arguments
+    ///
+    /// * `first` - The first date of the range.
+    /// * `last` - The last date of the range.
+    /// * `by_day` - The ordinals and weekdays.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    fn weekday_dates(first: NaiveDate, last: NaiveDate, by_day: &[(i32, u32)]) -> Vec<NaiveDate> {
+        let mut result: Vec<NaiveDate> = Vec::new();
+
+        for (ordinal, weekday) in by_day.iter() {
+            let matches: Vec<NaiveDate> = first
+                .iter_days()
+                .take_while(|o| *o <= last)
+                .filter(|o| o.weekday().number_from_monday() == *weekday)
+                .collect();
+            let len = matches.len() as i32;
+
+            if *ordinal == 0 {
+                result.extend(matches);
+            } else {
+                let index = if *ordinal > 0 {
+                    *ordinal - 1
+                } else {
+                    len + *ordinal
+                };
+                if index >= 0 && index < len {
+                    result.push(matches[index as usize]);
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Determine whether a date matches days of the month.
+    ///
+    /// # Arguments
+    ///
+    /// * `date` - The date.
+    /// * `by_month_day` - The days of the month (negative from the end).
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    fn matches_month_day(date: NaiveDate, by_month_day: &[i32]) -> bool {
+        let last_day = DateUtility::days_in_month(date.year(), date.month()) as i32;
+        let day = date.day() as i32;
+
+        by_month_day
+            .iter()
+            .any(|o| *o == day || *o == day - last_day - 1)
+    }
+}
+
+/// Recurrence schedule utility.
+pub struct RecurrenceUtility {}
+
+/// Recurrence schedule utility implementation.
+impl RecurrenceUtility {
+    /// Create the skip periods mask that reduces the dates of a
+    /// regular frequency to the dates of a recurrence rule.
+    ///
+    /// # Arguments
+    ///
+    /// * `dates` - The recurrence rule dates.
+    /// * `grid` - The dates of the regular frequency (starting with
+    ///   the first recurrence rule date).
+    ///
+    /// # Return
+    ///
+    /// * See description or None if the regular frequency
+    ///   misses any of the recurrence rule dates.
+
+    pub fn mask(dates: &[usize], grid: &[usize]) -> Option<String> {
+        if dates.iter().any(|o| grid.binary_search(o).is_err()) {
+            return None;
+        }
+
+        Some(
+            grid.iter()
+                .map(|o| {
+                    if dates.binary_search(o).is_ok() {
+                        '0'
+                    } else {
+                        '1'
+                    }
+                })
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dates(rule: &str, default_count: usize) -> Vec<usize> {
+        ElemRecurrence::parse(rule).unwrap().dates(default_count)
+    }
+
+    #[test]
+    fn parse_rejects_invalid_rules() {
+        for rule in [
+            "",
+            "COUNT=3",
+            "FREQ=HOURLY",
+            "FREQ=MONTHLY;COUNT=2;UNTIL=20250101",
+            "FREQ=MONTHLY;INTERVAL=0",
+            "FREQ=MONTHLY;BYMONTHDAY=0",
+            "FREQ=MONTHLY;BYMONTH=13",
+            "FREQ=MONTHLY;BYDAY=XX",
+            "FREQ=MONTHLY;BYDAY=0MO",
+            "FREQ=MONTHLY;UNTIL=20240230",
+            "FREQ=MONTHLY;BYHOUR=9",
+        ]
+        .iter()
+        {
+            assert!(ElemRecurrence::parse(rule).is_none(), "{}", rule);
+        }
+    }
+
+    #[test]
+    fn parse_normalizes_descriptor_value() {
+        let rule =
+            ElemRecurrence::parse("RRULE:freq=monthly;interval=3;byday=+2tu;until=2030-12-31")
+                .unwrap();
+        assert_eq!(
+            rule.descriptor_value(),
+            "FREQ=MONTHLY;INTERVAL=3;BYDAY=2TU;UNTIL=20301231"
+        );
+        assert_eq!(rule.until(), 20301231);
+
+        let rule =
+            ElemRecurrence::parse("DTSTART=20240101;FREQ=WEEKLY;BYDAY=MO,-1FR;COUNT=4").unwrap();
+        assert_eq!(
+            rule.descriptor_value(),
+            "DTSTART=20240101;FREQ=WEEKLY;BYDAY=MO,-1FR;COUNT=4"
+        );
+        assert_eq!(rule.start_date(), 20240101);
+        assert_eq!(rule.count(), 4);
+    }
+
+    #[test]
+    fn monthly_days_of_month() {
+        assert_eq!(
+            dates("DTSTART=20240101;FREQ=MONTHLY;BYMONTHDAY=15,-1;COUNT=4", 0),
+            vec![20240115, 20240131, 20240215, 20240229]
+        );
+    }
+
+    #[test]
+    fn monthly_ordinal_weekday() {
+        assert_eq!(
+            dates(
+                "DTSTART=20240101;FREQ=MONTHLY;INTERVAL=3;BYDAY=2TU;COUNT=3",
+                0
+            ),
+            vec![20240109, 20240409, 20240709]
+        );
+    }
+
+    #[test]
+    fn monthly_last_weekday_by_set_pos() {
+        assert_eq!(
+            dates(
+                "DTSTART=20240101;FREQ=MONTHLY;BYDAY=MO,TU,WE,TH,FR;BYSETPOS=-1;COUNT=3",
+                0
+            ),
+            vec![20240131, 20240229, 20240329]
+        );
+    }
+
+    #[test]
+    fn weekly_until() {
+        assert_eq!(
+            dates("DTSTART=20240103;FREQ=WEEKLY;BYDAY=MO,FR;UNTIL=20240112", 0),
+            vec![20240105, 20240108, 20240112]
+        );
+    }
+
+    #[test]
+    fn yearly_skips_missing_days() {
+        assert_eq!(
+            dates("DTSTART=20240229;FREQ=YEARLY;COUNT=2", 0),
+            vec![20240229, 20280229]
+        );
+    }
+
+    #[test]
+    fn default_count_and_start_date() {
+        assert_eq!(
+            dates("DTSTART=20240101;FREQ=DAILY;INTERVAL=2", 3),
+            vec![20240101, 20240103, 20240105]
+        );
+        assert!(dates("FREQ=DAILY", 3).is_empty());
+    }
+
+    #[test]
+    fn mask_of_grid() {
+        let grid = vec![20240115, 20240215, 20240315];
+        assert_eq!(
+            RecurrenceUtility::mask(&[20240115, 20240315], &grid),
+            Some(String::from("010"))
+        );
+        assert_eq!(RecurrenceUtility::mask(&[20240120], &grid), None);
+    }
+}
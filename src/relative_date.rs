@@ -0,0 +1,253 @@
+//! Event dates relative to other events.
+// Copyright (c) 2021 ShiftLeft Software
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::recurrence::*;
+
+/// Event descriptor group for a relative event date.
+pub const GROUP_RELATIVE_DATE: &str = "Relative-date";
+/// Event descriptor name for the relative date rule.
+pub const NAME_RELATIVE_DATE_RULE: &str = "Rule";
+
+/// Relative date error.
+#[derive(Clone, PartialEq)]
+pub enum RelativeDateError {
+    /// The rule is not anchor-name~offset~frequency.
+    InvalidRule,
+    /// The frequency of the rule is not valid.
+    InvalidFrequency(String),
+    /// No event has the anchor name.
+    MissingAnchor(String),
+    /// More than one event has the anchor name.
+    DuplicateAnchor(String),
+    /// The anchors form a cycle (event names along the cycle).
+    Cycle(Vec<String>),
+}
+
+/// Date of an event relative to the date of an anchor event.
+#[derive(Clone)]
+pub struct ElemRelativeDate {
+    /// Event name of the anchor event.
+    anchor: String,
+    /// Offset from the anchor date in intervals of the frequency.
+    offset: i32,
+    /// Frequency of the offset (e.g., "1-month").
+    frequency: String,
+}
+
+/// Relative date implementation.
+impl ElemRelativeDate {
+    /// Parse a relative date rule from its descriptor value
+    /// (anchor-name~offset~frequency, e.g., "Loan~1~1-month"
+    /// for one month after the event named Loan or "Loan~60~1-month"
+    /// for a balloon at month 60; the offset may be negative).
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The descriptor value.
+    ///
+    /// # Return
+    ///
+    /// * See description or the error.
+
+    pub fn parse(value: &str) -> Result<ElemRelativeDate, RelativeDateError> {
+        let tokens: Vec<&str> = value.split('~').map(|o| o.trim()).collect();
+        if tokens.len() != 3 || tokens[0].is_empty() {
+            return Err(RelativeDateError::InvalidRule);
+        }
+
+        let offset = match tokens[1].parse::<i32>() {
+            Err(_e) => return Err(RelativeDateError::InvalidRule),
+            Ok(o) => o,
+        };
+
+        let frequency = tokens[2].to_lowercase();
+        if !SCHEDULE_FREQUENCIES.contains(&frequency.as_str()) {
+            return Err(RelativeDateError::InvalidFrequency(String::from(tokens[2])));
+        }
+
+        Ok(ElemRelativeDate {
+            anchor: String::from(tokens[0]),
+            offset,
+            frequency,
+        })
+    }
+
+    /// Return the descriptor value of the relative date rule.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn descriptor_value(&self) -> String {
+        format!("{}~{}~{}", self.anchor, self.offset, self.frequency)
+    }
+
+    /// Get the event name of the anchor event.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn anchor(&self) -> &str {
+        self.anchor.as_str()
+    }
+
+    /// Get the offset from the anchor date in intervals of the frequency.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn offset(&self) -> i32 {
+        self.offset
+    }
+
+    /// Get the frequency of the offset.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn frequency(&self) -> &str {
+        self.frequency.as_str()
+    }
+}
+
+/// Relative date utility.
+pub struct RelativeDateUtility {}
+
+/// Relative date utility implementation.
+impl RelativeDateUtility {
+    /// Return the order in which the relative dates of events are
+    /// reflowed (every anchor event is reflowed before the events
+    /// relative to it).
+    ///
+    /// # Arguments
+    ///
+    /// * `names` - The event name of each event.
+    /// * `anchors` - The anchor name of each event (None if the
+    ///   event date is not relative).
+    ///
+    /// # Return
+    ///
+    /// * The indexes of the events with relative dates together with
+    ///   the index of their anchor events or the first error found.
+
+    pub fn order(
+        names: &[String],
+        anchors: &[Option<String>],
+    ) -> Result<Vec<(usize, usize)>, RelativeDateError> {
+        let mut anchor_indexes: Vec<Option<usize>> = Vec::new();
+        for anchor in anchors.iter() {
+            anchor_indexes.push(match anchor {
+                None => None,
+                Some(o) => Some(RelativeDateUtility::anchor_index(names, o)?),
+            });
+        }
+
+        let mut result: Vec<(usize, usize)> = Vec::new();
+        let mut done = vec![false; names.len()];
+
+        for start in 0..names.len() {
+            let mut chain: Vec<usize> = Vec::new();
+            let mut index = start;
+            while !done[index] {
+                if let Some(o) = chain.iter().position(|o| *o == index) {
+                    let mut cycle: Vec<String> =
+                        chain[o..].iter().map(|o2| names[*o2].clone()).collect();
+                    cycle.push(names[index].clone());
+                    return Err(RelativeDateError::Cycle(cycle));
+                }
+                chain.push(index);
+                match anchor_indexes[index] {
+                    None => break,
+                    Some(o) => index = o,
+                }
+            }
+
+            for index in chain.into_iter().rev() {
+                if done[index] {
+                    continue;
+                }
+                done[index] = true;
+                if let Some(o) = anchor_indexes[index] {
+                    result.push((index, o));
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Return the index of the event with an anchor name.
+    ///
+    /// # Arguments
+    ///
+    /// * `names` - The event name of each event.
+    /// * `anchor` - The anchor name.
+    ///
+    /// # Return
+    ///
+    /// * See description or the error.
+
+    fn anchor_index(names: &[String], anchor: &str) -> Result<usize, RelativeDateError> {
+        let mut matches = names
+            .iter()
+            .enumerate()
+            .filter(|(_o1, o2)| o2.as_str() == anchor)
+            .map(|(o1, _o2)| o1);
+
+        match (matches.next(), matches.next()) {
+            (None, _) => Err(RelativeDateError::MissingAnchor(String::from(anchor))),
+            (Some(o), None) => Ok(o),
+            _ => Err(RelativeDateError::DuplicateAnchor(String::from(anchor))),
+        }
+    }
+
+    /// Return the locale resource key of a relative date error.
+    ///
+    /// # Arguments
+    ///
+    /// * `error` - The relative date error.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn error_key(error: &RelativeDateError) -> &'static str {
+        match error {
+            RelativeDateError::InvalidRule => "Error_Relative_Rule",
+            RelativeDateError::InvalidFrequency(_o) => "Error_Relative_Frequency",
+            RelativeDateError::MissingAnchor(_o) => "Error_Relative_Missing",
+            RelativeDateError::DuplicateAnchor(_o) => "Error_Relative_Duplicate",
+            RelativeDateError::Cycle(_o) => "Error_Relative_Cycle",
+        }
+    }
+
+    /// Return the detail of a relative date error (appended
+    /// to the error's locale resource text).
+    ///
+    /// # Arguments
+    ///
+    /// * `error` - The relative date error.
+    ///
+    /// # Return
+    ///
+    /// * See description (empty if there is no detail).
+
+    pub fn error_detail(error: &RelativeDateError) -> String {
+        match error {
+            RelativeDateError::InvalidFrequency(o)
+            | RelativeDateError::MissingAnchor(o)
+            | RelativeDateError::DuplicateAnchor(o) => o.clone(),
+            RelativeDateError::Cycle(o) => o.join(" > "),
+            _ => String::from(""),
+        }
+    }
+}
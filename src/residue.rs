@@ -0,0 +1,175 @@
+//! Final payment rounding residue policies.
+// Copyright (c) 2021 ShiftLeft Software
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use rust_decimal::prelude::*;
+
+use crate::currency::*;
+
+/// Cashflow preferences descriptor name for the residue policy
+/// (within the rounding descriptor group).
+pub const NAME_RESIDUE_POLICY: &str = "Residue";
+
+/// Rounding residue policy.
+#[derive(Clone, Copy, PartialEq)]
+pub enum ResiduePolicy {
+    /// Leave the residue as the final balance.
+    Carry,
+    /// Adjust the last payment by the residue.
+    AdjustLast,
+    /// Adjust the first payment so that no residue remains.
+    AdjustFirst,
+    /// Spread the residue over all payments (the remainder
+    /// adjusts the last payment).
+    Spread,
+}
+
+/// Rounding residue utility.
+pub struct ResidueUtility {}
+
+/// Rounding residue utility implementation.
+impl ResidueUtility {
+    /// Parse a residue policy mnemonic.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The mnemonic (carry, last, first or spread).
+    ///
+    /// # Return
+    ///
+    /// * See description or None if not recognized.
+
+    pub fn parse_policy(value: &str) -> Option<ResiduePolicy> {
+        match value.trim().to_lowercase().as_str() {
+            "carry" => Some(ResiduePolicy::Carry),
+            "last" => Some(ResiduePolicy::AdjustLast),
+            "first" => Some(ResiduePolicy::AdjustFirst),
+            "spread" => Some(ResiduePolicy::Spread),
+            _ => None,
+        }
+    }
+
+    /// Return the mnemonic of a residue policy.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - The residue policy.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn policy_mnemonic(policy: ResiduePolicy) -> &'static str {
+        match policy {
+            ResiduePolicy::Carry => "carry",
+            ResiduePolicy::AdjustLast => "last",
+            ResiduePolicy::AdjustFirst => "first",
+            ResiduePolicy::Spread => "spread",
+        }
+    }
+
+    /// Calculate the amount added to each payment when spreading
+    /// a residue over a number of payments.
+    ///
+    /// # Arguments
+    ///
+    /// * `residue` - The residue.
+    /// * `periods` - The number of payments.
+    /// * `decimal_digits` - The number of decimal digits.
+    /// * `mode` - The rounding mode.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn spread_amount(
+        residue: Decimal,
+        periods: usize,
+        decimal_digits: u32,
+        mode: RoundingMode,
+    ) -> Decimal {
+        if periods == 0 {
+            return Decimal::zero();
+        }
+
+        CurrencyUtility::round(residue / Decimal::from(periods), decimal_digits, mode)
+    }
+
+    /// Calculate the first payment adjustment that removes a residue.
+    /// An adjustment to the first payment changes the final balance by
+    /// the adjustment grown by the interest of the remaining periods, so
+    /// the adjustment is scaled by the effect measured with a trial.
+    ///
+    /// # Arguments
+    ///
+    /// * `residue` - The residue before the trial.
+    /// * `trial` - The trial adjustment.
+    /// * `trial_residue` - The residue after the trial adjustment.
+    ///
+    /// # Return
+    ///
+    /// * See description (the trial if the effect cannot be measured).
+
+    pub fn first_adjustment(residue: Decimal, trial: Decimal, trial_residue: Decimal) -> Decimal {
+        let effect = residue - trial_residue;
+        if effect.is_zero() {
+            return trial;
+        }
+
+        trial * residue / effect
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use amfnengine::*;
+
+    #[test]
+    fn policy_mnemonics_round_trip() {
+        for value in ["carry", "last", "first", "spread"].iter() {
+            let policy = ResidueUtility::parse_policy(value).unwrap();
+            assert_eq!(ResidueUtility::policy_mnemonic(policy), *value);
+        }
+        assert!(ResidueUtility::parse_policy(" Spread ") == Some(ResiduePolicy::Spread));
+        assert!(ResidueUtility::parse_policy("middle").is_none());
+    }
+
+    #[test]
+    fn spread_amount_rounds_per_payment() {
+        assert_eq!(
+            ResidueUtility::spread_amount(dec!(0.05), 3, 2, RoundingMode::BiasUp),
+            dec!(0.02)
+        );
+        assert_eq!(
+            ResidueUtility::spread_amount(dec!(0.05), 3, 2, RoundingMode::Truncate),
+            dec!(0.01)
+        );
+        assert_eq!(
+            ResidueUtility::spread_amount(dec!(-0.05), 3, 2, RoundingMode::BiasUp),
+            dec!(-0.02)
+        );
+        assert_eq!(
+            ResidueUtility::spread_amount(dec!(0.05), 0, 2, RoundingMode::BiasUp),
+            dec!(0)
+        );
+    }
+
+    #[test]
+    fn first_adjustment_scales_by_effect() {
+        assert_eq!(
+            ResidueUtility::first_adjustment(dec!(10), dec!(1), dec!(8)),
+            dec!(5)
+        );
+        assert_eq!(
+            ResidueUtility::first_adjustment(dec!(10), dec!(1), dec!(10)),
+            dec!(1)
+        );
+    }
+}
@@ -0,0 +1,495 @@
+//! Revolving line of credit balances, limits and minimum payments.
+// Copyright (c) 2021 ShiftLeft Software
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use rust_decimal::prelude::*;
+
+use amfnengine::*;
+
+use crate::am_row::*;
+use crate::date_utility::*;
+
+/// Cashflow preferences descriptor group for a revolving line of credit.
+pub const GROUP_REVOLVING: &str = "Revolving";
+/// Cashflow preferences descriptor name for the revolving terms.
+pub const NAME_REVOLVING_TERMS: &str = "Terms";
+
+/// Minimum payment rule.
+#[derive(Clone, Copy, PartialEq)]
+pub enum MinimumPaymentType {
+    /// The interest accrued for the period.
+    InterestOnly,
+    /// A percentage of the balance (subject to a minimum amount).
+    Percent,
+}
+
+/// Revolving warning type.
+#[derive(Clone, Copy, PartialEq)]
+pub enum RevolvingWarningType {
+    /// A draw exceeds the available credit.
+    OverLimit,
+    /// A draw is made after the end of the draw period.
+    AfterDrawPeriod,
+    /// A payment is less than the minimum payment due.
+    BelowMinimum,
+}
+
+/// Revolving line of credit terms.
+#[derive(Clone, Copy)]
+pub struct ElemRevolvingTerms {
+    /// Credit limit.
+    credit_limit: Decimal,
+    /// End of the draw period (YYYYMMDD, zero if none).
+    draw_end: usize,
+    /// Minimum payment rule.
+    minimum_type: MinimumPaymentType,
+    /// Percentage of the balance for the percent rule.
+    percent: Decimal,
+    /// Minimum amount of the percent rule.
+    floor: Decimal,
+}
+
+/// Revolving line of credit terms implementation.
+impl ElemRevolvingTerms {
+    /// Parse the terms from their descriptor value (limit~draw-end~
+    /// rule~percent~floor, e.g., "50000~2032-01-01~interest-only" or
+    /// "10000~~percent~2~25").
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The descriptor value.
+    ///
+    /// # Return
+    ///
+    /// * See description or None if not valid.
+
+    pub fn parse(value: &str) -> Option<ElemRevolvingTerms> {
+        let fields: Vec<&str> = value.split('~').map(|o| o.trim()).collect();
+
+        let credit_limit = fields.first()?.parse::<Decimal>().ok()?;
+        let draw_end = match fields.get(1) {
+            None => 0,
+            Some(o) if o.is_empty() => 0,
+            Some(o) => {
+                let date = o.replace('-', "").parse::<usize>().ok()?;
+                DateUtility::to_naive(date)?;
+                date
+            }
+        };
+        let minimum_type = match fields.get(2).map(|o| o.to_lowercase()) {
+            None => MinimumPaymentType::InterestOnly,
+            Some(o) if o.is_empty() || o == "interest-only" => MinimumPaymentType::InterestOnly,
+            Some(o) if o == "percent" => MinimumPaymentType::Percent,
+            _ => return None,
+        };
+        let percent = match fields.get(3) {
+            None => dec!(0.0),
+            Some(o) => o.parse::<Decimal>().ok()?,
+        };
+        let floor = match fields.get(4) {
+            None => dec!(0.0),
+            Some(o) => o.parse::<Decimal>().ok()?,
+        };
+
+        if credit_limit <= dec!(0.0) || percent < dec!(0.0) || floor < dec!(0.0) {
+            return None;
+        }
+        if minimum_type == MinimumPaymentType::Percent && percent.is_zero() {
+            return None;
+        }
+
+        Some(ElemRevolvingTerms {
+            credit_limit,
+            draw_end,
+            minimum_type,
+            percent,
+            floor,
+        })
+    }
+
+    /// Return the descriptor value of the terms.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn descriptor_value(&self) -> String {
+        let draw_end = if self.draw_end == 0 {
+            String::from("")
+        } else {
+            DateUtility::format_date(self.draw_end)
+        };
+
+        match self.minimum_type {
+            MinimumPaymentType::InterestOnly => {
+                format!("{}~{}~interest-only", self.credit_limit, draw_end)
+            }
+            MinimumPaymentType::Percent => format!(
+                "{}~{}~percent~{}~{}",
+                self.credit_limit, draw_end, self.percent, self.floor
+            ),
+        }
+    }
+
+    /// Get the credit limit.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn credit_limit(&self) -> Decimal {
+        self.credit_limit
+    }
+
+    /// Get the end of the draw period.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn draw_end(&self) -> usize {
+        self.draw_end
+    }
+
+    /// Get the minimum payment rule.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn minimum_type(&self) -> MinimumPaymentType {
+        self.minimum_type
+    }
+
+    /// Get the percentage of the balance for the percent rule.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn percent(&self) -> Decimal {
+        self.percent
+    }
+
+    /// Get the minimum amount of the percent rule.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn floor(&self) -> Decimal {
+        self.floor
+    }
+
+    /// Calculate the minimum payment due. The payment never
+    /// exceeds the balance.
+    ///
+    /// # Arguments
+    ///
+    /// * `balance` - The balance before the payment (including
+    ///   the interest of the period).
+    /// * `interest` - The interest of the period.
+    /// * `decimal_digits` - Number of decimal digits for rounding.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn minimum_payment(
+        &self,
+        balance: Decimal,
+        interest: Decimal,
+        decimal_digits: u32,
+    ) -> Decimal {
+        if balance <= dec!(0.0) {
+            return dec!(0.0);
+        }
+
+        let payment = match self.minimum_type {
+            MinimumPaymentType::InterestOnly => interest,
+            MinimumPaymentType::Percent => (balance * self.percent / dec!(100.0))
+                .round_dp(decimal_digits)
+                .max(self.floor),
+        };
+
+        payment.min(balance)
+    }
+}
+
+/// Revolving schedule row (one for each date).
+pub struct ElemRevolvingRow {
+    /// Date of the row (YYYYMMDD).
+    event_date: usize,
+    /// Draws (advances) on the date.
+    draw: Decimal,
+    /// Payments on the date.
+    payment: Decimal,
+    /// Interest accrued through the date.
+    interest: Decimal,
+    /// Balance after the row.
+    balance: Decimal,
+    /// Credit available after the row.
+    available: Decimal,
+    /// Minimum payment due on the date (zero if no interest accrued).
+    minimum_payment: Decimal,
+}
+
+/// Revolving schedule row implementation.
+impl ElemRevolvingRow {
+    /// Get the date of the row.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn event_date(&self) -> usize {
+        self.event_date
+    }
+
+    /// Get the draws.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn draw(&self) -> Decimal {
+        self.draw
+    }
+
+    /// Get the payments.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn payment(&self) -> Decimal {
+        self.payment
+    }
+
+    /// Get the interest accrued.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn interest(&self) -> Decimal {
+        self.interest
+    }
+
+    /// Get the balance.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn balance(&self) -> Decimal {
+        self.balance
+    }
+
+    /// Get the credit available.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn available(&self) -> Decimal {
+        self.available
+    }
+
+    /// Get the minimum payment due.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn minimum_payment(&self) -> Decimal {
+        self.minimum_payment
+    }
+}
+
+/// Revolving warning.
+pub struct ElemRevolvingWarning {
+    /// Date of the warning (YYYYMMDD).
+    event_date: usize,
+    /// Warning type.
+    warning_type: RevolvingWarningType,
+    /// Amount over the limit, drawn after the draw period
+    /// or short of the minimum payment.
+    amount: Decimal,
+}
+
+/// Revolving warning implementation.
+impl ElemRevolvingWarning {
+    /// Get the date of the warning.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn event_date(&self) -> usize {
+        self.event_date
+    }
+
+    /// Get the warning type.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn warning_type(&self) -> RevolvingWarningType {
+        self.warning_type
+    }
+
+    /// Get the amount of the warning.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn amount(&self) -> Decimal {
+        self.amount
+    }
+}
+
+/// Revolving line of credit utility.
+pub struct RevolvingUtility {}
+
+/// Revolving line of credit utility implementation.
+impl RevolvingUtility {
+    /// Return the mnemonic of a warning type.
+    ///
+    /// # Arguments
+    ///
+    /// * `warning_type` - The warning type.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn warning_mnemonic(warning_type: RevolvingWarningType) -> &'static str {
+        match warning_type {
+            RevolvingWarningType::OverLimit => "over-limit",
+            RevolvingWarningType::AfterDrawPeriod => "after-draw-period",
+            RevolvingWarningType::BelowMinimum => "below-minimum",
+        }
+    }
+
+    /// Group the principal changes and interest of amortization rows
+    /// into dated draws, payments and interest with the resulting
+    /// balance. The first principal change is a draw, so draws are the
+    /// principal changes in its direction and payments the changes in
+    /// the opposite direction (e.g., principal decreases are draws
+    /// for the loan templates).
+    ///
+    /// # Arguments
+    ///
+    /// * `rows` - The expanded amortization rows.
+    ///
+    /// # Return
+    ///
+    /// * One row for each date (without the available credit
+    ///   and minimum payment).
+
+    pub fn activity(rows: &[ElemAmRow]) -> Vec<ElemRevolvingRow> {
+        let draws_decrease = rows
+            .iter()
+            .find(|o| !o.principal_decrease().is_zero() || !o.principal_increase().is_zero())
+            .map_or(true, |o| !o.principal_decrease().is_zero());
+
+        let mut result: Vec<ElemRevolvingRow> = Vec::new();
+        for row in rows.iter() {
+            let (draw, payment) = if draws_decrease {
+                (row.principal_decrease(), row.principal_increase())
+            } else {
+                (row.principal_increase(), row.principal_decrease())
+            };
+            match result.last_mut() {
+                Some(o) if o.event_date == row.event_date() => {
+                    o.draw += draw;
+                    o.payment += payment;
+                    o.interest += row.interest().abs();
+                }
+                _ => result.push(ElemRevolvingRow {
+                    event_date: row.event_date(),
+                    draw,
+                    payment,
+                    interest: row.interest().abs(),
+                    balance: dec!(0.0),
+                    available: dec!(0.0),
+                    minimum_payment: dec!(0.0),
+                }),
+            }
+        }
+
+        let mut balance = dec!(0.0);
+        for row in result.iter_mut() {
+            balance += row.draw + row.interest - row.payment;
+            row.balance = balance;
+        }
+
+        result
+    }
+
+    /// Create the revolving schedule and its warnings from amortization
+    /// rows (see activity).
+    ///
+    /// # Arguments
+    ///
+    /// * `rows` - The expanded amortization rows.
+    /// * `terms` - The revolving terms.
+    /// * `decimal_digits` - Number of decimal digits for rounding.
+    ///
+    /// # Return
+    ///
+    /// * One row for each date and the warnings.
+
+    pub fn schedule(
+        rows: &[ElemAmRow],
+        terms: &ElemRevolvingTerms,
+        decimal_digits: u32,
+    ) -> (Vec<ElemRevolvingRow>, Vec<ElemRevolvingWarning>) {
+        let mut result = RevolvingUtility::activity(rows);
+
+        let mut warnings: Vec<ElemRevolvingWarning> = Vec::new();
+        let mut balance = dec!(0.0);
+        for row in result.iter_mut() {
+            let available = (terms.credit_limit() - balance - row.interest).max(dec!(0.0));
+            if row.draw > available {
+                warnings.push(ElemRevolvingWarning {
+                    event_date: row.event_date,
+                    warning_type: RevolvingWarningType::OverLimit,
+                    amount: row.draw - available,
+                });
+            }
+            if row.draw > dec!(0.0) && terms.draw_end() > 0 && row.event_date > terms.draw_end() {
+                warnings.push(ElemRevolvingWarning {
+                    event_date: row.event_date,
+                    warning_type: RevolvingWarningType::AfterDrawPeriod,
+                    amount: row.draw,
+                });
+            }
+
+            if row.interest > dec!(0.0) {
+                row.minimum_payment =
+                    terms.minimum_payment(balance + row.interest, row.interest, decimal_digits);
+                if row.payment < row.minimum_payment {
+                    warnings.push(ElemRevolvingWarning {
+                        event_date: row.event_date,
+                        warning_type: RevolvingWarningType::BelowMinimum,
+                        amount: row.minimum_payment - row.payment,
+                    });
+                }
+            }
+
+            balance = row.balance;
+            row.available = (terms.credit_limit() - balance).max(dec!(0.0));
+        }
+
+        (result, warnings)
+    }
+}
@@ -0,0 +1,196 @@
+//! Sensitivity analysis.
+// Copyright (c) 2021 ShiftLeft Software
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use rust_decimal::prelude::*;
+
+use amfnengine::*;
+
+/// Name given to the scratch cashflows used by the analysis.
+pub const SENSITIVITY_SCRATCH_NAME: &str = "~Sensitivity";
+
+/// Maximum number of values along one axis of a grid.
+pub const SENSITIVITY_MAX_STEPS: usize = 100;
+
+/// Sensitivity output type.
+#[derive(Clone, Copy, PartialEq)]
+pub enum SensitivityOutput {
+    /// Value calculated for an event (by event index).
+    Payment(usize),
+    /// Total interest.
+    Interest,
+    /// Final balance.
+    Balance,
+}
+
+/// Sensitivity input element. An input varies the value
+/// of one event column over a range.
+pub struct ElemSensitivityInput {
+    /// Event index.
+    index: usize,
+    /// Event column name (e.g., "Value" or "Periods").
+    col_name: String,
+    /// Values of the range.
+    values: Vec<Decimal>,
+}
+
+/// Sensitivity input implementation.
+impl ElemSensitivityInput {
+    /// Parse a sensitivity input of the form
+    /// "index~column~start~end~step" (e.g., "2~Value~5.0~8.0~0.5").
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The input definition.
+    ///
+    /// # Return
+    ///
+    /// * See description or None if the definition is invalid.
+
+    pub fn parse(value: &str) -> Option<ElemSensitivityInput> {
+        let tokens: Vec<&str> = value.split('~').map(|o| o.trim()).collect();
+        if tokens.len() != 5 || tokens[1].is_empty() {
+            return None;
+        }
+
+        let index = tokens[0].parse::<usize>().ok()?;
+        let start = tokens[2].parse::<Decimal>().ok()?;
+        let end = tokens[3].parse::<Decimal>().ok()?;
+        let step = tokens[4].parse::<Decimal>().ok()?;
+
+        if step <= dec!(0.0) || end < start {
+            return None;
+        }
+
+        let mut values: Vec<Decimal> = Vec::new();
+        let mut val = start;
+        while val <= end {
+            if values.len() >= SENSITIVITY_MAX_STEPS {
+                return None;
+            }
+            values.push(val.normalize());
+            val += step;
+        }
+
+        Some(ElemSensitivityInput {
+            index,
+            col_name: String::from(tokens[1]),
+            values,
+        })
+    }
+
+    /// Get the event index.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Get the event column name.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn col_name(&self) -> &str {
+        self.col_name.as_str()
+    }
+
+    /// Get the values of the range.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn values(&self) -> &[Decimal] {
+        &self.values
+    }
+}
+
+/// Sensitivity analysis utility.
+pub struct SensitivityUtility {}
+
+/// Sensitivity analysis utility implementation.
+impl SensitivityUtility {
+    /// Parse a sensitivity output of the form "payment~index",
+    /// "interest" or "balance".
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The output definition.
+    ///
+    /// # Return
+    ///
+    /// * See description or None if the definition is invalid.
+
+    pub fn parse_output(value: &str) -> Option<SensitivityOutput> {
+        let mut tokens = value.split('~').map(|o| o.trim());
+
+        match tokens.next()?.to_lowercase().as_str() {
+            "payment" => Some(SensitivityOutput::Payment(
+                tokens.next()?.parse::<usize>().ok()?,
+            )),
+            "interest" => Some(SensitivityOutput::Interest),
+            "balance" => Some(SensitivityOutput::Balance),
+            _ => None,
+        }
+    }
+
+    /// Return a sensitivity grid as a json string with the row input
+    /// values ("row-values"), the column input values ("column-values")
+    /// and the outputs for each row ("grid"). Outputs that could not be
+    /// calculated are empty strings.
+    ///
+    /// # Arguments
+    ///
+    /// * `row_values` - The row input values.
+    /// * `column_values` - The column input values (empty for one input).
+    /// * `grid` - The outputs by row and column.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn to_json(
+        row_values: &[Decimal],
+        column_values: &[Decimal],
+        grid: &[Vec<Option<Decimal>>],
+    ) -> String {
+        let join = |values: &[Decimal]| -> String {
+            values
+                .iter()
+                .map(|o| format!("\"{}\"", o))
+                .collect::<Vec<String>>()
+                .join(",")
+        };
+
+        let rows: Vec<String> = grid
+            .iter()
+            .map(|row| {
+                let cells: Vec<String> = row
+                    .iter()
+                    .map(|o| match o {
+                        None => String::from("\"\""),
+                        Some(val) => format!("\"{}\"", val),
+                    })
+                    .collect();
+                format!("[{}]", cells.join(","))
+            })
+            .collect();
+
+        format!(
+            "{{\"row-values\":[{}],\"column-values\":[{}],\"grid\":[{}]}}",
+            join(row_values),
+            join(column_values),
+            rows.join(",")
+        )
+    }
+}
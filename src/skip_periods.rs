@@ -0,0 +1,439 @@
+//! Skip periods mask builder and validation.
+// Copyright (c) 2021 ShiftLeft Software
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use chrono::Datelike;
+
+use crate::date_utility::*;
+
+/// Month names recognized by skip periods rules.
+const MONTH_NAMES: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+/// Weekday names recognized by skip periods rules (ISO order).
+const WEEKDAY_NAMES: [&str; 7] = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
+
+/// Calendar unit selected by a skip periods rule.
+#[derive(Clone, Copy, PartialEq)]
+pub enum SkipUnit {
+    /// Months of the year (1 to 12).
+    Month,
+    /// Days of the week (1 for Monday to 7 for Sunday).
+    Weekday,
+}
+
+/// Skip periods error.
+#[derive(Clone, PartialEq)]
+pub enum SkipPeriodsError {
+    /// The rule is not pay|skip~months|weekdays~list.
+    InvalidRule,
+    /// A month of the rule is not valid.
+    InvalidMonth(String),
+    /// A weekday of the rule is not valid.
+    InvalidWeekday(String),
+    /// A character of the mask (1 based position) is not 0 or 1.
+    InvalidCharacter(usize),
+    /// The mask (length) is longer than the event periods.
+    TooLong(usize, usize),
+    /// Every period of the event is skipped.
+    AllSkipped,
+}
+
+/// Skip periods rule (e.g., pay only in harvest months).
+pub struct ElemSkipRule {
+    /// Pay only in (rather than skip) the selected months or weekdays.
+    pay: bool,
+    /// Calendar unit.
+    unit: SkipUnit,
+    /// Selected months or weekdays.
+    values: Vec<u32>,
+}
+
+/// Skip periods rule implementation.
+impl ElemSkipRule {
+    /// Parse a skip periods rule (pay|skip~months|weekdays~list, where
+    /// the list contains numbers, names or ranges, e.g., "pay~months~9-11",
+    /// "skip~months~jun,jul,aug" or "skip~weekdays~sat-sun").
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The rule.
+    ///
+    /// # Return
+    ///
+    /// * See description or the error.
+
+    pub fn parse(value: &str) -> Result<ElemSkipRule, SkipPeriodsError> {
+        let tokens: Vec<&str> = value.split('~').map(|o| o.trim()).collect();
+        if tokens.len() != 3 {
+            return Err(SkipPeriodsError::InvalidRule);
+        }
+
+        let pay = match tokens[0].to_lowercase().as_str() {
+            "pay" => true,
+            "skip" => false,
+            _ => return Err(SkipPeriodsError::InvalidRule),
+        };
+
+        let unit = match tokens[1].to_lowercase().as_str() {
+            "months" => SkipUnit::Month,
+            "weekdays" => SkipUnit::Weekday,
+            _ => return Err(SkipPeriodsError::InvalidRule),
+        };
+
+        let mut values: Vec<u32> = Vec::new();
+        for item in tokens[2].split(',').map(|o| o.trim()) {
+            let mut bounds = item.splitn(2, '-');
+            let first = ElemSkipRule::parse_value(unit, bounds.next().unwrap_or(""))?;
+            let last = match bounds.next() {
+                None => first,
+                Some(o) => ElemSkipRule::parse_value(unit, o)?,
+            };
+
+            let count = match unit {
+                SkipUnit::Month => 12,
+                SkipUnit::Weekday => 7,
+            };
+            let mut value = first;
+            loop {
+                if !values.contains(&value) {
+                    values.push(value);
+                }
+                if value == last {
+                    break;
+                }
+                value = value % count + 1;
+            }
+        }
+
+        Ok(ElemSkipRule { pay, unit, values })
+    }
+
+    /// Parse a month or weekday number or name.
+    ///
+    /// # Arguments
+    ///
+    /// * `unit` - The calendar unit.
+    /// * `value` - The number or name.
+    ///
+    /// # Return
+    ///
+    /// * See description or the error.
+
+    fn parse_value(unit: SkipUnit, value: &str) -> Result<u32, SkipPeriodsError> {
+        let value = value.trim();
+        let names: &[&str] = match unit {
+            SkipUnit::Month => &MONTH_NAMES,
+            SkipUnit::Weekday => &WEEKDAY_NAMES,
+        };
+
+        let result = match value.parse::<u32>() {
+            Ok(o) if o >= 1 && o as usize <= names.len() => Some(o),
+            Ok(_o) => None,
+            Err(_e) => {
+                let prefix: String = value.to_lowercase().chars().take(3).collect();
+                names
+                    .iter()
+                    .position(|o| value.len() >= 3 && *o == prefix)
+                    .map(|o| o as u32 + 1)
+            }
+        };
+
+        match result {
+            Some(o) => Ok(o),
+            None => Err(match unit {
+                SkipUnit::Month => SkipPeriodsError::InvalidMonth(String::from(value)),
+                SkipUnit::Weekday => SkipPeriodsError::InvalidWeekday(String::from(value)),
+            }),
+        }
+    }
+
+    /// Get whether payments are made only in the selected
+    /// months or weekdays.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn pay(&self) -> bool {
+        self.pay
+    }
+
+    /// Get the calendar unit.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn unit(&self) -> SkipUnit {
+        self.unit
+    }
+
+    /// Get the selected months or weekdays.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn values(&self) -> &[u32] {
+        &self.values
+    }
+
+    /// Determine whether the period of a date is skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `date` - The date of the period (YYYYMMDD).
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn skipped(&self, date: usize) -> bool {
+        let value = match self.unit {
+            SkipUnit::Month => (date / 100 % 100) as u32,
+            SkipUnit::Weekday => {
+                DateUtility::to_naive(date).map_or(0, |o| o.weekday().number_from_monday())
+            }
+        };
+
+        self.values.contains(&value) != self.pay
+    }
+}
+
+/// Skip periods utility.
+pub struct SkipPeriodsUtility {}
+
+/// Skip periods utility implementation.
+impl SkipPeriodsUtility {
+    /// Build the skip periods mask of an event from a rule. Each
+    /// character of the mask corresponds to a period of the event
+    /// ("1" if the period is skipped).
+    ///
+    /// # Arguments
+    ///
+    /// * `dates` - The dates of the event periods.
+    /// * `rule` - The skip periods rule.
+    ///
+    /// # Return
+    ///
+    /// * The mask (empty if no period is skipped) or the error.
+
+    pub fn build(dates: &[usize], rule: &ElemSkipRule) -> Result<String, SkipPeriodsError> {
+        let mask: String = dates
+            .iter()
+            .map(|o| if rule.skipped(*o) { '1' } else { '0' })
+            .collect();
+
+        if !mask.contains('1') {
+            return Ok(String::from(""));
+        }
+
+        SkipPeriodsUtility::validate(mask.as_str(), dates.len())?;
+
+        Ok(mask)
+    }
+
+    /// Validate a skip periods mask.
+    ///
+    /// # Arguments
+    ///
+    /// * `mask` - The skip periods mask.
+    /// * `periods` - The number of event periods.
+    ///
+    /// # Return
+    ///
+    /// * Nothing or the first error found.
+
+    pub fn validate(mask: &str, periods: usize) -> Result<(), SkipPeriodsError> {
+        if let Some(o) = mask.chars().position(|o| o != '0' && o != '1') {
+            return Err(SkipPeriodsError::InvalidCharacter(o + 1));
+        }
+
+        if mask.len() > periods {
+            return Err(SkipPeriodsError::TooLong(mask.len(), periods));
+        }
+
+        if !mask.is_empty() && !mask.contains('0') && mask.len() == periods {
+            return Err(SkipPeriodsError::AllSkipped);
+        }
+
+        Ok(())
+    }
+
+    /// Merge two skip periods masks (a period is skipped
+    /// if it is skipped by either mask).
+    ///
+    /// # Arguments
+    ///
+    /// * `mask1` - The first mask.
+    /// * `mask2` - The second mask.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn merge(mask1: &str, mask2: &str) -> String {
+        let len = mask1.len().max(mask2.len());
+        let mut chars1 = mask1.chars();
+        let mut chars2 = mask2.chars();
+
+        (0..len)
+            .map(|_o| {
+                let (ch1, ch2) = (chars1.next(), chars2.next());
+                if ch1 == Some('1') || ch2 == Some('1') {
+                    '1'
+                } else {
+                    '0'
+                }
+            })
+            .collect()
+    }
+
+    /// Return the locale resource key of a skip periods error.
+    ///
+    /// # Arguments
+    ///
+    /// * `error` - The skip periods error.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn error_key(error: &SkipPeriodsError) -> &'static str {
+        match error {
+            SkipPeriodsError::InvalidRule => "Error_Skip_Rule",
+            SkipPeriodsError::InvalidMonth(_o) => "Error_Skip_Month",
+            SkipPeriodsError::InvalidWeekday(_o) => "Error_Skip_Weekday",
+            SkipPeriodsError::InvalidCharacter(_o) => "Error_Skip_Character",
+            SkipPeriodsError::TooLong(_o1, _o2) => "Error_Skip_Length",
+            SkipPeriodsError::AllSkipped => "Error_Skip_All",
+        }
+    }
+
+    /// Return the detail of a skip periods error (appended
+    /// to the error's locale resource text).
+    ///
+    /// # Arguments
+    ///
+    /// * `error` - The skip periods error.
+    ///
+    /// # Return
+    ///
+    /// * See description (empty if there is no detail).
+
+    pub fn error_detail(error: &SkipPeriodsError) -> String {
+        match error {
+            SkipPeriodsError::InvalidMonth(o) | SkipPeriodsError::InvalidWeekday(o) => o.clone(),
+            SkipPeriodsError::InvalidCharacter(o) => o.to_string(),
+            SkipPeriodsError::TooLong(o1, o2) => format!("{} > {}", o1, o2),
+            _ => String::from(""),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_ranges_and_names() {
+        let rule = ElemSkipRule::parse("pay~months~9-11").ok().unwrap();
+        assert!(rule.pay());
+        assert!(rule.unit() == SkipUnit::Month);
+        assert_eq!(rule.values(), &[9, 10, 11]);
+
+        let rule = ElemSkipRule::parse("Skip ~ Months ~ june,JUL,aug")
+            .ok()
+            .unwrap();
+        assert!(!rule.pay());
+        assert_eq!(rule.values(), &[6, 7, 8]);
+
+        let rule = ElemSkipRule::parse("skip~weekdays~sat-mon").ok().unwrap();
+        assert!(rule.unit() == SkipUnit::Weekday);
+        assert_eq!(rule.values(), &[6, 7, 1]);
+
+        let rule = ElemSkipRule::parse("pay~months~nov-feb").ok().unwrap();
+        assert_eq!(rule.values(), &[11, 12, 1, 2]);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(ElemSkipRule::parse("pay~months").err() == Some(SkipPeriodsError::InvalidRule));
+        assert!(ElemSkipRule::parse("keep~months~1").err() == Some(SkipPeriodsError::InvalidRule));
+        assert!(ElemSkipRule::parse("pay~years~1").err() == Some(SkipPeriodsError::InvalidRule));
+        assert!(
+            ElemSkipRule::parse("pay~months~13").err()
+                == Some(SkipPeriodsError::InvalidMonth(String::from("13")))
+        );
+        assert!(
+            ElemSkipRule::parse("skip~weekdays~sa").err()
+                == Some(SkipPeriodsError::InvalidWeekday(String::from("sa")))
+        );
+    }
+
+    #[test]
+    fn build_mask_from_rule() {
+        let dates = [20240815, 20240915, 20241015, 20241115, 20241215];
+        let rule = ElemSkipRule::parse("pay~months~9-11").ok().unwrap();
+        assert_eq!(
+            SkipPeriodsUtility::build(&dates, &rule).ok(),
+            Some(String::from("10001"))
+        );
+
+        let rule = ElemSkipRule::parse("skip~months~jan").ok().unwrap();
+        assert_eq!(
+            SkipPeriodsUtility::build(&dates, &rule).ok(),
+            Some(String::from(""))
+        );
+
+        let rule = ElemSkipRule::parse("pay~months~jan").ok().unwrap();
+        assert!(
+            SkipPeriodsUtility::build(&dates, &rule).err() == Some(SkipPeriodsError::AllSkipped)
+        );
+
+        // 20240106 and 20240107 are a Saturday and Sunday.
+        let rule = ElemSkipRule::parse("skip~weekdays~sat-sun").ok().unwrap();
+        assert_eq!(
+            SkipPeriodsUtility::build(&[20240105, 20240106, 20240107, 20240108], &rule).ok(),
+            Some(String::from("0110"))
+        );
+    }
+
+    #[test]
+    fn validate_mask() {
+        assert!(SkipPeriodsUtility::validate("", 0).is_ok());
+        assert!(SkipPeriodsUtility::validate("0101", 6).is_ok());
+        assert!(
+            SkipPeriodsUtility::validate("01x1", 6).err()
+                == Some(SkipPeriodsError::InvalidCharacter(3))
+        );
+        assert!(
+            SkipPeriodsUtility::validate("0101", 3).err() == Some(SkipPeriodsError::TooLong(4, 3))
+        );
+        assert!(SkipPeriodsUtility::validate("111", 3).err() == Some(SkipPeriodsError::AllSkipped));
+        assert!(SkipPeriodsUtility::validate("111", 4).is_ok());
+    }
+
+    #[test]
+    fn merge_masks() {
+        assert_eq!(SkipPeriodsUtility::merge("1001", "01"), "1101");
+        assert_eq!(SkipPeriodsUtility::merge("", "010"), "010");
+    }
+
+    #[test]
+    fn error_keys_and_details() {
+        let error = SkipPeriodsError::TooLong(4, 3);
+        assert_eq!(SkipPeriodsUtility::error_key(&error), "Error_Skip_Length");
+        assert_eq!(SkipPeriodsUtility::error_detail(&error), "4 > 3");
+        assert_eq!(
+            SkipPeriodsUtility::error_detail(&SkipPeriodsError::AllSkipped),
+            ""
+        );
+    }
+}
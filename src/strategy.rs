@@ -0,0 +1,194 @@
+//! Extra-payment strategies.
+// Copyright (c) 2021 ShiftLeft Software
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use rust_decimal::prelude::*;
+
+use amfnengine::*;
+
+use crate::am_row::*;
+
+/// Extra-payment strategy type.
+#[derive(Clone, Copy, PartialEq)]
+pub enum StrategyType {
+    /// Fixed extra amount with each payment.
+    Fixed,
+    /// Lump sum once per year.
+    Annual,
+    /// Round each payment up to the nearest multiple of an amount.
+    RoundUp,
+    /// Convert the payment to half the payment every two weeks.
+    Biweekly,
+}
+
+/// Extra-payment strategy utility.
+pub struct StrategyUtility {}
+
+/// Extra-payment strategy utility implementation.
+impl StrategyUtility {
+    /// Parse an extra-payment strategy mnemonic.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The mnemonic (fixed, annual, roundup or biweekly).
+    ///
+    /// # Return
+    ///
+    /// * See description or None if not recognized.
+
+    pub fn parse_type(value: &str) -> Option<StrategyType> {
+        match value.trim().to_lowercase().as_str() {
+            "fixed" => Some(StrategyType::Fixed),
+            "annual" => Some(StrategyType::Annual),
+            "roundup" => Some(StrategyType::RoundUp),
+            "biweekly" => Some(StrategyType::Biweekly),
+            _ => None,
+        }
+    }
+
+    /// Calculate the extra amount paid with each payment
+    /// for a round-up strategy.
+    ///
+    /// # Arguments
+    ///
+    /// * `payment` - The regular payment.
+    /// * `nearest` - The amount to round up to.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn round_up_extra(payment: Decimal, nearest: Decimal) -> Decimal {
+        if nearest <= dec!(0.0) {
+            return dec!(0.0);
+        }
+
+        (payment / nearest).ceil() * nearest - payment
+    }
+
+    /// Calculate the number of biweekly payments that replace
+    /// a number of payments at another frequency.
+    ///
+    /// # Arguments
+    ///
+    /// * `periods` - The number of regular payments.
+    /// * `periods_in_year` - The number of regular payments per year.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn biweekly_periods(periods: usize, periods_in_year: usize) -> usize {
+        let years = periods_in_year.max(1);
+
+        (periods * 26 + years - 1) / years
+    }
+
+    /// Calculate the number of annual lump sums paid over a
+    /// number of regular payments.
+    ///
+    /// # Arguments
+    ///
+    /// * `periods` - The number of regular payments.
+    /// * `periods_in_year` - The number of regular payments per year.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn annual_periods(periods: usize, periods_in_year: usize) -> usize {
+        let years = periods_in_year.max(1);
+
+        ((periods + years - 1) / years).max(1)
+    }
+}
+
+/// Payoff metrics of an amortization schedule.
+pub struct ElemPayoffMetrics {
+    /// Total interest up to the payoff date (absolute value).
+    total_interest: Decimal,
+    /// Date the balance is paid off (or the last date).
+    payoff_date: usize,
+    /// Number of payments up to the payoff date.
+    payments: usize,
+}
+
+/// Payoff metrics implementation.
+impl ElemPayoffMetrics {
+    /// Calculate the payoff metrics from expanded amortization rows.
+    /// Rows after the balance has been paid off are not included.
+    /// The payments are the principal changes in the direction of the
+    /// payments (see ElemAmRow::payment_direction).
+    ///
+    /// # Arguments
+    ///
+    /// * `rows` - The expanded amortization rows.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn new(rows: &[ElemAmRow]) -> ElemPayoffMetrics {
+        let mut total_interest = dec!(0.0);
+        let mut payoff_date: usize = 0;
+        let mut payments: usize = 0;
+        let mut has_balance = false;
+        let direction = ElemAmRow::payment_direction(rows);
+
+        for row in rows.iter() {
+            total_interest += row.interest();
+            payoff_date = row.event_date();
+
+            if row.payment(direction) > dec!(0.0) && has_balance {
+                payments += 1;
+            }
+
+            if !row.balance().is_zero() {
+                has_balance = true;
+            } else if has_balance && payments > 0 {
+                break;
+            }
+        }
+
+        ElemPayoffMetrics {
+            total_interest: total_interest.abs(),
+            payoff_date,
+            payments,
+        }
+    }
+
+    /// Get the total interest.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn total_interest(&self) -> Decimal {
+        self.total_interest
+    }
+
+    /// Get the payoff date.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn payoff_date(&self) -> usize {
+        self.payoff_date
+    }
+
+    /// Get the number of payments.
+    ///
+    /// # Return
+    ///
+    /// * See description.
+
+    pub fn payments(&self) -> usize {
+        self.payments
+    }
+}